@@ -0,0 +1,204 @@
+//! Axis-aligned bounding boxes.
+
+use nalgebra::{Point3, Vector3};
+
+/// An axis-aligned bounding box, stored as its minimum and maximum corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    min: Point3<f32>,
+    max: Point3<f32>,
+}
+
+impl Aabb {
+    /// Creates a bounding box from its minimum and maximum corners.
+    ///
+    /// # Panics (debug builds only)
+    /// Panics if `min` is greater than `max` on any axis.
+    pub fn new(min: Point3<f32>, max: Point3<f32>) -> Self {
+        debug_assert!(
+            min.x <= max.x && min.y <= max.y && min.z <= max.z,
+            "Aabb min must not exceed max"
+        );
+        Self { min, max }
+    }
+
+    /// Computes the smallest box containing all the given points.
+    ///
+    /// Returns `None` if there are no points.
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Point3<f32>>,
+    {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, p| aabb.including(*p)))
+    }
+
+    /// Returns the minimum corner.
+    #[inline]
+    pub fn min(&self) -> Point3<f32> {
+        self.min
+    }
+
+    /// Returns the maximum corner.
+    #[inline]
+    pub fn max(&self) -> Point3<f32> {
+        self.max
+    }
+
+    /// Returns the size of the box along each axis.
+    #[inline]
+    pub fn extents(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+
+    /// Returns the center point of the box.
+    #[inline]
+    pub fn center(&self) -> Point3<f32> {
+        Point3::from((self.min.coords + self.max.coords) * 0.5)
+    }
+
+    /// Returns the total area of the box's six faces.
+    pub fn surface_area(&self) -> f32 {
+        let e = self.extents();
+        2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+    }
+
+    /// Returns the range `(min, max)` of `direction · p` over all points `p`
+    /// in the box.
+    pub fn projected_extent(&self, direction: &Vector3<f32>) -> (f32, f32) {
+        let center = self.center().coords.dot(direction);
+        let radius = (self.extents() * 0.5).dot(&direction.abs());
+        (center - radius, center + radius)
+    }
+
+    /// Returns the smallest box containing both this box and `point`.
+    pub fn including(&self, point: Point3<f32>) -> Self {
+        Self {
+            min: self.min.inf(&point),
+            max: self.max.sup(&point),
+        }
+    }
+
+    /// Returns `true` if `point` lies inside the box or on its boundary.
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    /// Returns the squared distance from `point` to the nearest point of the
+    /// box, 0 if the point is inside.
+    pub fn distance_squared_to_point(&self, point: Point3<f32>) -> f32 {
+        let nearest = point.sup(&self.min).inf(&self.max);
+        (point - nearest).norm_squared()
+    }
+
+    /// Returns `true` if the boxes share at least one point.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// Returns the smallest box containing both boxes.
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Polygon, Rectangle, Triangle};
+
+    #[test]
+    fn from_points_bounds_all_points() {
+        let points = [
+            Point3::new(1.0, -2.0, 0.5),
+            Point3::new(-1.0, 3.0, 0.0),
+            Point3::new(0.0, 0.0, 2.0),
+        ];
+        let aabb = Aabb::from_points(&points).unwrap();
+
+        assert_eq!(aabb.min(), Point3::new(-1.0, -2.0, 0.0));
+        assert_eq!(aabb.max(), Point3::new(1.0, 3.0, 2.0));
+        assert!(Aabb::from_points(&[]).is_none());
+    }
+
+    #[test]
+    fn projected_extent_along_diagonal() {
+        let aabb = Aabb::new(Point3::new(1.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        let minus_y = Vector3::new(0.0, -1.0, 0.0);
+        assert_eq!(aabb.projected_extent(&x), (1.0, 2.0));
+        assert_eq!(aabb.projected_extent(&minus_y), (-1.0, 0.0));
+        let (min, max) = aabb.projected_extent(&Vector3::new(1.0, 1.0, 0.0));
+        assert!((min - 1.0).abs() < 1e-6 && (max - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn primitives_bound_their_vertices() {
+        let triangle = Triangle::new(
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.0, 0.0, -1.0),
+        );
+        let aabb = triangle.aabb();
+        assert_eq!(aabb.min(), Point3::new(0.0, 0.0, -1.0));
+        assert_eq!(aabb.max(), Point3::new(1.0, 2.0, 0.0));
+        assert_eq!(Polygon::from(&triangle).aabb(), aabb);
+
+        let rectangle = Rectangle::new(
+            Point3::new(1.0, 1.0, 1.0),
+            Vector3::new(-2.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 3.0),
+        );
+        let aabb = rectangle.aabb();
+        assert_eq!(aabb.min(), Point3::new(-1.0, 1.0, 1.0));
+        assert_eq!(aabb.max(), Point3::new(1.0, 1.0, 4.0));
+    }
+
+    #[test]
+    fn contains_point_includes_boundary() {
+        let aabb = Aabb::new(Point3::origin(), Point3::new(2.0, 1.0, 1.0));
+
+        assert!(aabb.contains_point(Point3::new(1.0, 0.5, 0.5)));
+        assert!(aabb.contains_point(Point3::new(2.0, 1.0, 0.0)));
+        assert!(!aabb.contains_point(Point3::new(2.5, 0.5, 0.5)));
+        assert!(!aabb.contains_point(Point3::new(1.0, -0.1, 0.5)));
+    }
+
+    #[test]
+    fn intersects_overlapping_and_touching_boxes() {
+        let aabb = Aabb::new(Point3::origin(), Point3::new(2.0, 2.0, 2.0));
+
+        let overlapping = Aabb::new(Point3::new(1.0, 1.0, 1.0), Point3::new(3.0, 3.0, 3.0));
+        let touching = Aabb::new(Point3::new(2.0, 0.0, 0.0), Point3::new(3.0, 1.0, 1.0));
+        let separated = Aabb::new(Point3::new(0.0, 3.0, 0.0), Point3::new(1.0, 4.0, 1.0));
+
+        assert!(aabb.intersects(&overlapping));
+        assert!(overlapping.intersects(&aabb));
+        assert!(aabb.intersects(&touching));
+        assert!(!aabb.intersects(&separated));
+        assert!(!separated.intersects(&aabb));
+    }
+
+    #[test]
+    fn surface_area_of_box() {
+        let aabb = Aabb::new(Point3::origin(), Point3::new(1.0, 2.0, 3.0));
+        assert_eq!(aabb.surface_area(), 2.0 * (2.0 + 6.0 + 3.0));
+    }
+
+    #[test]
+    fn distance_squared_to_point_measures_to_nearest_face_or_corner() {
+        let aabb = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+
+        let distance_squared = |x, y, z| aabb.distance_squared_to_point(Point3::new(x, y, z));
+
+        assert_eq!(distance_squared(1.0, 0.5, 0.5), 0.0);
+        assert_eq!(distance_squared(1.0, 3.0, 0.5), 4.0);
+        assert_eq!(distance_squared(-1.0, 2.0, 0.5), 2.0);
+        assert_eq!(distance_squared(3.0, 2.0, 3.0), 6.0);
+    }
+}
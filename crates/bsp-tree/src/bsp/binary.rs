@@ -0,0 +1,371 @@
+//! Compact binary serialization of BSP trees.
+
+use std::io::{self, Read, Write};
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{Plane3D, Polygon};
+
+use super::node::BspNode;
+use super::tree::BspTree;
+
+/// Magic bytes at the start of a serialized [`BspTree`].
+const MAGIC: &[u8; 4] = b"BSPT";
+/// Current format version.
+const VERSION: u8 = 1;
+/// Tag for a missing child.
+const TAG_EMPTY: u8 = 0;
+/// Tag for a node.
+const TAG_NODE: u8 = 1;
+
+impl BspTree {
+    /// Writes the tree, including its polygons, in a compact binary format.
+    ///
+    /// The format is the magic bytes `BSPT`, a version byte, then the nodes in
+    /// pre-order. Each node is a tag byte (`1` for a node, `0` for a missing
+    /// child) followed, for nodes, by the plane's normal and offset as four
+    /// `f32`s, then the `coplanar_front`, `coplanar_back` and
+    /// [`leftover`](BspNode::leftover) lists. A list is its polygon count as
+    /// a `u32`, then per polygon its vertex count as a `u32` and its vertices
+    /// as `f32` triples. All numbers are little-endian.
+    ///
+    /// Node ids are reassigned on reading, and recorded splitters, cut edges
+    /// and polygon texture coordinates are not stored.
+    pub fn write_binary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_tree(&mut writer, self.root())
+    }
+
+    /// Reads a tree written by [`write_binary`](Self::write_binary).
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the data is not a
+    /// BSP tree of a supported version or describes invalid geometry.
+    pub fn read_binary<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a BSP tree"));
+        }
+        if header[4] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported BSP tree version {}", header[4]),
+            ));
+        }
+        Ok(Self::from_root(read_tree(&mut reader)?))
+    }
+}
+
+/// Writes the nodes in pre-order with an explicit stack, so that very deep
+/// trees do not overflow the call stack.
+fn write_tree<W: Write>(writer: &mut W, root: Option<&BspNode>) -> io::Result<()> {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        match node {
+            Some(node) => {
+                write_node(writer, node)?;
+                // Pushed in reverse, so the front child is written next
+                stack.push(node.back());
+                stack.push(node.front());
+            }
+            None => writer.write_all(&[TAG_EMPTY])?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes a single node without its children.
+fn write_node<W: Write>(writer: &mut W, node: &BspNode) -> io::Result<()> {
+    writer.write_all(&[TAG_NODE])?;
+    let normal = node.plane().normal();
+    write_f32s(
+        writer,
+        &[normal.x, normal.y, normal.z, node.plane().offset()],
+    )?;
+    write_polygons(writer, node.coplanar_front())?;
+    write_polygons(writer, node.coplanar_back())?;
+    write_polygons(writer, node.leftover())
+}
+
+fn write_polygons<W: Write>(writer: &mut W, polygons: &[Polygon]) -> io::Result<()> {
+    write_count(writer, polygons.len())?;
+    for polygon in polygons {
+        write_count(writer, polygon.len())?;
+        for v in polygon.vertices() {
+            write_f32s(writer, &[v.x, v.y, v.z])?;
+        }
+    }
+    Ok(())
+}
+
+fn write_count<W: Write>(writer: &mut W, count: usize) -> io::Result<()> {
+    let count = u32::try_from(count)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many elements"))?;
+    writer.write_all(&count.to_le_bytes())
+}
+
+fn write_f32s<W: Write>(writer: &mut W, values: &[f32]) -> io::Result<()> {
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Reads the nodes in pre-order with an explicit stack, so a crafted file
+/// describing a very deep tree cannot overflow the call stack.
+fn read_tree<R: Read>(reader: &mut R) -> io::Result<Option<BspNode>> {
+    // Nodes whose children are still being read, and whether their front
+    // child is already attached
+    let mut pending: Vec<(BspNode, bool)> = Vec::new();
+    loop {
+        let mut subtree = match read_node(reader)? {
+            Some(node) => {
+                pending.push((node, false));
+                continue;
+            }
+            None => None,
+        };
+        // Attach the finished subtree, completing every parent it is the
+        // back child of
+        loop {
+            match pending.pop() {
+                None => return Ok(subtree),
+                Some((mut parent, false)) => {
+                    parent.set_front(subtree);
+                    pending.push((parent, true));
+                    break;
+                }
+                Some((mut parent, true)) => {
+                    parent.set_back(subtree);
+                    subtree = Some(parent);
+                }
+            }
+        }
+    }
+}
+
+/// Reads a single node without its children, or `None` for a missing child.
+fn read_node<R: Read>(reader: &mut R) -> io::Result<Option<BspNode>> {
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    match tag[0] {
+        TAG_EMPTY => Ok(None),
+        TAG_NODE => {
+            let [x, y, z, offset] = read_f32s(reader)?;
+            let normal = Vector3::new(x, y, z);
+            let is_unit = (normal.norm() - 1.0).abs() <= 1e-3;
+            if !is_unit {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "plane normal is not unit length",
+                ));
+            }
+
+            let plane = Plane3D::from_unit_normal(normal, offset);
+            let coplanar_front = read_polygons(reader)?;
+            let coplanar_back = read_polygons(reader)?;
+            let mut node = BspNode::with_coplanar(plane, coplanar_front, coplanar_back);
+            node.set_leftover(read_polygons(reader)?);
+            Ok(Some(node))
+        }
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid node tag {other}"),
+        )),
+    }
+}
+
+fn read_polygons<R: Read>(reader: &mut R) -> io::Result<Vec<Polygon>> {
+    // Counts are not trusted for preallocation, as corrupt data could
+    // request huge buffers
+    let count = read_count(reader)?;
+    let mut polygons = Vec::new();
+    for _ in 0..count {
+        let vertex_count = read_count(reader)?;
+        if vertex_count < 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("polygon with {vertex_count} vertices"),
+            ));
+        }
+        let mut vertices = Vec::new();
+        for _ in 0..vertex_count {
+            let [x, y, z] = read_f32s(reader)?;
+            vertices.push(Point3::new(x, y, z));
+        }
+        let polygon = Polygon::try_new(vertices)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        polygons.push(polygon);
+    }
+    Ok(polygons)
+}
+
+fn read_count<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32s<R: Read, const N: usize>(reader: &mut R) -> io::Result<[f32; N]> {
+    let mut values = [0f32; N];
+    for value in &mut values {
+        let mut bytes = [0u8; 4];
+        reader.read_exact(&mut bytes)?;
+        *value = f32::from_le_bytes(bytes);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::{BuildOptions, FirstPolygon};
+    use crate::test_fixtures::cube;
+
+    #[test]
+    fn round_trip_preserves_polygons_and_planes() {
+        // A slanted quad splits some cube faces, giving fragments
+        let mut polygons = vec![Polygon::new(vec![
+            Point3::new(-1.0, -1.0, 1.5),
+            Point3::new(3.0, -1.0, 0.5),
+            Point3::new(3.0, 3.0, 0.5),
+            Point3::new(-1.0, 3.0, 1.5),
+        ])];
+        polygons.extend(cube());
+        let tree = BspTree::from_polygons(polygons);
+
+        let mut bytes = Vec::new();
+        tree.write_binary(&mut bytes).unwrap();
+        let restored = BspTree::read_binary(bytes.as_slice()).unwrap();
+
+        assert_eq!(restored.collect_polygons(), tree.collect_polygons());
+        assert_eq!(restored.to_plane_tree(), tree.to_plane_tree());
+        assert_eq!(restored.aabb(), tree.aabb());
+        assert_eq!(
+            restored.root().unwrap().coplanar_front(),
+            tree.root().unwrap().coplanar_front()
+        );
+    }
+
+    #[test]
+    fn round_trip_keeps_leftover_polygons() {
+        let options = BuildOptions::new().with_max_depth(Some(2));
+        let tree = BspTree::build_with_options(cube(), &FirstPolygon, &options);
+
+        let mut bytes = Vec::new();
+        tree.write_binary(&mut bytes).unwrap();
+        let restored = BspTree::read_binary(bytes.as_slice()).unwrap();
+
+        assert_eq!(restored.collect_polygons(), tree.collect_polygons());
+        let leftover = |tree: &BspTree| -> Vec<usize> {
+            tree.iter_nodes_dfs()
+                .map(|(node, _)| node.leftover().len())
+                .collect()
+        };
+        assert!(leftover(&tree).iter().any(|&count| count > 0));
+        assert_eq!(leftover(&restored), leftover(&tree));
+    }
+
+    #[test]
+    fn round_trip_keeps_small_polygons() {
+        let tiny = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1e-4, 0.0, 0.0),
+            Point3::new(0.0, 1e-4, 0.0),
+        ]);
+        let tree = BspTree::from_polygons(vec![tiny]);
+        assert_eq!(tree.polygon_count(), 1);
+
+        let mut bytes = Vec::new();
+        tree.write_binary(&mut bytes).unwrap();
+        let restored = BspTree::read_binary(bytes.as_slice()).unwrap();
+        assert_eq!(restored.collect_polygons(), tree.collect_polygons());
+    }
+
+    #[test]
+    fn cube_encoding_size() {
+        let tree = BspTree::from_polygons(cube());
+
+        let mut bytes = Vec::new();
+        tree.write_binary(&mut bytes).unwrap();
+        // Header; per node a tag, plane, three list counts and one quad of
+        // 4 + 4 * 12 bytes; 7 empty child tags
+        assert_eq!(bytes.len(), 5 + 6 * (1 + 16 + 12 + 52) + 7);
+    }
+
+    #[test]
+    fn empty_tree_round_trip() {
+        let mut bytes = Vec::new();
+        BspTree::new().write_binary(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 6);
+        assert!(BspTree::read_binary(bytes.as_slice()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_rejects_invalid_data() {
+        let err = BspTree::read_binary(&b"BSPP\x01\x00"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = BspTree::read_binary(&b"BSPT\x02\x00"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // A node with a valid plane and a two-vertex polygon
+        let mut bytes = b"BSPT\x01\x01".to_vec();
+        for value in [0.0f32, 0.0, 1.0, 0.0] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        let err = BspTree::read_binary(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // A node with a valid plane and a bent quad
+        let mut bytes = b"BSPT\x01\x01".to_vec();
+        for value in [0.0f32, 0.0, 1.0, 0.0] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(4u32.to_le_bytes());
+        for value in [
+            0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 0.0,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+        let err = BspTree::read_binary(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Truncated input
+        let mut bytes = Vec::new();
+        BspTree::from_polygons(cube())
+            .write_binary(&mut bytes)
+            .unwrap();
+        bytes.truncate(bytes.len() - 3);
+        let err = BspTree::read_binary(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn read_and_write_deep_tree_without_recursion() {
+        // A chain of nodes, each the front child of the previous one
+        const DEPTH: usize = 100_000;
+        let mut bytes = b"BSPT\x01".to_vec();
+        for _ in 0..DEPTH {
+            bytes.push(TAG_NODE);
+            for value in [0.0f32, 0.0, 1.0, 0.0] {
+                bytes.extend(value.to_le_bytes());
+            }
+            bytes.extend(0u32.to_le_bytes());
+            bytes.extend(0u32.to_le_bytes());
+            bytes.extend(0u32.to_le_bytes());
+        }
+        bytes.extend(std::iter::repeat_n(TAG_EMPTY, DEPTH + 1));
+
+        let tree = BspTree::read_binary(bytes.as_slice()).unwrap();
+        assert_eq!(tree.iter_nodes_dfs().count(), DEPTH);
+
+        let mut written = Vec::new();
+        tree.write_binary(&mut written).unwrap();
+        assert_eq!(written, bytes);
+    }
+}
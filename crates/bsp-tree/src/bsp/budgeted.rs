@@ -0,0 +1,275 @@
+//! Tree construction spread over several calls with a time budget.
+
+use std::time::{Duration, Instant};
+
+use crate::Polygon;
+
+use super::node::{BspNode, Direction};
+use super::options::BuildOptions;
+use super::selector::{FirstPolygon, PlaneSelector};
+use super::tree::{split_node, without_degenerate, BspTree, NodeSplit};
+
+/// A BSP tree build that can be paused and resumed.
+///
+/// Building a large scene in one go can block for a long time. A
+/// `BuildProgress` instead splits one node per step and lets the caller
+/// bound how long each call to [`resume`](Self::resume) may run, so the build
+/// can be interleaved with frames of an interactive application.
+///
+/// Nodes are split in the same order as [`BspTree::build_with_options`], so
+/// the finished tree is identical to the one built in a single call with the
+/// same selector and options.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use bsp_tree::bsp::{BuildProgress, FirstPolygon};
+/// use bsp_tree::{BspTree, Polygon};
+/// use nalgebra::Point3;
+///
+/// let polygons = vec![Polygon::new(vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(0.0, 1.0, 0.0),
+/// ])];
+///
+/// let mut progress = BspTree::build_budgeted(polygons, FirstPolygon, Duration::from_millis(4));
+/// while !progress.resume(Duration::from_millis(4)) {
+///     // Render a frame, handle input, ...
+/// }
+/// let tree = progress.finish();
+/// assert_eq!(tree.polygon_count(), 1);
+/// ```
+#[derive(Debug)]
+pub struct BuildProgress<S = FirstPolygon, T = ()> {
+    selector: S,
+    options: BuildOptions,
+    /// Split nodes in pre-order, with the indices of their children.
+    nodes: Vec<PendingNode<T>>,
+    /// Polygon lists still to be split, the next one on top.
+    jobs: Vec<Job<T>>,
+}
+
+/// A split node whose children may not be split yet.
+#[derive(Debug)]
+struct PendingNode<T> {
+    node: BspNode<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+}
+
+/// Polygons waiting to become the subtree on one side of a split node, or
+/// the root if there is no parent.
+#[derive(Debug)]
+struct Job<T> {
+    parent: Option<(usize, Direction)>,
+    /// Depth of the node to be split, 1 for the root.
+    depth: usize,
+    polygons: Vec<Polygon<T>>,
+}
+
+impl<T: Clone> BspTree<T> {
+    /// Starts building a tree and runs the build for about `max_duration`.
+    ///
+    /// Returns the build state, to be continued with
+    /// [`BuildProgress::resume`] and turned into a tree with
+    /// [`BuildProgress::finish`].
+    pub fn build_budgeted<S: PlaneSelector<T>>(
+        polygons: Vec<Polygon<T>>,
+        selector: S,
+        max_duration: Duration,
+    ) -> BuildProgress<S, T> {
+        let mut progress = BuildProgress::with_options(polygons, selector, BuildOptions::default());
+        progress.resume(max_duration);
+        progress
+    }
+}
+
+impl<S: PlaneSelector> BuildProgress<S> {
+    /// Creates a build without splitting anything yet.
+    ///
+    /// For polygons that carry a payload, use
+    /// [`with_options`](Self::with_options) with the default options.
+    pub fn new(polygons: Vec<Polygon>, selector: S) -> Self {
+        Self::with_options(polygons, selector, BuildOptions::default())
+    }
+}
+
+impl<S: PlaneSelector<T>, T: Clone> BuildProgress<S, T> {
+    /// Creates a build with custom [`BuildOptions`], without splitting
+    /// anything yet.
+    pub fn with_options(polygons: Vec<Polygon<T>>, selector: S, options: BuildOptions) -> Self {
+        let polygons = without_degenerate(polygons);
+        let mut jobs = Vec::new();
+        if !polygons.is_empty() {
+            jobs.push(Job {
+                parent: None,
+                depth: 1,
+                polygons,
+            });
+        }
+        Self {
+            selector,
+            options,
+            nodes: Vec::new(),
+            jobs,
+        }
+    }
+
+    /// Returns `true` once every node has been split.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Returns the number of nodes split so far.
+    #[inline]
+    pub fn nodes_built(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Splits the next node. Returns `true` if the build is finished.
+    pub fn step(&mut self) -> bool {
+        let Some(job) = self.jobs.pop() else {
+            return true;
+        };
+
+        let split = split_node(job.polygons, &self.selector, &self.options, job.depth);
+        if let Some(NodeSplit {
+            node, front, back, ..
+        }) = split
+        {
+            let index = self.nodes.len();
+            self.nodes.push(PendingNode {
+                node,
+                front: None,
+                back: None,
+            });
+            if let Some((parent, direction)) = job.parent {
+                let parent = &mut self.nodes[parent];
+                match direction {
+                    Direction::Front => parent.front = Some(index),
+                    Direction::Back => parent.back = Some(index),
+                }
+            }
+
+            // Pushed last so the front subtree is split first, as in a
+            // recursive build
+            for (direction, polygons) in [(Direction::Back, back), (Direction::Front, front)] {
+                if !polygons.is_empty() {
+                    self.jobs.push(Job {
+                        parent: Some((index, direction)),
+                        depth: job.depth + 1,
+                        polygons,
+                    });
+                }
+            }
+        }
+
+        self.is_finished()
+    }
+
+    /// Continues the build for about `max_duration`. Returns `true` if the
+    /// build is finished.
+    ///
+    /// At least one node is split per call, so a build always progresses,
+    /// and a call may overrun the budget by the time one split takes.
+    pub fn resume(&mut self, max_duration: Duration) -> bool {
+        let start = Instant::now();
+        while !self.step() {
+            if start.elapsed() >= max_duration {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Completes the build, however long it takes, and returns the tree.
+    pub fn finish(mut self) -> BspTree<T> {
+        while !self.step() {}
+
+        let mut nodes: Vec<Option<PendingNode<T>>> = self.nodes.into_iter().map(Some).collect();
+        let root = (!nodes.is_empty()).then(|| assemble(&mut nodes, 0));
+        BspTree::from_root(root)
+    }
+}
+
+/// Links the split node at `index` with its subtrees, taking them out of
+/// `nodes`.
+fn assemble<T>(nodes: &mut [Option<PendingNode<T>>], index: usize) -> BspNode<T> {
+    let PendingNode {
+        mut node,
+        front,
+        back,
+    } = nodes[index].take().expect("each node has one parent");
+    node.set_front(front.map(|i| assemble(nodes, i)));
+    node.set_back(back.map(|i| assemble(nodes, i)));
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::BalancedSelector;
+    use crate::test_fixtures::cube_grid;
+
+    #[test]
+    fn resuming_matches_single_build() {
+        let full = BspTree::from_polygons(cube_grid(4));
+
+        let mut progress = BspTree::build_budgeted(cube_grid(4), FirstPolygon, Duration::ZERO);
+        assert_eq!(progress.nodes_built(), 1);
+
+        let mut calls = 1;
+        while !progress.resume(Duration::ZERO) {
+            calls += 1;
+        }
+        assert!(calls > 10);
+        assert!(progress.is_finished());
+
+        let tree = progress.finish();
+        assert_eq!(tree.to_plane_tree(), full.to_plane_tree());
+        assert_eq!(tree.collect_polygons(), full.collect_polygons());
+        assert_eq!(tree.aabb(), full.aabb());
+    }
+
+    #[test]
+    fn finish_completes_a_paused_build() {
+        let options = BuildOptions::new().with_sorted_coplanar(true);
+        let full =
+            BspTree::build_with_options(cube_grid(4), &BalancedSelector::default(), &options);
+
+        let selector = BalancedSelector::default();
+        let mut progress = BuildProgress::with_options(cube_grid(4), selector, options);
+        for _ in 0..5 {
+            progress.step();
+        }
+        assert_eq!(progress.nodes_built(), 5);
+        assert!(!progress.is_finished());
+
+        let tree = progress.finish();
+        assert_eq!(tree.to_plane_tree(), full.to_plane_tree());
+        assert_eq!(tree.collect_polygons(), full.collect_polygons());
+    }
+
+    #[test]
+    fn max_depth_matches_single_build() {
+        let options = BuildOptions::new().with_max_depth(Some(4));
+        let full = BspTree::build_with_options(cube_grid(4), &FirstPolygon, &options);
+        assert_eq!(full.depth(), 4);
+
+        let tree = BuildProgress::with_options(cube_grid(4), FirstPolygon, options).finish();
+        assert_eq!(tree.to_plane_tree(), full.to_plane_tree());
+        assert_eq!(tree.collect_polygons(), full.collect_polygons());
+    }
+
+    #[test]
+    fn empty_build_is_finished() {
+        let mut progress = BuildProgress::new(Vec::new(), FirstPolygon);
+        assert!(progress.is_finished());
+        assert!(progress.step());
+        assert!(progress.finish().is_empty());
+    }
+}
@@ -0,0 +1,306 @@
+//! Traversal order cached across small eye movements.
+
+use nalgebra::Point3;
+
+use crate::{PlaneSide, Polygon};
+
+use super::node::{BspNode, NodeId};
+use super::tree::BspTree;
+use super::visitor::BspVisitor;
+
+/// A back-to-front node order for a tree, updated incrementally as the eye
+/// moves.
+///
+/// The full traversals in [`BspTree`] classify the eye against every plane on
+/// each call. In a render loop the eye usually moves only slightly, and most
+/// of those classifications stay the same. This cache stores the last
+/// [`PlaneSide`] of the eye for each node together with the resulting node
+/// order. [`update`](Self::update) reclassifies the eye and, for each node
+/// whose plane it crossed, swaps the order of that node's two subtrees in
+/// place, leaving the rest of the order untouched.
+///
+/// As in the full traversals, an eye on a plane counts as being in front of
+/// it, so moving onto or off a plane from the front does not flip a node.
+///
+/// # Example
+///
+/// ```
+/// use bsp_tree::{BspTree, Polygon, TraversalCache};
+/// use bsp_tree::bsp::CollectingVisitor;
+/// use nalgebra::Point3;
+///
+/// let tree = BspTree::from_polygons(vec![Polygon::new(vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(0.0, 1.0, 0.0),
+/// ])]);
+///
+/// let mut cache = TraversalCache::new(&tree, Point3::new(0.0, 0.0, 5.0));
+/// for frame in 0..3 {
+///     cache.update(Point3::new(0.1 * frame as f32, 0.0, 5.0));
+///     let mut visitor = CollectingVisitor::new();
+///     cache.traverse_back_to_front(&mut visitor);
+///     assert_eq!(visitor.polygons().len(), 1);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TraversalCache<'a, T = ()> {
+    /// Nodes in pre-order; all other vectors are indexed the same way.
+    nodes: Vec<&'a BspNode<T>>,
+    front: Vec<Option<usize>>,
+    back: Vec<Option<usize>>,
+    subtree_size: Vec<usize>,
+    sides: Vec<PlaneSide>,
+    /// Node indices, farthest first.
+    order: Vec<usize>,
+    /// Position of each node within `order`.
+    position: Vec<usize>,
+}
+
+impl<'a, T: Clone> TraversalCache<'a, T> {
+    /// Creates a cache for `tree` with the order computed for `eye`.
+    pub fn new(tree: &'a BspTree<T>, eye: Point3<f32>) -> Self {
+        let mut cache = Self {
+            nodes: Vec::new(),
+            front: Vec::new(),
+            back: Vec::new(),
+            subtree_size: Vec::new(),
+            sides: Vec::new(),
+            order: Vec::new(),
+            position: Vec::new(),
+        };
+        if let Some(root) = tree.root() {
+            cache.index_node(root, eye);
+            cache.order = Vec::with_capacity(cache.nodes.len());
+            cache.order_node(0);
+            cache.position = vec![0; cache.nodes.len()];
+            cache.reposition(0..cache.order.len());
+        }
+        cache
+    }
+
+    /// Moves the eye to `eye`, updating the order of every node whose plane
+    /// it crossed.
+    ///
+    /// Returns the ids of the flipped nodes, in pre-order. The result is empty
+    /// when the eye crossed no plane, in which case the order is unchanged.
+    pub fn update(&mut self, eye: Point3<f32>) -> Vec<NodeId> {
+        let mut flipped = Vec::new();
+        for index in 0..self.nodes.len() {
+            let side = self.nodes[index].plane().classify_point(eye);
+            let was_back = self.sides[index] == PlaneSide::Back;
+            self.sides[index] = side;
+            if was_back != (side == PlaneSide::Back) {
+                self.flip(index, was_back);
+                flipped.push(self.nodes[index].id());
+            }
+        }
+        flipped
+    }
+
+    /// Returns the side of `id`'s plane the eye was on at the last update,
+    /// or `None` if the tree has no such node.
+    pub fn side(&self, id: NodeId) -> Option<PlaneSide> {
+        self.nodes
+            .iter()
+            .position(|node| node.id() == id)
+            .map(|index| self.sides[index])
+    }
+
+    /// Returns the ids of all nodes in back-to-front order.
+    pub fn back_to_front(&self) -> Vec<NodeId> {
+        self.order.iter().map(|&i| self.nodes[i].id()).collect()
+    }
+
+    /// Visits the cached nodes' polygons back-to-front, exactly as
+    /// [`BspTree::traverse_back_to_front`] would for the last eye position.
+    pub fn traverse_back_to_front<V: BspVisitor<T>>(&self, visitor: &mut V) {
+        for &index in &self.order {
+            visit_node(self.nodes[index], visitor);
+        }
+    }
+
+    /// Visits the cached nodes' polygons front-to-back, exactly as
+    /// [`BspTree::traverse_front_to_back`] would for the last eye position.
+    pub fn traverse_front_to_back<V: BspVisitor<T>>(&self, visitor: &mut V) {
+        for &index in self.order.iter().rev() {
+            visit_node(self.nodes[index], visitor);
+        }
+    }
+
+    /// Appends `node`'s subtree in pre-order and returns its index.
+    fn index_node(&mut self, node: &'a BspNode<T>, eye: Point3<f32>) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(node);
+        self.front.push(None);
+        self.back.push(None);
+        self.subtree_size.push(1);
+        self.sides.push(node.plane().classify_point(eye));
+
+        if let Some(front) = node.front() {
+            self.front[index] = Some(self.index_node(front, eye));
+        }
+        if let Some(back) = node.back() {
+            self.back[index] = Some(self.index_node(back, eye));
+        }
+        self.subtree_size[index] =
+            1 + self.size_of(self.front[index]) + self.size_of(self.back[index]);
+        index
+    }
+
+    fn order_node(&mut self, index: usize) {
+        let (far, near) = self.children_far_first(index, self.sides[index] == PlaneSide::Back);
+        if let Some(far) = far {
+            self.order_node(far);
+        }
+        self.order.push(index);
+        if let Some(near) = near {
+            self.order_node(near);
+        }
+    }
+
+    /// Swaps the two subtree blocks around `index` in the order, given which
+    /// side the eye was on before.
+    fn flip(&mut self, index: usize, was_back: bool) {
+        let (far, near) = self.children_far_first(index, was_back);
+        let far_len = self.size_of(far);
+        let start = self.position[index] - far_len;
+        let end = start + self.subtree_size[index];
+
+        // [far][node][near] becomes [near][node][far]
+        let span = &self.order[start..end];
+        let flipped: Vec<usize> = span[far_len + 1..]
+            .iter()
+            .chain(&span[far_len..=far_len])
+            .chain(&span[..far_len])
+            .copied()
+            .collect();
+        debug_assert_eq!(flipped.len(), end - start);
+        debug_assert_eq!(self.size_of(near), end - start - far_len - 1);
+
+        self.order[start..end].copy_from_slice(&flipped);
+        self.reposition(start..end);
+    }
+
+    /// Returns `index`'s (far, near) children for an eye on the given side.
+    fn children_far_first(&self, index: usize, eye_behind: bool) -> (Option<usize>, Option<usize>) {
+        if eye_behind {
+            (self.front[index], self.back[index])
+        } else {
+            (self.back[index], self.front[index])
+        }
+    }
+
+    fn size_of(&self, index: Option<usize>) -> usize {
+        index.map_or(0, |i| self.subtree_size[i])
+    }
+
+    fn reposition(&mut self, range: std::ops::Range<usize>) {
+        for k in range {
+            self.position[self.order[k]] = k;
+        }
+    }
+}
+
+fn visit_node<T: Clone, V: BspVisitor<T>>(node: &BspNode<T>, visitor: &mut V) {
+    let polygons: Vec<Polygon<T>> = node.polygons().cloned().collect();
+    if !polygons.is_empty() {
+        visitor.visit(&polygons);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::CollectingVisitor;
+    use crate::test_fixtures::cube;
+
+    fn assert_matches_full_traversal(cache: &TraversalCache, tree: &BspTree, eye: Point3<f32>) {
+        let mut cached = CollectingVisitor::new();
+        cache.traverse_back_to_front(&mut cached);
+        let mut full = CollectingVisitor::new();
+        tree.traverse_back_to_front(eye, &mut full);
+        assert_eq!(cached.polygons(), full.polygons());
+
+        let mut cached = CollectingVisitor::new();
+        cache.traverse_front_to_back(&mut cached);
+        let mut full = CollectingVisitor::new();
+        tree.traverse_front_to_back(eye, &mut full);
+        assert_eq!(cached.polygons(), full.polygons());
+
+        assert_eq!(
+            cache.back_to_front(),
+            TraversalCache::new(tree, eye).back_to_front()
+        );
+    }
+
+    #[test]
+    fn small_move_keeps_order() {
+        let tree = BspTree::from_polygons(cube());
+        let mut cache = TraversalCache::new(&tree, Point3::new(1.0, 1.0, 5.0));
+        let before = cache.back_to_front();
+
+        let eye = Point3::new(1.2, 0.9, 5.1);
+        assert!(cache.update(eye).is_empty());
+
+        assert_eq!(cache.back_to_front(), before);
+        assert_matches_full_traversal(&cache, &tree, eye);
+    }
+
+    #[test]
+    fn crossing_a_plane_flips_only_that_node() {
+        let tree = BspTree::from_polygons(cube());
+        let mut cache = TraversalCache::new(&tree, Point3::new(1.0, 1.0, 5.0));
+
+        // Moving below z = 2 crosses only the top face's plane
+        let eye = Point3::new(1.0, 1.0, 1.5);
+        let flipped = cache.update(eye);
+
+        assert_eq!(flipped.len(), 1);
+        let index = cache
+            .nodes
+            .iter()
+            .position(|n| n.id() == flipped[0])
+            .unwrap();
+        let plane = cache.nodes[index].plane();
+        assert_eq!(
+            plane.classify_point(Point3::new(0.5, 1.5, 2.0)),
+            PlaneSide::OnPlane
+        );
+        assert_eq!(
+            plane.classify_point(Point3::new(1.0, 1.0, 3.0)),
+            PlaneSide::Front
+        );
+        assert_eq!(cache.side(flipped[0]), Some(PlaneSide::Back));
+        assert_matches_full_traversal(&cache, &tree, eye);
+
+        // Moving back restores the original order
+        let eye = Point3::new(1.0, 1.0, 5.0);
+        assert_eq!(cache.update(eye), flipped);
+        assert_matches_full_traversal(&cache, &tree, eye);
+    }
+
+    #[test]
+    fn crossing_several_planes_matches_full_traversal() {
+        let tree = BspTree::from_polygons(cube());
+        let mut cache = TraversalCache::new(&tree, Point3::new(-1.0, -1.0, -1.0));
+
+        for eye in [
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(3.0, -1.0, 1.0),
+            Point3::new(3.0, 3.0, 3.0),
+            Point3::new(-1.0, 1.0, 3.0),
+        ] {
+            cache.update(eye);
+            assert_matches_full_traversal(&cache, &tree, eye);
+        }
+    }
+
+    #[test]
+    fn empty_tree() {
+        let tree = BspTree::new();
+        let mut cache = TraversalCache::new(&tree, Point3::origin());
+        assert!(cache.update(Point3::new(1.0, 0.0, 0.0)).is_empty());
+        assert!(cache.back_to_front().is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//! Resumable traversal for spreading work across frames.
+
+use nalgebra::Point3;
+
+use crate::Polygon;
+
+use super::iter::OrderedNodes;
+use super::node::BspNode;
+use super::tree::BspTree;
+
+/// A paused front-to-back traversal of a [`BspTree`].
+///
+/// Created by [`BspTree::traverse_front_to_back_resumable`]. Each call to
+/// [`next_chunk`](Self::next_chunk) continues where the previous one stopped
+/// and returns at most a given number of polygons, so a renderer can bound
+/// the work done per frame. Concatenating all chunks gives the same polygons
+/// in the same order as [`BspTree::traverse_front_to_back`].
+///
+/// # Example
+///
+/// ```
+/// use bsp_tree::{BspTree, Polygon};
+/// use nalgebra::Point3;
+///
+/// let tree = BspTree::from_polygons(vec![Polygon::new(vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(0.0, 1.0, 0.0),
+/// ])]);
+///
+/// let mut cursor = tree.traverse_front_to_back_resumable(Point3::new(0.0, 0.0, 5.0));
+/// while !cursor.is_finished() {
+///     for polygon in cursor.next_chunk(64) {
+///         // draw polygon
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TraversalCursor<'a, T = ()> {
+    nodes: OrderedNodes<'a, T>,
+    /// A node whose polygons were only partly returned, with the number
+    /// returned so far.
+    partial: Option<(&'a BspNode<T>, usize)>,
+}
+
+impl<T: Clone> BspTree<T> {
+    /// Starts a front-to-back traversal that can be consumed in chunks.
+    ///
+    /// See [`TraversalCursor`].
+    pub fn traverse_front_to_back_resumable(&self, eye: Point3<f32>) -> TraversalCursor<'_, T> {
+        TraversalCursor {
+            nodes: OrderedNodes::new(self.root(), eye, true),
+            partial: None,
+        }
+    }
+}
+
+impl<'a, T> TraversalCursor<'a, T> {
+    /// Returns up to `max_polygons` further polygons, nearest first.
+    ///
+    /// Returns fewer polygons only when the traversal finishes.
+    pub fn next_chunk(&mut self, max_polygons: usize) -> Vec<&'a Polygon<T>> {
+        let mut chunk = Vec::with_capacity(max_polygons);
+
+        while chunk.len() < max_polygons {
+            let Some((node, offset)) = self
+                .partial
+                .take()
+                .or_else(|| self.nodes.next().map(|(node, _)| (node, 0)))
+            else {
+                break;
+            };
+            let remaining = max_polygons - chunk.len();
+            chunk.extend(node.polygons().skip(offset).take(remaining));
+            let emitted = offset + remaining;
+            if emitted < node.polygons().count() {
+                self.partial = Some((node, emitted));
+            }
+        }
+
+        chunk
+    }
+
+    /// Returns `true` once every polygon has been returned.
+    pub fn is_finished(&self) -> bool {
+        self.partial.is_none() && self.nodes.is_finished()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::CollectingVisitor;
+    use crate::test_fixtures::cube;
+
+    /// The six faces of the cube [0, 2]^3, wound counter-clockwise from outside,
+    /// each split in two triangles.
+    fn cube_triangles() -> Vec<Polygon> {
+        cube()
+            .iter()
+            .flat_map(|face| {
+                let v = face.vertices();
+                [
+                    Polygon::new(vec![v[0], v[1], v[2]]),
+                    Polygon::new(vec![v[0], v[2], v[3]]),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resumed_chunks_match_full_traversal() {
+        let tree = BspTree::from_polygons(cube_triangles());
+        let eye = Point3::new(3.0, 0.5, 4.0);
+
+        let mut expected = CollectingVisitor::new();
+        tree.traverse_front_to_back(eye, &mut expected);
+
+        let mut cursor = tree.traverse_front_to_back_resumable(eye);
+        let mut visited = Vec::new();
+        let mut calls = 0;
+        while !cursor.is_finished() {
+            let chunk = cursor.next_chunk(5);
+            assert!(chunk.len() <= 5);
+            visited.extend(chunk.into_iter().cloned());
+            calls += 1;
+        }
+
+        assert_eq!(visited, expected.polygons());
+        assert_eq!(visited.len(), 12);
+        assert!(calls >= 3);
+        assert!(cursor.next_chunk(5).is_empty());
+    }
+
+    #[test]
+    fn chunk_of_one_splits_coplanar_groups() {
+        let tree = BspTree::from_polygons(cube_triangles());
+        let eye = Point3::new(1.0, 1.0, 1.0);
+
+        let mut cursor = tree.traverse_front_to_back_resumable(eye);
+        let mut count = 0;
+        loop {
+            let chunk = cursor.next_chunk(1);
+            if chunk.is_empty() {
+                break;
+            }
+            assert_eq!(chunk.len(), 1);
+            count += 1;
+        }
+
+        assert_eq!(count, 12);
+    }
+
+    #[test]
+    fn empty_tree_is_finished() {
+        let tree = BspTree::new();
+        let mut cursor = tree.traverse_front_to_back_resumable(Point3::origin());
+
+        assert!(cursor.is_finished());
+        assert!(cursor.next_chunk(10).is_empty());
+    }
+}
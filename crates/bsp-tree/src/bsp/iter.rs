@@ -0,0 +1,366 @@
+//! Iterator-based traversal, as an alternative to [`BspVisitor`](super::BspVisitor).
+
+use std::collections::VecDeque;
+
+use nalgebra::{Isometry3, Point3};
+
+use crate::{Frustum, PlaneSide, Polygon};
+
+use super::node::BspNode;
+use super::tree::BspTree;
+
+/// Nodes with their depths in visibility order relative to an eye point,
+/// nearest first or farthest first.
+///
+/// The single walk behind the visitor traversals, the polygon iterators and
+/// [`TraversalCursor`](super::TraversalCursor). Uses an explicit stack
+/// rather than recursion, so that the deep trees
+/// [`FirstPolygon`](super::FirstPolygon) builds from unfavourable input do
+/// not overflow the call stack.
+#[derive(Debug, Clone)]
+pub(crate) struct OrderedNodes<'a, T> {
+    eye: Point3<f32>,
+    nearest_first: bool,
+    frustum: Option<&'a Frustum>,
+    stack: Vec<Step<'a, T>>,
+}
+
+/// Pending work, popped from the end of the stack.
+#[derive(Debug)]
+enum Step<'a, T> {
+    /// Traverse a whole subtree whose root is at the given depth.
+    Descend(&'a BspNode<T>, usize),
+    /// Yield a node at the given depth.
+    Visit(&'a BspNode<T>, usize),
+}
+
+impl<T> Clone for Step<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Step<'_, T> {}
+
+impl<'a, T> OrderedNodes<'a, T> {
+    pub(crate) fn new(root: Option<&'a BspNode<T>>, eye: Point3<f32>, nearest_first: bool) -> Self {
+        Self {
+            eye,
+            nearest_first,
+            frustum: None,
+            stack: root
+                .map(|root| Step::Descend(root, 1))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Skips subtrees whose cached [`bounds`](BspNode::bounds) lie outside
+    /// `frustum`.
+    pub(crate) fn culled(mut self, frustum: &'a Frustum) -> Self {
+        self.frustum = Some(frustum);
+        self
+    }
+
+    /// Returns `true` once every node has been yielded.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+impl<'a, T> Iterator for OrderedNodes<'a, T> {
+    type Item = (&'a BspNode<T>, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop()? {
+                Step::Descend(node, depth) => {
+                    let culled = self.frustum.is_some_and(|frustum| {
+                        node.bounds().is_none_or(|bounds| frustum.excludes(bounds))
+                    });
+                    if culled {
+                        continue;
+                    }
+                    let (near, far) = match node.plane().classify_point(self.eye) {
+                        PlaneSide::Front | PlaneSide::OnPlane => (node.front(), node.back()),
+                        PlaneSide::Back => (node.back(), node.front()),
+                    };
+                    let (first, last) = if self.nearest_first {
+                        (near, far)
+                    } else {
+                        (far, near)
+                    };
+                    // Pushed in reverse, so `first` is popped next
+                    self.stack
+                        .extend(last.map(|child| Step::Descend(child, depth + 1)));
+                    self.stack.push(Step::Visit(node, depth));
+                    self.stack
+                        .extend(first.map(|child| Step::Descend(child, depth + 1)));
+                }
+                Step::Visit(node, depth) => return Some((node, depth)),
+            }
+        }
+    }
+}
+
+/// Nodes with their depths in breadth-first order.
+#[derive(Debug, Clone)]
+struct NodesBreadthFirst<'a, T> {
+    queue: VecDeque<(&'a BspNode<T>, usize)>,
+}
+
+impl<'a, T> Iterator for NodesBreadthFirst<'a, T> {
+    type Item = (&'a BspNode<T>, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth) = self.queue.pop_front()?;
+        for child in [node.front(), node.back()].into_iter().flatten() {
+            self.queue.push_back((child, depth + 1));
+        }
+        Some((node, depth))
+    }
+}
+
+/// Nodes with their depths in depth-first pre-order.
+#[derive(Debug, Clone)]
+struct NodesDepthFirst<'a, T> {
+    stack: Vec<(&'a BspNode<T>, usize)>,
+}
+
+impl<'a, T> Iterator for NodesDepthFirst<'a, T> {
+    type Item = (&'a BspNode<T>, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth) = self.stack.pop()?;
+        // Pushed in reverse, so the front child is popped next
+        for child in [node.back(), node.front()].into_iter().flatten() {
+            self.stack.push((child, depth + 1));
+        }
+        Some((node, depth))
+    }
+}
+
+impl<T> BspNode<T> {
+    /// Returns the nodes of this subtree with their depths within it, in
+    /// depth-first pre-order starting with this node at depth 1.
+    pub(crate) fn descendants(&self) -> impl Iterator<Item = (&BspNode<T>, usize)> + '_ {
+        NodesDepthFirst {
+            stack: vec![(self, 1)],
+        }
+    }
+}
+
+impl<T: Clone> BspTree<T> {
+    /// Returns every node with its depth, level by level, front child before
+    /// back child.
+    ///
+    /// Depths count nodes as [`depth`](Self::depth) does: the root has depth
+    /// 1, so a node's depth is one more than the length of its
+    /// [path](Self::path_to). Useful for laying out the tree topology:
+    ///
+    /// ```
+    /// use bsp_tree::{BspTree, Polygon};
+    /// use nalgebra::Point3;
+    ///
+    /// let triangle = |z: f32| {
+    ///     Polygon::new(vec![
+    ///         Point3::new(0.0, 0.0, z),
+    ///         Point3::new(1.0, 0.0, z),
+    ///         Point3::new(0.0, 1.0, z),
+    ///     ])
+    /// };
+    /// let tree = BspTree::from_polygons(vec![triangle(0.0), triangle(1.0)]);
+    ///
+    /// let depths: Vec<usize> = tree.iter_nodes_bfs().map(|(_, depth)| depth).collect();
+    /// assert_eq!(depths, [1, 2]);
+    /// ```
+    pub fn iter_nodes_bfs(&self) -> impl Iterator<Item = (&BspNode<T>, usize)> + '_ {
+        NodesBreadthFirst {
+            queue: self.root().map(|root| (root, 1)).into_iter().collect(),
+        }
+    }
+
+    /// Returns every node with its depth in depth-first pre-order (node,
+    /// front subtree, back subtree), which is the order of the node ids.
+    ///
+    /// Depths are counted as in [`iter_nodes_bfs`](Self::iter_nodes_bfs).
+    pub fn iter_nodes_dfs(&self) -> impl Iterator<Item = (&BspNode<T>, usize)> + '_ {
+        self.root().into_iter().flat_map(BspNode::descendants)
+    }
+
+    /// Returns the polygons in front-to-back order relative to `eye`.
+    ///
+    /// Yields the same polygons in the same order as
+    /// [`traverse_front_to_back`](Self::traverse_front_to_back), borrowed from
+    /// the tree, so iterator adapters can stop early:
+    ///
+    /// ```
+    /// use bsp_tree::{BspTree, Polygon};
+    /// use nalgebra::Point3;
+    ///
+    /// let tree = BspTree::from_polygons(vec![Polygon::new(vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    /// ])]);
+    ///
+    /// let eye = Point3::new(0.0, 0.0, 5.0);
+    /// let nearest: Vec<&Polygon> = tree.iter_front_to_back(eye).take(100).collect();
+    /// assert_eq!(nearest.len(), 1);
+    /// ```
+    pub fn iter_front_to_back(&self, eye: Point3<f32>) -> impl Iterator<Item = &Polygon<T>> + '_ {
+        OrderedNodes::new(self.root(), eye, true).flat_map(|(node, _)| node.polygons())
+    }
+
+    /// Returns the polygons of an instance of the tree placed in the world by
+    /// `instance`, in front-to-back order relative to the world-space `eye`.
+    ///
+    /// Yields the same untransformed polygons in the same order as
+    /// [`traverse_front_to_back_transformed`](Self::traverse_front_to_back_transformed).
+    pub fn iter_front_to_back_transformed(
+        &self,
+        instance: &Isometry3<f32>,
+        eye: Point3<f32>,
+    ) -> impl Iterator<Item = &Polygon<T>> + '_ {
+        self.iter_front_to_back(instance.inverse_transform_point(&eye))
+    }
+
+    /// Returns the polygons in back-to-front (painter's) order relative to
+    /// `eye`.
+    ///
+    /// Yields the same polygons in the same order as
+    /// [`traverse_back_to_front`](Self::traverse_back_to_front).
+    pub fn iter_back_to_front(&self, eye: Point3<f32>) -> impl Iterator<Item = &Polygon<T>> + '_ {
+        OrderedNodes::new(self.root(), eye, false).flat_map(|(node, _)| node.polygons())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::{CollectingVisitor, NodeId};
+
+    fn make_triangle(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Polygon {
+        Polygon::new(vec![
+            Point3::new(a[0], a[1], a[2]),
+            Point3::new(b[0], b[1], b[2]),
+            Point3::new(c[0], c[1], c[2]),
+        ])
+    }
+
+    /// Triangles in several orientations, including a coplanar pair.
+    fn scene() -> Vec<Polygon> {
+        vec![
+            make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            make_triangle([2.0, 2.0, 0.0], [3.0, 2.0, 0.0], [2.0, 3.0, 0.0]),
+            make_triangle([0.0, 0.0, 1.0], [0.0, 1.0, 1.0], [1.0, 0.0, 1.0]),
+            make_triangle([0.5, -1.0, -1.0], [0.5, 2.0, -1.0], [0.5, 0.0, 2.0]),
+            make_triangle([-1.0, 0.5, -1.0], [-1.0, 0.5, 2.0], [2.0, 0.5, 0.0]),
+            make_triangle([0.0, 0.0, -2.0], [1.0, 0.0, -2.0], [0.0, 1.0, -2.0]),
+        ]
+    }
+
+    #[test]
+    fn iterators_match_visitor_order() {
+        let tree = BspTree::from_polygons(scene());
+
+        for eye in [
+            Point3::new(0.2, 0.3, 5.0),
+            Point3::new(3.0, -1.0, 0.5),
+            Point3::new(-2.0, 4.0, -3.0),
+        ] {
+            let mut front_to_back = CollectingVisitor::new();
+            tree.traverse_front_to_back(eye, &mut front_to_back);
+            let iterated: Vec<Polygon> = tree.iter_front_to_back(eye).cloned().collect();
+            assert_eq!(iterated, front_to_back.polygons());
+
+            let mut back_to_front = CollectingVisitor::new();
+            tree.traverse_back_to_front(eye, &mut back_to_front);
+            let iterated: Vec<Polygon> = tree.iter_back_to_front(eye).cloned().collect();
+            assert_eq!(iterated, back_to_front.polygons());
+        }
+    }
+
+    #[test]
+    fn transformed_order_matches_a_transformed_copy() {
+        let tree = BspTree::from_polygons(scene());
+        let instance = Isometry3::new(
+            nalgebra::Vector3::new(10.0, -3.0, 2.0),
+            nalgebra::Vector3::new(0.5, -1.2, 0.3),
+        );
+        let mut moved = BspTree::from_polygons(scene());
+        moved.transform(&instance);
+
+        for eye in [Point3::new(12.0, 0.0, 8.0), Point3::new(4.0, -6.0, -1.0)] {
+            let local: Vec<Polygon> = tree
+                .iter_front_to_back_transformed(&instance, eye)
+                .cloned()
+                .collect();
+            let world: Vec<Polygon> = moved.iter_front_to_back(eye).cloned().collect();
+            assert_eq!(local.len(), world.len());
+            for (local, world) in local.iter().zip(&world) {
+                let placed = local.transformed(&instance);
+                assert!((placed.centroid() - world.centroid()).norm() < 1e-4);
+            }
+
+            let mut visitor = CollectingVisitor::new();
+            tree.traverse_front_to_back_transformed(&instance, eye, &mut visitor);
+            assert_eq!(visitor.polygons(), local);
+        }
+    }
+
+    fn stored_in(node: &BspNode, polygon: &Polygon) -> bool {
+        node.polygons().any(|p| std::ptr::eq(p, polygon))
+            || node.front().is_some_and(|front| stored_in(front, polygon))
+            || node.back().is_some_and(|back| stored_in(back, polygon))
+    }
+
+    #[test]
+    fn iterator_borrows_from_tree() {
+        let tree = BspTree::from_polygons(scene());
+        let eye = Point3::new(0.2, 0.3, 5.0);
+
+        let nearest: Vec<&Polygon> = tree.iter_front_to_back(eye).take(2).collect();
+
+        assert_eq!(nearest.len(), 2);
+        let root = tree.root().unwrap();
+        assert!(nearest.iter().all(|polygon| stored_in(root, polygon)));
+    }
+
+    #[test]
+    fn depth_first_nodes_follow_ids_and_paths() {
+        let tree = BspTree::from_polygons(scene());
+
+        let nodes: Vec<(&BspNode, usize)> = tree.iter_nodes_dfs().collect();
+        assert!(nodes.len() > 3);
+        for (i, (node, depth)) in nodes.iter().enumerate() {
+            assert_eq!(node.id(), NodeId(i));
+            assert_eq!(tree.path_to(node.id()).unwrap().len() + 1, *depth);
+        }
+    }
+
+    #[test]
+    fn breadth_first_nodes_go_level_by_level() {
+        let tree = BspTree::from_polygons(scene());
+
+        let nodes: Vec<(&BspNode, usize)> = tree.iter_nodes_bfs().collect();
+        assert!(nodes.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        assert_eq!(nodes.last().unwrap().1, tree.depth());
+        for (node, depth) in &nodes {
+            assert_eq!(tree.path_to(node.id()).unwrap().len() + 1, *depth);
+        }
+
+        let mut ids: Vec<usize> = nodes.iter().map(|(node, _)| node.id().0).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, (0..tree.iter_nodes_dfs().count()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty_tree_yields_nothing() {
+        let tree = BspTree::new();
+        assert_eq!(tree.iter_front_to_back(Point3::origin()).count(), 0);
+        assert_eq!(tree.iter_back_to_front(Point3::origin()).count(), 0);
+        assert_eq!(tree.iter_nodes_bfs().count(), 0);
+        assert_eq!(tree.iter_nodes_dfs().count(), 0);
+    }
+}
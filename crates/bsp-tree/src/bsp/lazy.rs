@@ -0,0 +1,283 @@
+//! BSP trees that subdivide on demand.
+
+use std::cell::{Cell, OnceCell, RefCell};
+
+use nalgebra::Point3;
+
+use crate::{PlaneSide, Polygon};
+
+use super::node::{BspNode, Direction};
+use super::options::BuildOptions;
+use super::selector::{FirstPolygon, PlaneSelector};
+use super::tree::{split_node, without_degenerate, BspTree, NodeSplit};
+use super::visitor::BspVisitor;
+
+/// A BSP tree that splits its polygons only when a query reaches them.
+///
+/// Building a [`BspTree`] partitions the whole scene up front. For large
+/// scenes where only a small part is ever queried, a `LazyBspTree` instead
+/// keeps each region's polygons in an unprocessed bucket and runs the split
+/// for a node the first time a traversal or query descends into it. Once
+/// expanded, a node stays expanded, so repeated queries cost the same as on
+/// a fully built tree.
+///
+/// Expansion happens behind `&self` using interior mutability, so the tree
+/// is not `Sync`. Expanding every node produces the same partition as
+/// [`BspTree::build_with_options`] with the same selector and options.
+///
+/// # Example
+///
+/// ```
+/// use bsp_tree::bsp::LazyBspTree;
+/// use bsp_tree::Polygon;
+/// use nalgebra::Point3;
+///
+/// let tree = LazyBspTree::from_polygons(vec![Polygon::new(vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(0.0, 1.0, 0.0),
+/// ])]);
+/// assert_eq!(tree.expanded_count(), 0);
+///
+/// assert!(!tree.contains_point(Point3::new(0.2, 0.2, 1.0)));
+/// assert_eq!(tree.expanded_count(), 1);
+/// ```
+#[derive(Debug)]
+pub struct LazyBspTree<S = FirstPolygon, T = ()> {
+    root: LazyNode<T>,
+    selector: S,
+    options: BuildOptions,
+    expanded: Cell<usize>,
+}
+
+/// A node whose polygons are split on first access.
+#[derive(Debug)]
+struct LazyNode<T> {
+    /// Depth of the node, 1 for the root.
+    depth: usize,
+    pending: RefCell<Vec<Polygon<T>>>,
+    expanded: OnceCell<Option<Expanded<T>>>,
+}
+
+/// The result of splitting a [`LazyNode`]'s polygons.
+#[derive(Debug)]
+struct Expanded<T> {
+    /// The node's plane and polygons; its children are unused.
+    node: BspNode<T>,
+    front: Box<LazyNode<T>>,
+    back: Box<LazyNode<T>>,
+}
+
+impl LazyBspTree {
+    /// Creates a lazy tree using the default plane selector ([`FirstPolygon`]).
+    ///
+    /// For polygons that carry a payload, use [`new`](Self::new) with
+    /// [`FirstPolygon`].
+    pub fn from_polygons(polygons: Vec<Polygon>) -> Self {
+        Self::new(polygons, FirstPolygon)
+    }
+}
+
+impl<S: PlaneSelector<T>, T: Clone> LazyBspTree<S, T> {
+    /// Creates a lazy tree without splitting anything yet.
+    pub fn new(polygons: Vec<Polygon<T>>, selector: S) -> Self {
+        Self::with_options(polygons, selector, BuildOptions::default())
+    }
+
+    /// Creates a lazy tree with custom [`BuildOptions`], applied as each node
+    /// is expanded.
+    pub fn with_options(polygons: Vec<Polygon<T>>, selector: S, options: BuildOptions) -> Self {
+        Self {
+            root: LazyNode::new(without_degenerate(polygons), 1),
+            selector,
+            options,
+            expanded: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of nodes split so far.
+    pub fn expanded_count(&self) -> usize {
+        self.expanded.get()
+    }
+
+    /// Returns the front/back steps taken from the root to the leaf cell
+    /// containing `point`, as [`BspTree::trace`].
+    ///
+    /// Only the nodes along the path are expanded.
+    pub fn trace(&self, point: Point3<f32>) -> Vec<Direction> {
+        let mut path = Vec::new();
+        let mut current = self.expand(&self.root);
+        while let Some(expanded) = current {
+            let next = match expanded.node.plane().classify_point(point) {
+                PlaneSide::Front | PlaneSide::OnPlane => {
+                    path.push(Direction::Front);
+                    &expanded.front
+                }
+                PlaneSide::Back => {
+                    path.push(Direction::Back);
+                    &expanded.back
+                }
+            };
+            current = self.expand(next);
+        }
+        path
+    }
+
+    /// Returns `true` if `point` lies inside the solid bounded by the tree,
+    /// as [`BspTree::contains_point`].
+    ///
+    /// Only the nodes along the point's path are expanded.
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.trace(point).last() == Some(&Direction::Back)
+    }
+
+    /// Traverses the tree front-to-back relative to `eye`, as
+    /// [`BspTree::traverse_front_to_back`]. Expands every node.
+    pub fn traverse_front_to_back<V: BspVisitor<T>>(&self, eye: Point3<f32>, visitor: &mut V) {
+        self.traverse_node(&self.root, eye, visitor, true);
+    }
+
+    /// Traverses the tree back-to-front relative to `eye`, as
+    /// [`BspTree::traverse_back_to_front`]. Expands every node.
+    pub fn traverse_back_to_front<V: BspVisitor<T>>(&self, eye: Point3<f32>, visitor: &mut V) {
+        self.traverse_node(&self.root, eye, visitor, false);
+    }
+
+    /// Expands every node and copies the result into a [`BspTree`].
+    pub fn to_tree(&self) -> BspTree<T> {
+        BspTree::from_root(self.materialize(&self.root))
+    }
+
+    /// Splits `node`'s pending polygons if that has not happened yet.
+    ///
+    /// Returns `None` for an empty region.
+    fn expand<'a>(&self, node: &'a LazyNode<T>) -> Option<&'a Expanded<T>> {
+        node.expanded
+            .get_or_init(|| {
+                let polygons = node.pending.take();
+                let depth = node.depth;
+                let NodeSplit {
+                    node, front, back, ..
+                } = split_node(polygons, &self.selector, &self.options, depth)?;
+                self.expanded.set(self.expanded.get() + 1);
+                Some(Expanded {
+                    node,
+                    front: Box::new(LazyNode::new(front, depth + 1)),
+                    back: Box::new(LazyNode::new(back, depth + 1)),
+                })
+            })
+            .as_ref()
+    }
+
+    fn traverse_node<V: BspVisitor<T>>(
+        &self,
+        node: &LazyNode<T>,
+        eye: Point3<f32>,
+        visitor: &mut V,
+        near_first: bool,
+    ) {
+        let Some(expanded) = self.expand(node) else {
+            return;
+        };
+
+        let eye_in_front = expanded.node.plane().classify_point(eye) != PlaneSide::Back;
+        let (first, second) = if eye_in_front == near_first {
+            (&expanded.front, &expanded.back)
+        } else {
+            (&expanded.back, &expanded.front)
+        };
+
+        self.traverse_node(first, eye, visitor, near_first);
+        let polygons: Vec<Polygon<T>> = expanded.node.polygons().cloned().collect();
+        if !polygons.is_empty() {
+            visitor.visit(&polygons);
+        }
+        self.traverse_node(second, eye, visitor, near_first);
+    }
+
+    fn materialize(&self, node: &LazyNode<T>) -> Option<BspNode<T>> {
+        let expanded = self.expand(node)?;
+        let mut result = expanded.node.clone();
+        result.set_front(self.materialize(&expanded.front));
+        result.set_back(self.materialize(&expanded.back));
+        Some(result)
+    }
+}
+
+impl<T> LazyNode<T> {
+    fn new(polygons: Vec<Polygon<T>>, depth: usize) -> Self {
+        Self {
+            depth,
+            pending: RefCell::new(polygons),
+            expanded: OnceCell::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::CollectingVisitor;
+    use crate::test_fixtures::cube_grid;
+
+    #[test]
+    fn query_expands_only_its_path() {
+        let lazy = LazyBspTree::from_polygons(cube_grid(6));
+        let full = BspTree::from_polygons(cube_grid(6));
+        assert_eq!(lazy.expanded_count(), 0);
+
+        let corner = Point3::new(0.5, 0.5, 0.5);
+        let path = lazy.trace(corner);
+
+        assert_eq!(path, full.trace(corner));
+        assert_eq!(lazy.expanded_count(), path.len());
+        assert!(lazy.expanded_count() < full.to_plane_tree().node_count() / 4);
+        assert!(lazy.contains_point(corner));
+
+        // Repeating the query expands nothing new
+        lazy.trace(corner);
+        assert_eq!(lazy.expanded_count(), path.len());
+    }
+
+    #[test]
+    fn traversal_matches_full_tree() {
+        let lazy = LazyBspTree::from_polygons(cube_grid(6));
+        let full = BspTree::from_polygons(cube_grid(6));
+        let eye = Point3::new(-4.0, 7.0, 3.0);
+
+        let mut expected = CollectingVisitor::new();
+        full.traverse_back_to_front(eye, &mut expected);
+        let mut actual = CollectingVisitor::new();
+        lazy.traverse_back_to_front(eye, &mut actual);
+        assert_eq!(actual.polygons(), expected.polygons());
+
+        let mut expected = CollectingVisitor::new();
+        full.traverse_front_to_back(eye, &mut expected);
+        let mut actual = CollectingVisitor::new();
+        lazy.traverse_front_to_back(eye, &mut actual);
+        assert_eq!(actual.polygons(), expected.polygons());
+
+        assert_eq!(lazy.expanded_count(), full.to_plane_tree().node_count());
+    }
+
+    #[test]
+    fn to_tree_matches_eager_build() {
+        let lazy = LazyBspTree::from_polygons(cube_grid(6));
+        let full = BspTree::from_polygons(cube_grid(6));
+
+        let tree = lazy.to_tree();
+
+        assert_eq!(tree.polygon_count(), full.polygon_count());
+        assert_eq!(tree.depth(), full.depth());
+        assert_eq!(tree.collect_polygons(), full.collect_polygons());
+    }
+
+    #[test]
+    fn empty_lazy_tree() {
+        let lazy = LazyBspTree::from_polygons(Vec::new());
+        assert!(lazy.trace(Point3::origin()).is_empty());
+        assert!(!lazy.contains_point(Point3::origin()));
+        assert!(lazy.to_tree().is_empty());
+        assert_eq!(lazy.expanded_count(), 0);
+    }
+}
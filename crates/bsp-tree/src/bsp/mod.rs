@@ -30,16 +30,54 @@
 //!
 //! - [`BspTree`]: The main container holding the root node
 //! - [`BspNode`]: Internal nodes storing a splitting plane and coplanar polygons
+//! - [`BuildOptions`]: Configuration for tree construction
+//! - [`BuildProgress`]: A tree build resumed across calls with a time budget
+//! - [`LazyBspTree`]: A tree that splits nodes on first access
+//! - [`PlaneTree`]: The partition planes of a tree, without polygons
+//! - [`Provenance`]: Which input polygon each of a tree's polygons came from
 //! - [`PlaneSelector`]: Strategy trait for choosing splitting planes
 //! - [`BspVisitor`]: Visitor trait for custom traversal behavior
+//! - [`BspNodeVisitor`]: Visitor that also sees each node's plane and depth
+//! - [`TraversalCache`]: Traversal order cached across eye movements
+//! - [`TraversalCursor`]: Front-to-back traversal consumed in bounded chunks
+//! - [`TreeStats`]: Shape statistics of a built tree
+//! - [`BuildReport`]: How many polygons a tree build cut
 
+mod binary;
+mod budgeted;
+mod cached;
+mod cursor;
+mod iter;
+mod lazy;
 mod node;
+mod options;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod plane_tree;
+mod provenance;
+mod query;
 mod selector;
+mod stats;
 mod tree;
 mod visitor;
 
 // Re-export main types
-pub use node::{faces_same_direction, BspNode};
-pub use selector::{FirstPolygon, PlaneSelector};
+pub use budgeted::BuildProgress;
+pub use cached::TraversalCache;
+pub use cursor::TraversalCursor;
+pub use lazy::LazyBspTree;
+pub use node::{
+    faces_same_direction, faces_same_direction_with_epsilon, BspNode, Direction, NodeId,
+    FACING_EPSILON,
+};
+pub use options::BuildOptions;
+pub use plane_tree::{PlaneNode, PlaneTree};
+pub use provenance::Provenance;
+pub use query::InsertLocation;
+pub use selector::{
+    AxisAlignedSelector, BalancedSelector, CostSelector, FirstPolygon, MinSplitSelector,
+    PlaneSelector, RandomSelector, SampledSelector,
+};
+pub use stats::{BuildReport, TreeStats};
 pub use tree::BspTree;
-pub use visitor::{BspVisitor, CollectingVisitor, FnVisitor};
+pub use visitor::{BspNodeVisitor, BspVisitor, CollectingVisitor, FnVisitor};
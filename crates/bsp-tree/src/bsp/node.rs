@@ -1,6 +1,26 @@
 //! BSP tree node implementation.
 
-use crate::{Plane3D, Polygon};
+use nalgebra::{Isometry3, Point3};
+
+use crate::{Aabb, Plane3D, Polygon};
+
+/// Identifier of a node within a [`BspTree`](super::BspTree).
+///
+/// Ids are assigned in depth-first pre-order (node, front subtree, back
+/// subtree) when the tree is constructed, so the root is always `NodeId(0)`.
+/// Nodes created directly with [`BspNode::new`] have id 0 until they are
+/// part of a built tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+/// Which child was taken when stepping down from a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The front child (positive side of the splitting plane)
+    Front,
+    /// The back child (negative side of the splitting plane)
+    Back,
+}
 
 /// A node in the BSP tree.
 ///
@@ -17,51 +37,88 @@ use crate::{Plane3D, Polygon};
 ///
 /// This distinction is important for CSG operations where polygon
 /// facing determines inside/outside classification.
+///
+/// A node capped by the [maximum depth](super::BuildOptions::with_max_depth)
+/// or [leaf polygon budget](super::BuildOptions::with_max_leaf_polygons)
+/// keeps the polygons that would have gone into its subtrees, unsplit, in a
+/// separate [`leftover`](Self::leftover) list.
 #[derive(Debug, Clone)]
-pub struct BspNode {
+pub struct BspNode<T = ()> {
+    /// Identifier of this node within its tree.
+    id: NodeId,
+
     /// The splitting plane for this node.
     plane: Plane3D,
 
     /// Polygons coplanar with the plane, facing the SAME direction as the plane normal.
-    coplanar_front: Vec<Polygon>,
+    coplanar_front: Vec<Polygon<T>>,
 
     /// Polygons coplanar with the plane, facing the OPPOSITE direction as the plane normal.
-    coplanar_back: Vec<Polygon>,
+    coplanar_back: Vec<Polygon<T>>,
+
+    /// Polygons kept unsplit at a capped node, NOT on the plane.
+    leftover: Vec<Polygon<T>>,
 
     /// Subtree containing polygons in FRONT of the splitting plane.
-    front: Option<Box<BspNode>>,
+    front: Option<Box<BspNode<T>>>,
 
     /// Subtree containing polygons BEHIND the splitting plane.
-    back: Option<Box<BspNode>>,
+    back: Option<Box<BspNode<T>>>,
+
+    /// The input polygon whose plane was chosen as the splitting plane, if known.
+    splitter: Option<Polygon<T>>,
+
+    /// Segments along which this node's plane cut polygons, if recorded.
+    cut_edges: Vec<(Point3<f32>, Point3<f32>)>,
+
+    /// Bounding box of all polygons in this subtree, `None` if there are none.
+    bounds: Option<Aabb>,
 }
 
 impl BspNode {
     /// Creates a new BSP node with the given splitting plane.
     ///
-    /// The node starts with no coplanar polygons and no children.
+    /// The node starts with no coplanar polygons and no children. For a node
+    /// whose polygons carry a payload, use
+    /// [`with_coplanar`](Self::with_coplanar) with empty lists.
     pub fn new(plane: Plane3D) -> Self {
-        Self {
-            plane,
-            coplanar_front: Vec::new(),
-            coplanar_back: Vec::new(),
-            front: None,
-            back: None,
-        }
+        Self::with_coplanar(plane, Vec::new(), Vec::new())
     }
+}
 
+impl<T> BspNode<T> {
     /// Creates a new BSP node with a splitting plane and initial coplanar polygons.
     pub fn with_coplanar(
         plane: Plane3D,
-        coplanar_front: Vec<Polygon>,
-        coplanar_back: Vec<Polygon>,
+        coplanar_front: Vec<Polygon<T>>,
+        coplanar_back: Vec<Polygon<T>>,
     ) -> Self {
-        Self {
+        let mut node = Self {
+            id: NodeId::default(),
             plane,
             coplanar_front,
             coplanar_back,
+            leftover: Vec::new(),
             front: None,
             back: None,
-        }
+            splitter: None,
+            cut_edges: Vec::new(),
+            bounds: None,
+        };
+        node.update_bounds();
+        node
+    }
+
+    /// Returns this node's identifier within its tree.
+    #[inline]
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Sets this node's identifier.
+    #[inline]
+    pub(crate) fn set_id(&mut self, id: NodeId) {
+        self.id = id;
     }
 
     /// Returns a reference to the splitting plane.
@@ -70,20 +127,62 @@ impl BspNode {
         &self.plane
     }
 
+    /// Returns the polygon that was selected as the splitter for this node.
+    ///
+    /// Set by [`BspTree::build`](super::BspTree::build); `None` for nodes
+    /// constructed by hand. The splitter is also stored among the node's
+    /// coplanar polygons.
+    #[inline]
+    pub fn splitter(&self) -> Option<&Polygon<T>> {
+        self.splitter.as_ref()
+    }
+
+    /// Sets the polygon recorded as this node's splitter.
+    #[inline]
+    pub fn set_splitter(&mut self, splitter: Option<Polygon<T>>) {
+        self.splitter = splitter;
+    }
+
+    /// Returns the segments along which this node's plane cut spanning
+    /// polygons, one per cut polygon.
+    ///
+    /// Recorded when building with
+    /// [`BuildOptions::with_cut_edges`](super::BuildOptions::with_cut_edges)
+    /// and for every polygon [`BspTree::insert`](super::BspTree::insert)
+    /// cuts; [`BspTree::remove_matching`](super::BspTree::remove_matching)
+    /// drops the edges of polygons it removes. Empty for nodes constructed by
+    /// hand.
+    #[inline]
+    pub fn cut_edges(&self) -> &[(Point3<f32>, Point3<f32>)] {
+        &self.cut_edges
+    }
+
+    /// Sets the segments recorded as this node's cut edges.
+    #[inline]
+    pub(crate) fn set_cut_edges(&mut self, cut_edges: Vec<(Point3<f32>, Point3<f32>)>) {
+        self.cut_edges = cut_edges;
+    }
+
+    /// Records one more cut edge on this node.
+    #[inline]
+    pub(crate) fn add_cut_edge(&mut self, edge: (Point3<f32>, Point3<f32>)) {
+        self.cut_edges.push(edge);
+    }
+
     /// Returns coplanar polygons facing the same direction as the plane normal.
     #[inline]
-    pub fn coplanar_front(&self) -> &[Polygon] {
+    pub fn coplanar_front(&self) -> &[Polygon<T>] {
         &self.coplanar_front
     }
 
     /// Returns coplanar polygons facing opposite to the plane normal.
     #[inline]
-    pub fn coplanar_back(&self) -> &[Polygon] {
+    pub fn coplanar_back(&self) -> &[Polygon<T>] {
         &self.coplanar_back
     }
 
     /// Returns all coplanar polygons at this node (both front and back facing).
-    pub fn all_coplanar(&self) -> impl Iterator<Item = &Polygon> {
+    pub fn all_coplanar(&self) -> impl Iterator<Item = &Polygon<T>> {
         self.coplanar_front.iter().chain(self.coplanar_back.iter())
     }
 
@@ -92,102 +191,414 @@ impl BspNode {
         self.coplanar_front.len() + self.coplanar_back.len()
     }
 
+    /// Returns the polygons kept unsplit at this node because it was capped
+    /// by the [maximum depth](super::BuildOptions::with_max_depth) or
+    /// [leaf polygon budget](super::BuildOptions::with_max_leaf_polygons).
+    ///
+    /// They lie anywhere in the node's region rather than on its plane, so
+    /// the plane says nothing about where they are. Empty for uncapped nodes.
+    #[inline]
+    pub fn leftover(&self) -> &[Polygon<T>] {
+        &self.leftover
+    }
+
+    /// Sets the polygons kept unsplit at this node.
+    pub(crate) fn set_leftover(&mut self, leftover: Vec<Polygon<T>>) {
+        self.leftover = leftover;
+        self.update_bounds();
+    }
+
+    /// Removes and returns the polygons kept unsplit at this node.
+    pub(crate) fn take_leftover(&mut self) -> Vec<Polygon<T>> {
+        let taken = std::mem::take(&mut self.leftover);
+        self.update_bounds();
+        taken
+    }
+
+    /// Returns every polygon stored at this node: the coplanar polygons,
+    /// then the [leftover](Self::leftover) ones.
+    pub fn polygons(&self) -> impl Iterator<Item = &Polygon<T>> {
+        self.all_coplanar().chain(self.leftover.iter())
+    }
+
+    /// Returns every polygon stored at this node for modification.
+    ///
+    /// Leaves the cached [`bounds`](Self::bounds) stale.
+    pub(crate) fn polygons_mut(&mut self) -> impl Iterator<Item = &mut Polygon<T>> {
+        self.coplanar_front
+            .iter_mut()
+            .chain(self.coplanar_back.iter_mut())
+            .chain(self.leftover.iter_mut())
+    }
+
     /// Returns a reference to the front child subtree.
     #[inline]
-    pub fn front(&self) -> Option<&BspNode> {
+    pub fn front(&self) -> Option<&BspNode<T>> {
         self.front.as_deref()
     }
 
     /// Returns a reference to the back child subtree.
     #[inline]
-    pub fn back(&self) -> Option<&BspNode> {
+    pub fn back(&self) -> Option<&BspNode<T>> {
         self.back.as_deref()
     }
 
     /// Returns a mutable reference to the front child subtree.
     #[inline]
-    pub fn front_mut(&mut self) -> Option<&mut BspNode> {
+    pub fn front_mut(&mut self) -> Option<&mut BspNode<T>> {
         self.front.as_deref_mut()
     }
 
     /// Returns a mutable reference to the back child subtree.
     #[inline]
-    pub fn back_mut(&mut self) -> Option<&mut BspNode> {
+    pub fn back_mut(&mut self) -> Option<&mut BspNode<T>> {
         self.back.as_deref_mut()
     }
 
+    /// Calls `f` on every node of the subtree in depth-first pre-order
+    /// (node, front subtree, back subtree).
+    ///
+    /// Uses an explicit stack rather than recursion, so that very deep trees
+    /// do not overflow the call stack. `f` runs before a node's children are
+    /// visited, so it may rearrange them.
+    pub(crate) fn for_each_node_mut<F: FnMut(&mut BspNode<T>)>(&mut self, mut f: F) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            f(node);
+            // Pushed in reverse, so the front child is visited next
+            stack.extend(node.back.as_deref_mut());
+            stack.extend(node.front.as_deref_mut());
+        }
+    }
+
     /// Sets the front child subtree.
-    #[inline]
-    pub fn set_front(&mut self, node: Option<BspNode>) {
+    pub fn set_front(&mut self, node: Option<BspNode<T>>) {
         self.front = node.map(Box::new);
+        self.update_bounds();
     }
 
     /// Sets the back child subtree.
-    #[inline]
-    pub fn set_back(&mut self, node: Option<BspNode>) {
+    pub fn set_back(&mut self, node: Option<BspNode<T>>) {
         self.back = node.map(Box::new);
+        self.update_bounds();
     }
 
     /// Adds a polygon to the coplanar front list.
-    #[inline]
-    pub fn add_coplanar_front(&mut self, polygon: Polygon) {
+    pub fn add_coplanar_front(&mut self, polygon: Polygon<T>) {
+        self.include_in_bounds(&polygon);
         self.coplanar_front.push(polygon);
     }
 
     /// Adds a polygon to the coplanar back list.
-    #[inline]
-    pub fn add_coplanar_back(&mut self, polygon: Polygon) {
+    pub fn add_coplanar_back(&mut self, polygon: Polygon<T>) {
+        self.include_in_bounds(&polygon);
         self.coplanar_back.push(polygon);
     }
 
+    /// Removes and returns this node's coplanar polygons as
+    /// `(coplanar_front, coplanar_back)`.
+    pub(crate) fn take_coplanar(&mut self) -> (Vec<Polygon<T>>, Vec<Polygon<T>>) {
+        let taken = (
+            std::mem::take(&mut self.coplanar_front),
+            std::mem::take(&mut self.coplanar_back),
+        );
+        self.update_bounds();
+        taken
+    }
+
+    /// Returns the bounding box of all polygons in this subtree, or `None`
+    /// if the subtree holds no polygons.
+    ///
+    /// The box is cached: it is kept up to date by this node's own methods,
+    /// and by [`BspTree`](super::BspTree) methods for the nodes they modify.
+    /// After changing a descendant through [`front_mut`](Self::front_mut) or
+    /// [`back_mut`](Self::back_mut), call [`update_bounds`](Self::update_bounds)
+    /// on every node on the path back up.
+    #[inline]
+    pub fn bounds(&self) -> Option<&Aabb> {
+        self.bounds.as_ref()
+    }
+
+    /// Recomputes the cached bounding box from this node's polygons and its
+    /// children's cached boxes.
+    pub fn update_bounds(&mut self) {
+        let children = [self.front(), self.back()]
+            .into_iter()
+            .flatten()
+            .filter_map(|child| child.bounds);
+        self.bounds = self
+            .polygons()
+            .map(Polygon::aabb)
+            .chain(children)
+            .reduce(|a, b| a.union(&b));
+    }
+
+    /// Recomputes the cached bounding boxes of every node in the subtree.
+    ///
+    /// Works bottom-up without recursion: in pre-order a node's children
+    /// come after it, so walking the order backwards settles both children's
+    /// boxes before their parent's.
+    pub(crate) fn update_subtree_bounds(&mut self) {
+        let nodes: Vec<&BspNode<T>> = self.descendants().map(|(node, _)| node).collect();
+        let mut sizes = vec![1; nodes.len()];
+        let mut bounds: Vec<Option<Aabb>> = vec![None; nodes.len()];
+        for (i, node) in nodes.iter().enumerate().rev() {
+            let front = node.front.is_some().then_some(i + 1);
+            let back = node
+                .back
+                .is_some()
+                .then(|| i + 1 + front.map_or(0, |front| sizes[front]));
+            let children = [front, back].into_iter().flatten();
+            sizes[i] = 1 + children.clone().map(|child| sizes[child]).sum::<usize>();
+            bounds[i] = node
+                .polygons()
+                .map(Polygon::aabb)
+                .chain(children.filter_map(|child| bounds[child]))
+                .reduce(|a, b| a.union(&b));
+        }
+
+        let mut bounds = bounds.into_iter();
+        self.for_each_node_mut(|node| node.bounds = bounds.next().flatten());
+    }
+
+    /// Grows the cached bounding box to include `polygon`.
+    fn include_in_bounds(&mut self, polygon: &Polygon<T>) {
+        let aabb = polygon.aabb();
+        self.bounds = Some(self.bounds.map_or(aabb, |bounds| bounds.union(&aabb)));
+    }
+
+    /// Turns the subtree inside out: flips every plane and polygon and swaps
+    /// the front and back children, recursively.
+    ///
+    /// The plane is flipped with [`Plane3D::flipped`] and each polygon with
+    /// [`Polygon::flipped`]. A flipped polygon still faces the same way as
+    /// the flipped plane, so coplanar polygons stay in their lists; only
+    /// their orientation relative to the original plane changes.
+    pub fn invert(&mut self)
+    where
+        T: Clone,
+    {
+        self.for_each_node_mut(|node| {
+            node.plane = node.plane.flipped();
+            for polygon in node.polygons_mut() {
+                *polygon = polygon.flipped();
+            }
+            node.splitter = node.splitter.as_ref().map(Polygon::flipped);
+            std::mem::swap(&mut node.front, &mut node.back);
+        });
+    }
+
+    /// Moves this node's plane, polygons and splitter by a rigid
+    /// motion. Children and the cached bounds are not affected.
+    pub(crate) fn transform_local(&mut self, iso: &Isometry3<f32>)
+    where
+        T: Clone,
+    {
+        self.plane = self.plane.transformed(iso);
+        for polygon in self.polygons_mut() {
+            *polygon = polygon.transformed(iso);
+        }
+        self.splitter = self.splitter.as_ref().map(|s| s.transformed(iso));
+        for (a, b) in &mut self.cut_edges {
+            *a = iso * *a;
+            *b = iso * *b;
+        }
+    }
+
+    /// Joins this node's coplanar polygons that share a full edge, as
+    /// [`merge_coplanar`](crate::merge_coplanar) does, in each of the two
+    /// coplanar lists. Children are not affected.
+    pub fn merge_coplanar(&mut self, epsilon: f32)
+    where
+        T: Clone + PartialEq,
+    {
+        self.coplanar_front =
+            crate::merge_coplanar(std::mem::take(&mut self.coplanar_front), epsilon);
+        self.coplanar_back =
+            crate::merge_coplanar(std::mem::take(&mut self.coplanar_back), epsilon);
+    }
+
     /// Checks if this node has any children.
     #[inline]
     pub fn is_leaf(&self) -> bool {
         self.front.is_none() && self.back.is_none()
     }
 
+    /// Returns the polygons stored at this node if it is a leaf, or nothing
+    /// for an interior node.
+    ///
+    /// A leaf built with a [maximum depth](super::BuildOptions::with_max_depth)
+    /// or [leaf polygon budget](super::BuildOptions::with_max_leaf_polygons)
+    /// can hold polygons that are not coplanar with its plane, see
+    /// [`leftover`](Self::leftover). They are in no particular order, so a
+    /// renderer has to sort them itself, e.g. by distance to the eye.
+    ///
+    /// Unlike [`BspTree::leaf_polygons`](super::BspTree::leaf_polygons),
+    /// which groups polygons by the empty cells of space below the nodes,
+    /// this only looks at what the node stores.
+    pub fn leaf_contents(&self) -> impl Iterator<Item = &Polygon<T>> {
+        self.is_leaf()
+            .then(|| self.polygons())
+            .into_iter()
+            .flatten()
+    }
+
     /// Returns the total number of polygons in this subtree (including all descendants).
     pub fn polygon_count(&self) -> usize {
-        let mut count = self.coplanar_count();
+        self.descendants()
+            .map(|(node, _)| node.polygons().count())
+            .sum()
+    }
 
-        if let Some(ref front) = self.front {
-            count += front.polygon_count();
-        }
-        if let Some(ref back) = self.back {
-            count += back.polygon_count();
+    /// Returns the bounding box of all polygons in this subtree.
+    ///
+    /// Returns `None` if the subtree contains no polygons. This is the
+    /// cached box from [`bounds`](Self::bounds).
+    pub fn subtree_aabb(&self) -> Option<Aabb> {
+        self.bounds
+    }
+
+    /// Returns the extents of the front and back subtrees along this node's
+    /// normal, as `((front_min, front_max), (back_min, back_max))`.
+    ///
+    /// Extents are signed distances from the splitting plane, computed by
+    /// projecting each subtree's bounding box onto the normal, so they may
+    /// overestimate the geometry slightly for planes that are not
+    /// axis-aligned. A renderer can skip a subtree whose whole range lies
+    /// behind an occluder. A missing or empty subtree yields the empty range
+    /// `(f32::INFINITY, f32::NEG_INFINITY)`.
+    pub fn child_extents(&self) -> ((f32, f32), (f32, f32)) {
+        let extent = |child: Option<&BspNode<T>>| match child.and_then(|n| n.subtree_aabb()) {
+            Some(aabb) => self.plane.distance_range(&aabb),
+            None => (f32::INFINITY, f32::NEG_INFINITY),
+        };
+        (extent(self.front()), extent(self.back()))
+    }
+
+    /// Estimates the surface area of the region this node covers.
+    ///
+    /// The region of a BSP node is an (often unbounded) convex cell, so it is
+    /// approximated by the bounding box of the polygons in its subtree.
+    /// Returns 0 for a subtree without polygons.
+    pub fn region_surface_area(&self) -> f32 {
+        self.subtree_aabb().map_or(0.0, |aabb| aabb.surface_area())
+    }
+
+    /// Returns this subtree with every polygon's payload replaced by `f`
+    /// applied to it, keeping the partition, ids and bounds.
+    ///
+    /// Uses an explicit stack rather than recursion, like [`Drop`].
+    pub(crate) fn map_data<U, F: FnMut(T) -> U>(self, mut f: F) -> BspNode<U> {
+        // Flatten into pre-order, remembering which children each node had
+        let mut flat = Vec::new();
+        let mut stack = vec![self];
+        while let Some(mut node) = stack.pop() {
+            let (front, back) = (node.front.take(), node.back.take());
+            flat.push((node, front.is_some(), back.is_some()));
+            stack.extend(back.map(|b| *b));
+            stack.extend(front.map(|f| *f));
         }
 
-        count
+        // Rebuild bottom-up: in reverse pre-order, a node's back subtree is
+        // finished first and its front subtree last
+        let mut built: Vec<BspNode<U>> = Vec::new();
+        while let Some((mut node, has_front, has_back)) = flat.pop() {
+            let mut mapped = BspNode::with_coplanar(node.plane.clone(), Vec::new(), Vec::new());
+            mapped.id = node.id;
+            mapped.coplanar_front = std::mem::take(&mut node.coplanar_front)
+                .into_iter()
+                .map(|p| p.map_data(&mut f))
+                .collect();
+            mapped.coplanar_back = std::mem::take(&mut node.coplanar_back)
+                .into_iter()
+                .map(|p| p.map_data(&mut f))
+                .collect();
+            mapped.leftover = std::mem::take(&mut node.leftover)
+                .into_iter()
+                .map(|p| p.map_data(&mut f))
+                .collect();
+            mapped.splitter = node.splitter.take().map(|p| p.map_data(&mut f));
+            mapped.cut_edges = std::mem::take(&mut node.cut_edges);
+            mapped.bounds = node.bounds;
+            if has_front {
+                mapped.front = built.pop().map(Box::new);
+            }
+            if has_back {
+                mapped.back = built.pop().map(Box::new);
+            }
+            built.push(mapped);
+        }
+        built.pop().expect("a subtree has at least its root")
     }
 
     /// Returns the depth of this subtree (1 for a leaf node).
     pub fn depth(&self) -> usize {
-        let front_depth = self.front.as_ref().map_or(0, |n| n.depth());
-        let back_depth = self.back.as_ref().map_or(0, |n| n.depth());
-        1 + front_depth.max(back_depth)
+        self.descendants()
+            .map(|(_, depth)| depth)
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+impl<T> Drop for BspNode<T> {
+    /// Drops the subtree with an explicit stack, as the default recursive
+    /// drop of the child boxes overflows the stack on very deep trees.
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        stack.extend(self.front.take());
+        stack.extend(self.back.take());
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.front.take());
+            stack.extend(node.back.take());
+        }
     }
 }
 
+/// Default tolerance for [`faces_same_direction`].
+///
+/// Unit normals whose dot product lies within this distance of zero are
+/// treated as a tie and count as facing the same direction.
+pub const FACING_EPSILON: f32 = 1e-6;
+
 /// Determines if a polygon faces the same direction as a plane.
 ///
 /// Compares the polygon's normal to the plane's normal using the dot product.
-/// Returns `true` if the normals point in roughly the same direction (dot > 0).
+/// Returns `true` if the normals point in roughly the same direction.
+///
+/// Uses the default [`FACING_EPSILON`] tolerance, so a near-zero dot product
+/// caused by float error in a near-degenerate fragment always sorts to the
+/// front instead of flipping between front and back.
+///
+/// # Panics
+///
+/// Panics if the polygon has a degenerate (zero-length) normal.
+#[inline]
+pub fn faces_same_direction<T>(polygon: &Polygon<T>, plane: &Plane3D) -> bool {
+    faces_same_direction_with_epsilon(polygon, plane, FACING_EPSILON)
+}
+
+/// Determines if a polygon faces the same direction as a plane, treating
+/// dot products within `epsilon` of zero as facing the same direction.
 ///
 /// # Panics
 ///
 /// Panics if the polygon has a degenerate (zero-length) normal.
 #[inline]
-pub fn faces_same_direction(polygon: &Polygon, plane: &Plane3D) -> bool {
+pub fn faces_same_direction_with_epsilon<T>(
+    polygon: &Polygon<T>,
+    plane: &Plane3D,
+    epsilon: f32,
+) -> bool {
     let poly_normal = polygon
         .unit_normal()
         .expect("Polygon must have a valid normal for BSP operations");
-    poly_normal.dot(&plane.normal()) > 0.0
+    poly_normal.dot(&plane.normal()) > -epsilon
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::cube;
     use nalgebra::{Point3, Vector3};
 
     fn make_triangle(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Polygon {
@@ -209,6 +620,14 @@ mod tests {
         assert_eq!(node.depth(), 1);
     }
 
+    #[test]
+    fn new_node_has_no_splitter() {
+        let plane = Plane3D::new(Vector3::new(0.0, 1.0, 0.0), 0.0);
+        let node = BspNode::new(plane);
+
+        assert!(node.splitter().is_none());
+    }
+
     #[test]
     fn with_coplanar_stores_polygons() {
         let plane = Plane3D::new(Vector3::new(0.0, 1.0, 0.0), 0.0);
@@ -239,6 +658,28 @@ mod tests {
         assert!(!node.is_leaf());
     }
 
+    #[test]
+    fn bounds_follow_polygons_and_children() {
+        let plane = Plane3D::new(Vector3::new(0.0, 0.0, 1.0), 0.0);
+        let mut node = BspNode::new(plane.clone());
+        assert!(node.bounds().is_none());
+
+        let own = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        node.add_coplanar_front(own);
+        assert_eq!(node.bounds().unwrap().max(), Point3::new(1.0, 1.0, 0.0));
+
+        let above = make_triangle([0.0, 0.0, 2.0], [3.0, 0.0, 2.0], [0.0, 1.0, 2.0]);
+        let child = BspNode::with_coplanar(above.plane(), vec![above], vec![]);
+        node.set_front(Some(child));
+        assert_eq!(node.bounds().unwrap().max(), Point3::new(3.0, 1.0, 2.0));
+
+        node.set_front(None);
+        assert_eq!(node.bounds().unwrap().max(), Point3::new(1.0, 1.0, 0.0));
+
+        node.take_coplanar();
+        assert!(node.bounds().is_none());
+    }
+
     #[test]
     fn depth_calculation() {
         let plane = Plane3D::new(Vector3::new(0.0, 1.0, 0.0), 0.0);
@@ -302,4 +743,69 @@ mod tests {
         // Same direction as plane normal
         assert!(faces_same_direction(&poly, &plane));
     }
+
+    #[test]
+    fn faces_same_direction_near_zero_dot_sorts_to_front() {
+        // Polygon normal is +Y; the planes' normals are almost perpendicular
+        let poly = make_triangle([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+        let up = Plane3D::new(Vector3::new(1.0, 1e-8, 0.0), 0.0);
+        let down = Plane3D::new(Vector3::new(1.0, -1e-8, 0.0), 0.0);
+
+        assert!(faces_same_direction(&poly, &up));
+        assert!(faces_same_direction(&poly, &down));
+
+        // Without the tie band the sign of the dot product decides
+        assert!(faces_same_direction_with_epsilon(&poly, &up, 0.0));
+        assert!(!faces_same_direction_with_epsilon(&poly, &down, 0.0));
+    }
+
+    #[test]
+    fn invert_flips_planes_and_swaps_children() {
+        let plane = Plane3D::new(Vector3::new(0.0, 0.0, 1.0), 0.0);
+        let facing_up = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let above = make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        let mut node = BspNode::with_coplanar(plane.clone(), vec![facing_up.clone()], vec![]);
+        let child = BspNode::with_coplanar(above.plane(), vec![above], vec![]);
+        node.set_front(Some(child));
+
+        node.invert();
+
+        assert_eq!(node.plane(), &plane.flipped());
+        let kept = &node.coplanar_front()[0];
+        assert_eq!(node.coplanar_front().len(), 1);
+        assert_eq!(kept, &facing_up.flipped());
+        assert!(node.coplanar_back().is_empty());
+        assert!(faces_same_direction(kept, node.plane()));
+        assert!(node.front().is_none());
+        let back = node.back().unwrap();
+        assert_eq!(back.plane().normal(), Vector3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn child_extents_of_node_splitting_cube() {
+        // A wall at x = 1 facing +X, then the faces of the cube [0, 2]^3
+        let mut polygons = vec![Polygon::new(vec![
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(1.0, 3.0, -1.0),
+            Point3::new(1.0, 3.0, 3.0),
+            Point3::new(1.0, -1.0, 3.0),
+        ])];
+        polygons.extend(cube());
+        let tree = crate::BspTree::from_polygons(polygons);
+        let root = tree.root().unwrap();
+
+        let ((front_min, front_max), (back_min, back_max)) = root.child_extents();
+
+        assert!(front_min.abs() < 1e-5 && (front_max - 1.0).abs() < 1e-5);
+        assert!((back_min + 1.0).abs() < 1e-5 && back_max.abs() < 1e-5);
+    }
+
+    #[test]
+    fn child_extents_of_leaf_are_empty() {
+        let plane = Plane3D::new(Vector3::new(0.0, 1.0, 0.0), 0.0);
+        let (front, back) = BspNode::new(plane).child_extents();
+
+        assert!(front.0 > front.1);
+        assert!(back.0 > back.1);
+    }
 }
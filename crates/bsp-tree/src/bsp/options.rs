@@ -0,0 +1,162 @@
+//! Configuration for BSP tree construction.
+
+use super::node::FACING_EPSILON;
+use crate::PLANE_EPSILON;
+
+/// Options controlling how a [`BspTree`](super::BspTree) is built.
+///
+/// The defaults reproduce [`BspTree::build`](super::BspTree::build). Options
+/// are set with the builder-style `with_*` methods:
+///
+/// ```
+/// use bsp_tree::{BspTree, BuildOptions, FirstPolygon, Polygon};
+///
+/// let polygons: Vec<Polygon> = Vec::new();
+/// let options = BuildOptions::new().with_sorted_coplanar(true);
+/// let tree = BspTree::build_with_options(polygons, &FirstPolygon, &options);
+/// assert!(tree.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildOptions {
+    sort_coplanar: bool,
+    facing_epsilon: f32,
+    plane_epsilon: f32,
+    max_depth: Option<usize>,
+    max_leaf_polygons: Option<usize>,
+    record_cut_edges: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            sort_coplanar: false,
+            facing_epsilon: FACING_EPSILON,
+            plane_epsilon: PLANE_EPSILON,
+            max_depth: None,
+            max_leaf_polygons: None,
+            record_cut_edges: false,
+        }
+    }
+}
+
+impl BuildOptions {
+    /// Creates the default build options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sorts each node's coplanar polygon lists by a canonical geometric key
+    /// (centroid, then vertices, compared lexicographically).
+    ///
+    /// Without this, coplanar polygons keep their input order, so building
+    /// the same polygons in a different order can produce different orderings.
+    pub fn with_sorted_coplanar(mut self, sort: bool) -> Self {
+        self.sort_coplanar = sort;
+        self
+    }
+
+    /// Returns whether coplanar polygon lists are sorted.
+    #[inline]
+    pub fn sort_coplanar(&self) -> bool {
+        self.sort_coplanar
+    }
+
+    /// Sets the tie band used to sort coplanar polygons by facing.
+    ///
+    /// A coplanar polygon goes to a node's `coplanar_front` list when the dot
+    /// product of its unit normal with the plane normal exceeds `-epsilon`,
+    /// see [`faces_same_direction_with_epsilon`](super::faces_same_direction_with_epsilon).
+    /// This is independent of [`PLANE_EPSILON`](crate::PLANE_EPSILON), which
+    /// decides whether a polygon is coplanar at all. Defaults to
+    /// [`FACING_EPSILON`].
+    pub fn with_facing_epsilon(mut self, epsilon: f32) -> Self {
+        self.facing_epsilon = epsilon;
+        self
+    }
+
+    /// Returns the tie band used to sort coplanar polygons by facing.
+    #[inline]
+    pub fn facing_epsilon(&self) -> f32 {
+        self.facing_epsilon
+    }
+
+    /// Sets the distance within which a vertex counts as lying on a
+    /// splitting plane, when classifying and splitting polygons.
+    ///
+    /// Scale it with the scene: a tolerance suited to kilometer-sized
+    /// geometry merges millimeter details, and one suited to millimeters
+    /// splits large near-coplanar faces. Defaults to
+    /// [`PLANE_EPSILON`].
+    pub fn with_plane_epsilon(mut self, epsilon: f32) -> Self {
+        self.plane_epsilon = epsilon;
+        self
+    }
+
+    /// Returns the distance within which a vertex counts as lying on a
+    /// splitting plane.
+    #[inline]
+    pub fn plane_epsilon(&self) -> f32 {
+        self.plane_epsilon
+    }
+
+    /// Limits the depth of the tree, counting the root as depth 1, or
+    /// removes the limit with `None`.
+    ///
+    /// A node at the limit gets no children: the polygons that would have
+    /// gone into its front and back subtrees are kept unsplit in its
+    /// [`leftover`](super::BspNode::leftover) list instead. Traversals do not
+    /// order them relative to each other, and queries test each of them
+    /// against its own plane. This bounds the node count, split count and
+    /// recursion depth of pathological inputs, at the cost of exact ordering
+    /// in the capped nodes. A limit of 0 behaves like 1. Defaults to `None`.
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the depth limit of the tree, if any.
+    #[inline]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Stops splitting once at most `max` polygons remain for a node and
+    /// none of them spans the plane of another, or removes the budget with
+    /// `None`.
+    ///
+    /// Such a node becomes a leaf holding all of the polygons, unsplit: those
+    /// in front of or behind its plane are kept in its
+    /// [`leftover`](super::BspNode::leftover) list like at the
+    /// [maximum depth](Self::with_max_depth), see
+    /// [`BspNode::leaf_contents`](super::BspNode::leaf_contents). This
+    /// avoids the chains of small nodes that selectors like
+    /// [`FirstPolygon`](super::FirstPolygon) build for dense clusters of
+    /// closed shapes. Defaults to `None`.
+    pub fn with_max_leaf_polygons(mut self, max: Option<usize>) -> Self {
+        self.max_leaf_polygons = max;
+        self
+    }
+
+    /// Returns the number of polygons at or below which a node is left
+    /// unsplit, if any.
+    #[inline]
+    pub fn max_leaf_polygons(&self) -> Option<usize> {
+        self.max_leaf_polygons
+    }
+
+    /// Records, for each node, the segments along which its plane cut
+    /// spanning polygons, see [`BspNode::cut_edges`](super::BspNode::cut_edges).
+    ///
+    /// Meant for visualizing where a tree slices geometry. Defaults to
+    /// `false`.
+    pub fn with_cut_edges(mut self, record: bool) -> Self {
+        self.record_cut_edges = record;
+        self
+    }
+
+    /// Returns whether cut edges are recorded.
+    #[inline]
+    pub fn record_cut_edges(&self) -> bool {
+        self.record_cut_edges
+    }
+}
@@ -0,0 +1,107 @@
+//! Multi-threaded tree construction, behind the `rayon` feature.
+
+use crate::Polygon;
+
+use super::node::BspNode;
+use super::options::BuildOptions;
+use super::selector::PlaneSelector;
+use super::tree::{build_node, split_node, without_degenerate, BspTree, NodeSplit};
+
+/// Partitions smaller than this are built on the current thread, as the cost
+/// of handing them to another outweighs the work.
+const PARALLEL_THRESHOLD: usize = 1024;
+
+impl BspTree {
+    /// Builds a BSP tree like [`build`](Self::build), constructing the front
+    /// and back subtrees of large partitions in parallel with
+    /// [`rayon::join`].
+    ///
+    /// Selecting planes and cutting polygons have no shared state, so the
+    /// result is identical to [`build`](Self::build) with the same selector.
+    /// For polygons that carry a payload, use
+    /// [`build_parallel_with_options`](Self::build_parallel_with_options)
+    /// with the default options.
+    pub fn build_parallel<S: PlaneSelector + Sync>(polygons: Vec<Polygon>, selector: &S) -> Self {
+        Self::build_parallel_with_options(polygons, selector, &BuildOptions::default())
+    }
+}
+
+impl<T: Clone + Send> BspTree<T> {
+    /// Builds a BSP tree with custom [`BuildOptions`] like
+    /// [`build_parallel`](Self::build_parallel).
+    pub fn build_parallel_with_options<S: PlaneSelector<T> + Sync>(
+        polygons: Vec<Polygon<T>>,
+        selector: &S,
+        options: &BuildOptions,
+    ) -> Self {
+        let root = build_node_parallel(
+            without_degenerate(polygons),
+            selector,
+            options,
+            1,
+            PARALLEL_THRESHOLD,
+        );
+        Self::from_root(root)
+    }
+}
+
+/// Recursively builds a BSP node at `depth`, building its subtrees with
+/// [`rayon::join`] while the partition has at least `threshold` polygons.
+fn build_node_parallel<T: Clone + Send, S: PlaneSelector<T> + Sync>(
+    polygons: Vec<Polygon<T>>,
+    selector: &S,
+    options: &BuildOptions,
+    depth: usize,
+    threshold: usize,
+) -> Option<BspNode<T>> {
+    if polygons.len() < threshold {
+        return build_node(polygons, selector, options, depth, &mut 0);
+    }
+
+    let NodeSplit {
+        mut node,
+        front: front_list,
+        back: back_list,
+        ..
+    } = split_node(polygons, selector, options, depth)?;
+    let build = |polygons| build_node_parallel(polygons, selector, options, depth + 1, threshold);
+    let (front, back) = rayon::join(|| build(front_list), || build(back_list));
+    node.set_front(front);
+    node.set_back(back);
+    Some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::{BalancedSelector, FirstPolygon};
+    use crate::test_fixtures::cube_grid;
+
+    /// Checks that building in parallel at every partition of 8 or more
+    /// polygons gives the same tree as a sequential build.
+    fn assert_forking_matches<S: PlaneSelector + Sync>(selector: &S) {
+        let options = BuildOptions::default();
+        let parallel =
+            BspTree::from_root(build_node_parallel(cube_grid(4), selector, &options, 1, 8));
+        let sequential = BspTree::build(cube_grid(4), selector);
+
+        assert_eq!(parallel.to_plane_tree(), sequential.to_plane_tree());
+        assert_eq!(parallel.collect_polygons(), sequential.collect_polygons());
+        assert_eq!(parallel.aabb(), sequential.aabb());
+    }
+
+    #[test]
+    fn forking_build_matches_build() {
+        assert_forking_matches(&FirstPolygon);
+        assert_forking_matches(&BalancedSelector::default());
+    }
+
+    #[test]
+    fn build_parallel_matches_build() {
+        let parallel = BspTree::build_parallel(cube_grid(4), &FirstPolygon);
+        let sequential = BspTree::build(cube_grid(4), &FirstPolygon);
+        assert_eq!(parallel.collect_polygons(), sequential.collect_polygons());
+
+        assert!(BspTree::build_parallel(Vec::new(), &FirstPolygon).is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+//! Polygon-free BSP trees holding only the partition planes.
+
+use std::io::{self, Read, Write};
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{Plane3D, PlaneSide};
+
+use super::node::{BspNode, Direction};
+use super::tree::BspTree;
+
+/// Magic bytes at the start of a serialized [`PlaneTree`].
+const MAGIC: &[u8; 4] = b"BSPP";
+/// Current version of the serialized format.
+const VERSION: u8 = 1;
+
+/// Tag written in place of a missing child.
+const TAG_EMPTY: u8 = 0;
+/// Tag written before a present node.
+const TAG_NODE: u8 = 1;
+
+/// A lightweight BSP tree storing only splitting planes.
+///
+/// Obtained from [`BspTree::to_plane_tree`], it keeps the spatial partition
+/// of the full tree without any polygons, which is enough for point
+/// classification ([`trace`](Self::trace), [`contains_point`](Self::contains_point))
+/// and much smaller to store. Use [`write_to`](Self::write_to) and
+/// [`read_from`](Self::read_from) to serialize it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaneTree {
+    root: Option<PlaneNode>,
+}
+
+/// A node of a [`PlaneTree`]: a splitting plane and its children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaneNode {
+    plane: Plane3D,
+    front: Option<Box<PlaneNode>>,
+    back: Option<Box<PlaneNode>>,
+}
+
+impl PlaneNode {
+    /// Returns a reference to the splitting plane.
+    #[inline]
+    pub fn plane(&self) -> &Plane3D {
+        &self.plane
+    }
+
+    /// Returns a reference to the front child subtree.
+    #[inline]
+    pub fn front(&self) -> Option<&PlaneNode> {
+        self.front.as_deref()
+    }
+
+    /// Returns a reference to the back child subtree.
+    #[inline]
+    pub fn back(&self) -> Option<&PlaneNode> {
+        self.back.as_deref()
+    }
+
+    fn from_bsp_node<T>(node: &BspNode<T>) -> Self {
+        Self {
+            plane: node.plane().clone(),
+            front: node.front().map(|n| Box::new(Self::from_bsp_node(n))),
+            back: node.back().map(|n| Box::new(Self::from_bsp_node(n))),
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        1 + self.front().map_or(0, |n| n.node_count()) + self.back().map_or(0, |n| n.node_count())
+    }
+}
+
+impl PlaneTree {
+    /// Returns `true` if the tree has no planes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns a reference to the root node, if any.
+    #[inline]
+    pub fn root(&self) -> Option<&PlaneNode> {
+        self.root.as_ref()
+    }
+
+    /// Returns the number of planes in the tree.
+    pub fn node_count(&self) -> usize {
+        self.root.as_ref().map_or(0, |n| n.node_count())
+    }
+
+    /// Returns the front/back steps taken from the root to the leaf cell
+    /// containing `point`, exactly as [`BspTree::trace`].
+    pub fn trace(&self, point: Point3<f32>) -> Vec<Direction> {
+        let mut path = Vec::new();
+        let mut current = self.root.as_ref();
+        while let Some(node) = current {
+            current = match node.plane.classify_point(point) {
+                PlaneSide::Front | PlaneSide::OnPlane => {
+                    path.push(Direction::Front);
+                    node.front()
+                }
+                PlaneSide::Back => {
+                    path.push(Direction::Back);
+                    node.back()
+                }
+            };
+        }
+        path
+    }
+
+    /// Returns `true` if `point` lies inside the solid, exactly as
+    /// [`BspTree::contains_point`].
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.root.is_some() && self.trace(point).last() == Some(&Direction::Back)
+    }
+
+    /// Writes the tree in a compact binary format.
+    ///
+    /// The format is the magic bytes `BSPP`, a version byte, then the nodes in
+    /// pre-order. Each node is a tag byte (`1` for a node, `0` for a missing
+    /// child) followed, for nodes, by the plane's normal and offset as four
+    /// little-endian `f32`s.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_node(&mut writer, self.root.as_ref())
+    }
+
+    /// Reads a tree written by [`write_to`](Self::write_to).
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the data is not a
+    /// plane tree of a supported version.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a plane tree",
+            ));
+        }
+        if header[4] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported plane tree version {}", header[4]),
+            ));
+        }
+        Ok(Self {
+            root: read_node(&mut reader)?,
+        })
+    }
+}
+
+impl<T: Clone> BspTree<T> {
+    /// Extracts the partition planes of this tree, dropping all polygons.
+    pub fn to_plane_tree(&self) -> PlaneTree {
+        PlaneTree {
+            root: self.root().map(PlaneNode::from_bsp_node),
+        }
+    }
+}
+
+fn write_node<W: Write>(writer: &mut W, node: Option<&PlaneNode>) -> io::Result<()> {
+    let Some(node) = node else {
+        return writer.write_all(&[TAG_EMPTY]);
+    };
+
+    writer.write_all(&[TAG_NODE])?;
+    let normal = node.plane.normal();
+    for value in [normal.x, normal.y, normal.z, node.plane.offset()] {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    write_node(writer, node.front())?;
+    write_node(writer, node.back())
+}
+
+fn read_node<R: Read>(reader: &mut R) -> io::Result<Option<PlaneNode>> {
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    match tag[0] {
+        TAG_EMPTY => Ok(None),
+        TAG_NODE => {
+            let mut values = [0f32; 4];
+            for value in &mut values {
+                let mut bytes = [0u8; 4];
+                reader.read_exact(&mut bytes)?;
+                *value = f32::from_le_bytes(bytes);
+            }
+            let [x, y, z, offset] = values;
+            let normal = Vector3::new(x, y, z);
+            let is_unit = (normal.norm() - 1.0).abs() <= 1e-3;
+            if !is_unit {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "plane normal is not unit length",
+                ));
+            }
+
+            let front = read_node(reader)?.map(Box::new);
+            let back = read_node(reader)?.map(Box::new);
+            Ok(Some(PlaneNode {
+                plane: Plane3D::from_unit_normal(normal, offset),
+                front,
+                back,
+            }))
+        }
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid node tag {other}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::cube;
+
+    #[test]
+    fn contains_point_matches_full_tree() {
+        let tree = BspTree::from_polygons(cube());
+        let planes = tree.to_plane_tree();
+
+        assert_eq!(planes.node_count(), 6);
+
+        let samples = [
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.1, 1.9, 0.5),
+            Point3::new(3.0, 1.0, 1.0),
+            Point3::new(1.0, -1.0, 1.0),
+            Point3::new(-5.0, -5.0, -5.0),
+            Point3::new(1.0, 1.0, 2.5),
+        ];
+        for point in samples {
+            assert_eq!(planes.contains_point(point), tree.contains_point(point));
+            assert_eq!(planes.trace(point), tree.trace(point));
+        }
+        assert!(planes.contains_point(Point3::new(1.0, 1.0, 1.0)));
+        assert!(!planes.contains_point(Point3::new(3.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let planes = BspTree::from_polygons(cube()).to_plane_tree();
+
+        let mut bytes = Vec::new();
+        planes.write_to(&mut bytes).unwrap();
+        // Header, 6 nodes of 17 bytes, 7 empty child tags
+        assert_eq!(bytes.len(), 5 + 6 * 17 + 7);
+
+        let restored = PlaneTree::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(restored, planes);
+    }
+
+    #[test]
+    fn read_rejects_bad_magic() {
+        let err = PlaneTree::read_from(&b"NOPE\x01\x00"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn empty_tree_round_trip() {
+        let planes = BspTree::new().to_plane_tree();
+        assert!(planes.is_empty());
+
+        let mut bytes = Vec::new();
+        planes.write_to(&mut bytes).unwrap();
+        assert_eq!(PlaneTree::read_from(bytes.as_slice()).unwrap(), planes);
+    }
+}
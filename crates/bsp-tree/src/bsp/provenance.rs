@@ -0,0 +1,152 @@
+//! Mapping from a tree's polygons back to the input polygons they came from.
+
+use crate::Polygon;
+
+use super::selector::PlaneSelector;
+use super::tree::BspTree;
+
+/// A side table recording which input polygon each polygon of a tree came
+/// from.
+///
+/// Returned by [`BspTree::build_with_provenance`]. Polygons are identified by
+/// their index in [`BspTree::collect_polygons_ordered`]; inputs by their
+/// index in the vector passed to the build. A polygon split during
+/// construction yields several fragments, all mapping to the same input.
+///
+/// To carry data such as materials along with each fragment, attach it to
+/// the input polygons as a [payload](Polygon::with_data) instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    sources: Vec<usize>,
+}
+
+impl Provenance {
+    /// Returns the input index of the polygon at `index`, or `None` if the
+    /// index is out of range.
+    #[inline]
+    pub fn source(&self, index: usize) -> Option<usize> {
+        self.sources.get(index).copied()
+    }
+
+    /// Returns the input index of every polygon, in
+    /// [`collect_polygons_ordered`](BspTree::collect_polygons_ordered) order.
+    #[inline]
+    pub fn sources(&self) -> &[usize] {
+        &self.sources
+    }
+
+    /// Returns the indices of all polygons that came from input `source`.
+    pub fn fragments_of(&self, source: usize) -> impl Iterator<Item = usize> + '_ {
+        self.sources
+            .iter()
+            .enumerate()
+            .filter(move |(_, s)| **s == source)
+            .map(|(index, _)| index)
+    }
+
+    /// Returns the number of polygons in the tree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Returns `true` if the tree has no polygons.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+}
+
+impl BspTree {
+    /// Builds a tree and records where each of its polygons came from.
+    ///
+    /// The tree is identical to [`BspTree::build`] with the same selector;
+    /// the returned [`Provenance`] maps every polygon of it back to its input
+    /// index. The input index is carried through the build as each polygon's
+    /// [payload](Polygon::data), so the selector sees `Polygon<usize>`.
+    pub fn build_with_provenance<S: PlaneSelector<usize>>(
+        polygons: Vec<Polygon>,
+        selector: &S,
+    ) -> (BspTree, Provenance) {
+        let indexed = polygons
+            .into_iter()
+            .enumerate()
+            .map(|(index, polygon)| polygon.with_data(index))
+            .collect();
+        let tree = BspTree::build(indexed, selector);
+        let sources = tree
+            .collect_polygons_ordered()
+            .iter()
+            .map(|polygon| *polygon.data())
+            .collect();
+        (tree.map_data(|_| ()), Provenance { sources })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FirstPolygon;
+    use nalgebra::Point3;
+
+    /// Splits by the last polygon, so earlier inputs can get cut.
+    struct LastPolygon;
+
+    impl<T> PlaneSelector<T> for LastPolygon {
+        fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+            polygons.last()
+        }
+    }
+
+    fn make_triangle(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Polygon {
+        Polygon::new(vec![
+            Point3::new(a[0], a[1], a[2]),
+            Point3::new(b[0], b[1], b[2]),
+            Point3::new(c[0], c[1], c[2]),
+        ])
+    }
+
+    #[test]
+    fn split_fragments_map_to_their_input() {
+        // Input 0 spans the plane z = 0 of input 1 and gets split in two
+        let spanning = make_triangle([0.0, 0.0, -1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        let splitter = make_triangle([-5.0, -5.0, 0.0], [5.0, -5.0, 0.0], [0.0, 5.0, 0.0]);
+
+        let (tree, provenance) =
+            BspTree::build_with_provenance(vec![spanning, splitter.clone()], &LastPolygon);
+
+        let polygons = tree.collect_polygons_ordered();
+        assert_eq!(polygons.len(), 3);
+        assert_eq!(provenance.len(), 3);
+
+        let fragments: Vec<usize> = provenance.fragments_of(0).collect();
+        assert_eq!(fragments.len(), 2);
+        for index in fragments {
+            assert_eq!(provenance.source(index), Some(0));
+            assert_ne!(polygons[index], splitter);
+        }
+
+        let whole: Vec<usize> = provenance.fragments_of(1).collect();
+        assert_eq!(whole.len(), 1);
+        assert_eq!(polygons[whole[0]], splitter);
+    }
+
+    #[test]
+    fn matches_default_build() {
+        let polygons = vec![
+            make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]),
+            make_triangle([0.5, -1.0, -1.0], [0.5, 1.0, -1.0], [0.5, 0.0, 2.0]),
+        ];
+
+        let (tree, provenance) = BspTree::build_with_provenance(polygons.clone(), &FirstPolygon);
+        let expected = BspTree::from_polygons(polygons);
+
+        assert_eq!(
+            tree.collect_polygons_ordered(),
+            expected.collect_polygons_ordered()
+        );
+        assert_eq!(provenance.len(), tree.polygon_count());
+        assert!(provenance.source(provenance.len()).is_none());
+    }
+}
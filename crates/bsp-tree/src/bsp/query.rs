@@ -0,0 +1,1067 @@
+//! Spatial queries against a BSP tree.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{Classification, PlaneSide, Polygon, PLANE_EPSILON};
+
+use super::node::{BspNode, Direction};
+use super::tree::BspTree;
+
+/// Where a polygon would land if inserted into a tree without splitting it.
+///
+/// Returned by [`BspTree::classify_polygon`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertLocation {
+    path: Vec<Direction>,
+    classification: Classification,
+}
+
+impl InsertLocation {
+    /// Returns the front/back steps from the root to the deepest node the
+    /// polygon reaches.
+    pub fn path(&self) -> &[Direction] {
+        &self.path
+    }
+
+    /// Returns how the polygon relates to the deepest node's plane.
+    ///
+    /// [`Front`](Classification::Front) and [`Back`](Classification::Back)
+    /// mean the node has no child on that side, so the polygon can become a
+    /// new leaf there. [`Coplanar`](Classification::Coplanar) means it
+    /// belongs in the node itself, and [`Spanning`](Classification::Spanning)
+    /// that it has to be split to be inserted.
+    pub fn classification(&self) -> Classification {
+        self.classification
+    }
+}
+
+/// Maximum number of conservative advancement steps per polygon.
+const MAX_SWEEP_STEPS: usize = 64;
+
+/// Distance before a sample point at which a line of sight ends.
+const SIGHT_MARGIN: f32 = 10.0 * PLANE_EPSILON;
+
+impl<T: Clone> BspTree<T> {
+    /// Returns the front/back steps taken from the root to the leaf cell
+    /// containing `point`.
+    ///
+    /// Points on a splitting plane descend to its front side. The path is
+    /// empty for an empty tree.
+    pub fn trace(&self, point: Point3<f32>) -> Vec<Direction> {
+        let mut path = Vec::new();
+        let mut current = self.root();
+        while let Some(node) = current {
+            current = match node.plane().classify_point(point) {
+                PlaneSide::Front | PlaneSide::OnPlane => {
+                    path.push(Direction::Front);
+                    node.front()
+                }
+                PlaneSide::Back => {
+                    path.push(Direction::Back);
+                    node.back()
+                }
+            };
+        }
+        path
+    }
+
+    /// Returns `true` if `point` lies inside the solid bounded by the tree.
+    ///
+    /// Assumes the polygons form a closed surface with normals facing
+    /// outward, see [solids](BspTree#solids). A point is inside when it ends
+    /// in the cell behind a node that has no back child, i.e. behind every
+    /// surface around it. The result for points on the surface itself is
+    /// unspecified.
+    ///
+    /// Useful e.g. to check whether a camera has moved into a wall.
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.root().is_some() && self.trace(point).last() == Some(&Direction::Back)
+    }
+
+    /// Returns `true` if the solids bounded by the two trees intersect.
+    ///
+    /// Intended as a cheap check before CSG. Disjoint bounding boxes are
+    /// rejected immediately. Otherwise the solids overlap when a vertex or
+    /// polygon centroid of one lies inside the other, or when an edge of one
+    /// passes through a polygon of the other. Both trees must bound closed
+    /// solids, as for [`contains_point`](Self::contains_point). Solids that
+    /// only touch may be reported either way.
+    pub fn overlaps(&self, other: &BspTree<T>) -> bool {
+        let (Some(bounds), Some(other_bounds)) = (
+            self.root().and_then(BspNode::subtree_aabb),
+            other.root().and_then(BspNode::subtree_aabb),
+        ) else {
+            return false;
+        };
+        if !bounds.intersects(&other_bounds) {
+            return false;
+        }
+
+        let polygons = self.collect_polygons();
+        let other_polygons = other.collect_polygons();
+        has_point_inside(&polygons, other)
+            || has_point_inside(&other_polygons, self)
+            || has_edge_through(&polygons, &other_polygons)
+            || has_edge_through(&other_polygons, &polygons)
+    }
+
+    /// Sweeps a sphere along `motion` and returns the earliest contact.
+    ///
+    /// The sphere starts at `center` and moves to `center + motion`. Returns
+    /// `Some((t, normal))` where `t` in `[0, 1]` is the fraction of `motion`
+    /// travelled before touching a polygon and `normal` is the unit contact
+    /// normal pointing from the polygon towards the sphere. Returns `None`
+    /// if the sphere reaches the end of its motion unobstructed.
+    ///
+    /// Polygons are treated as two-sided. A sphere that already overlaps a
+    /// polygon reports a contact at `t = 0`.
+    pub fn sweep_sphere(
+        &self,
+        center: Point3<f32>,
+        radius: f32,
+        motion: Vector3<f32>,
+    ) -> Option<(f32, Vector3<f32>)> {
+        let mut earliest: Option<(f32, Vector3<f32>)> = None;
+        self.sweep_sphere_with(center, radius, motion, |_, t, normal| {
+            if earliest.is_none_or(|(best, _)| t < best) {
+                earliest = Some((t, normal));
+            }
+        });
+        earliest
+    }
+
+    /// Sweeps a sphere along `motion`, calling `on_contact` for every polygon
+    /// it touches.
+    ///
+    /// The callback receives the polygon, the fraction of `motion` at first
+    /// contact with it, and the contact normal, as in
+    /// [`sweep_sphere`](Self::sweep_sphere). Subtrees on the far side of a
+    /// splitting plane that the swept sphere never reaches are skipped.
+    /// Contacts are reported in traversal order, not sorted by time.
+    pub fn sweep_sphere_with<F>(
+        &self,
+        center: Point3<f32>,
+        radius: f32,
+        motion: Vector3<f32>,
+        mut on_contact: F,
+    ) where
+        F: FnMut(&Polygon<T>, f32, Vector3<f32>),
+    {
+        if let Some(root) = self.root() {
+            sweep_sphere_node(root, center, radius, motion, &mut on_contact);
+        }
+    }
+
+    /// Finds where `polygon` would be placed by an insertion, without
+    /// inserting or splitting it.
+    ///
+    /// Descends while the polygon lies entirely on one side of a node's plane
+    /// and that side has a child, and stops at the first node where it is
+    /// coplanar, spanning, or on a side without a child. Callers can then
+    /// choose between a full [`insert`](Self::insert) and a looser placement.
+    /// Returns `None` for an empty tree.
+    pub fn classify_polygon(&self, polygon: &Polygon<T>) -> Option<InsertLocation> {
+        let mut path = Vec::new();
+        let mut node = self.root()?;
+        loop {
+            let classification = polygon.classify(node.plane());
+            let step = match classification {
+                Classification::Front => node.front().map(|child| (Direction::Front, child)),
+                Classification::Back => node.back().map(|child| (Direction::Back, child)),
+                Classification::Coplanar | Classification::Spanning => None,
+            };
+            let Some((direction, child)) = step else {
+                return Some(InsertLocation {
+                    path,
+                    classification,
+                });
+            };
+            path.push(direction);
+            node = child;
+        }
+    }
+
+    /// Returns the nearest polygon hit by the ray from `origin` along `dir`,
+    /// together with the hit parameter `t` (the distance when `dir` is unit
+    /// length), e.g. for mouse picking.
+    ///
+    /// Nodes are visited front-to-back along the ray and the search stops at
+    /// the first hit. Polygons are hit from either side; see
+    /// [`Polygon::ray_intersect`].
+    pub fn raycast(&self, origin: Point3<f32>, dir: Vector3<f32>) -> Option<(f32, &Polygon<T>)> {
+        raycast_node(self.root()?, origin, dir)
+    }
+
+    /// Returns the point of the tree's surface nearest to `point`, together
+    /// with its distance, e.g. for collision response.
+    ///
+    /// Each polygon is treated as a filled region, see
+    /// [`Polygon::closest_point`]. Subtrees are searched on the point's side
+    /// of each plane first, and skipped once their cached
+    /// [bounds](BspNode::bounds) are farther away than the nearest point
+    /// found so far. Returns `None` for an empty tree.
+    pub fn closest_point(&self, point: Point3<f32>) -> Option<(Point3<f32>, f32)> {
+        let mut nearest = None;
+        closest_point_node(self.root()?, point, &mut nearest);
+        nearest.map(|(closest, distance_squared)| (closest, distance_squared.sqrt()))
+    }
+
+    /// Returns `true` if any polygon comes within `radius` of `center`, e.g.
+    /// for trigger volumes.
+    ///
+    /// Polygons are treated as filled regions, see
+    /// [`Polygon::closest_point`], and a sphere just touching one counts.
+    /// Stops at the first overlapping polygon found; see
+    /// [`overlapping_sphere`](Self::overlapping_sphere) for how subtrees are
+    /// pruned.
+    ///
+    /// # Panics
+    /// Panics if `radius` is negative.
+    pub fn intersects_sphere(&self, center: Point3<f32>, radius: f32) -> bool {
+        assert!(radius >= 0.0, "radius must be non-negative");
+        self.root().is_some_and(|root| {
+            sphere_overlaps_node(root, center, radius, &mut |_| ControlFlow::Break(())).is_break()
+        })
+    }
+
+    /// Returns every polygon that comes within `radius` of `center`, e.g. to
+    /// gather nearby surfaces for contact generation.
+    ///
+    /// Subtrees whose cached [bounds](BspNode::bounds) are farther than
+    /// `radius` from the center are skipped, as are subtrees on the far side
+    /// of a splitting plane the sphere does not reach. Polygons are returned
+    /// in depth-first pre-order.
+    ///
+    /// # Panics
+    /// Panics if `radius` is negative.
+    pub fn overlapping_sphere(&self, center: Point3<f32>, radius: f32) -> Vec<&Polygon<T>> {
+        assert!(radius >= 0.0, "radius must be non-negative");
+        let mut overlapping = Vec::new();
+        if let Some(root) = self.root() {
+            let _ = sphere_overlaps_node(root, center, radius, &mut |polygon| {
+                overlapping.push(polygon);
+                ControlFlow::Continue(())
+            });
+        }
+        overlapping
+    }
+
+    /// Returns `true` if the segment from `a` to `b` passes through a
+    /// polygon, e.g. to test line of sight.
+    ///
+    /// A polygon only blocks the segment if the endpoints lie on opposite
+    /// sides of its plane, further than [`PLANE_EPSILON`] from it. So a
+    /// segment grazing along a polygon's plane is not blocked, and neither
+    /// is one that ends on a polygon, at either endpoint. Polygons are
+    /// treated as two-sided. Returns `false` for an empty tree.
+    pub fn segment_blocked(&self, a: Point3<f32>, b: Point3<f32>) -> bool {
+        self.root()
+            .is_some_and(|root| segment_blocked_node(root, a, b))
+    }
+
+    /// Determines which polygons can be seen from at least one of `eyes`.
+    ///
+    /// Polygons are identified by their index in
+    /// [`collect_polygons_ordered`](Self::collect_polygons_ordered). A
+    /// polygon is visible when, for some eye, it faces the eye and the line
+    /// of sight to its centroid or to a point near one of its vertices is not
+    /// blocked by another polygon. Occluders are treated as two-sided, and a
+    /// polygon only partly visible between the sample points may be missed.
+    pub fn visibility_from(&self, eyes: &[Point3<f32>]) -> HashMap<usize, bool> {
+        let Some(root) = self.root() else {
+            return HashMap::new();
+        };
+
+        self.collect_polygons_ordered()
+            .iter()
+            .enumerate()
+            .map(|(index, polygon)| {
+                let plane = polygon.plane();
+                let samples = visibility_samples(polygon);
+                let visible = eyes
+                    .iter()
+                    .filter(|eye| plane.classify_point(**eye) == PlaneSide::Front)
+                    .any(|&eye| {
+                        samples.iter().any(|&sample| {
+                            // Stop just short of the polygon so it and its
+                            // coplanar neighbors do not block themselves
+                            let end = sample + (eye - sample).normalize() * SIGHT_MARGIN;
+                            !segment_blocked_node(root, eye, end)
+                        })
+                    });
+                (index, visible)
+            })
+            .collect()
+    }
+}
+
+/// Updates `nearest` with the closest point and its squared distance from
+/// `point` in the subtree at `node`, if one is nearer.
+fn closest_point_node<T: Clone>(
+    node: &BspNode<T>,
+    point: Point3<f32>,
+    nearest: &mut Option<(Point3<f32>, f32)>,
+) {
+    let Some(bounds) = node.bounds() else {
+        return;
+    };
+    let bound = bounds.distance_squared_to_point(point);
+    if nearest.is_some_and(|(_, best)| bound >= best) {
+        return;
+    }
+
+    for polygon in node.polygons() {
+        let closest = polygon.closest_point(point);
+        let distance_squared = (closest - point).norm_squared();
+        if nearest.is_none_or(|(_, best)| distance_squared < best) {
+            *nearest = Some((closest, distance_squared));
+        }
+    }
+
+    let (near, far) = match node.plane().classify_point(point) {
+        PlaneSide::Front | PlaneSide::OnPlane => (node.front(), node.back()),
+        PlaneSide::Back => (node.back(), node.front()),
+    };
+    for child in [near, far].into_iter().flatten() {
+        closest_point_node(child, point, nearest);
+    }
+}
+
+/// Calls `on_overlap` for each polygon in the subtree at `node` within
+/// `radius` of `center`, stopping once it breaks. `radius` must be
+/// non-negative, as it is only compared squared.
+fn sphere_overlaps_node<'a, T: Clone, F>(
+    node: &'a BspNode<T>,
+    center: Point3<f32>,
+    radius: f32,
+    on_overlap: &mut F,
+) -> ControlFlow<()>
+where
+    F: FnMut(&'a Polygon<T>) -> ControlFlow<()>,
+{
+    let radius_squared = radius * radius;
+    let Some(bounds) = node.bounds() else {
+        return ControlFlow::Continue(());
+    };
+    if bounds.distance_squared_to_point(center) > radius_squared {
+        return ControlFlow::Continue(());
+    }
+
+    for polygon in node.polygons() {
+        if (polygon.closest_point(center) - center).norm_squared() <= radius_squared {
+            on_overlap(polygon)?;
+        }
+    }
+
+    // A sphere entirely on one side of the plane cannot reach the other
+    let distance = node.plane().signed_distance(center);
+    if distance >= -radius
+        && let Some(front) = node.front()
+    {
+        sphere_overlaps_node(front, center, radius, on_overlap)?;
+    }
+    if distance <= radius
+        && let Some(back) = node.back()
+    {
+        sphere_overlaps_node(back, center, radius, on_overlap)?;
+    }
+    ControlFlow::Continue(())
+}
+
+/// Finds the nearest polygon hit by a ray in the subtree at `node`.
+///
+/// The subtree on the origin's side of the plane is searched first, then the
+/// node's coplanar polygons and only then, if the ray crosses the plane, the
+/// far subtree. Any hit in an earlier group is nearer than all hits in later
+/// ones, so the search stops at the first group with a hit. The node's
+/// [leftover](BspNode::leftover) polygons can lie anywhere along the ray, so
+/// they are always tested.
+fn raycast_node<T: Clone>(
+    node: &BspNode<T>,
+    origin: Point3<f32>,
+    dir: Vector3<f32>,
+) -> Option<(f32, &Polygon<T>)> {
+    let leftover_hit = nearest_hit(node.leftover(), origin, dir);
+    let hit = raycast_ordered(node, origin, dir);
+    [hit, leftover_hit]
+        .into_iter()
+        .flatten()
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+}
+
+/// Finds the nearest hit in the subtree at `node`, apart from the node's
+/// leftover polygons, see [`raycast_node`].
+fn raycast_ordered<T: Clone>(
+    node: &BspNode<T>,
+    origin: Point3<f32>,
+    dir: Vector3<f32>,
+) -> Option<(f32, &Polygon<T>)> {
+    let plane = node.plane();
+    let denom = plane.normal().dot(&dir);
+    let origin_in_front = match plane.classify_point(origin) {
+        PlaneSide::Front => true,
+        PlaneSide::Back => false,
+        PlaneSide::OnPlane => denom >= 0.0,
+    };
+    let (near, far) = if origin_in_front {
+        (node.front(), node.back())
+    } else {
+        (node.back(), node.front())
+    };
+
+    if let Some(hit) = near.and_then(|near| raycast_node(near, origin, dir)) {
+        return Some(hit);
+    }
+
+    let coplanar_hit = nearest_hit(node.all_coplanar(), origin, dir);
+    if coplanar_hit.is_some() {
+        return coplanar_hit;
+    }
+
+    let crosses = denom.abs() >= f32::EPSILON && -plane.signed_distance(origin) / denom >= 0.0;
+    if crosses {
+        far.and_then(|far| raycast_node(far, origin, dir))
+    } else {
+        None
+    }
+}
+
+/// Returns the nearest of `polygons` hit by the ray from `origin` along `dir`.
+fn nearest_hit<'a, T: Clone>(
+    polygons: impl IntoIterator<Item = &'a Polygon<T>>,
+    origin: Point3<f32>,
+    dir: Vector3<f32>,
+) -> Option<(f32, &'a Polygon<T>)> {
+    polygons
+        .into_iter()
+        .filter_map(|polygon| polygon.ray_intersect(origin, dir).map(|t| (t, polygon)))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+}
+
+fn sweep_sphere_node<T: Clone, F>(
+    node: &BspNode<T>,
+    center: Point3<f32>,
+    radius: f32,
+    motion: Vector3<f32>,
+    on_contact: &mut F,
+) where
+    F: FnMut(&Polygon<T>, f32, Vector3<f32>),
+{
+    // Leftover polygons are not bounded by the plane
+    for polygon in node.leftover() {
+        if let Some((t, normal)) = sweep_sphere_polygon(polygon, center, radius, motion) {
+            on_contact(polygon, t, normal);
+        }
+    }
+
+    let plane = node.plane();
+    let start = plane.signed_distance(center);
+    let end = plane.signed_distance(center + motion);
+
+    // The swept sphere stays strictly on one side: only that subtree matters
+    if start > radius && end > radius {
+        if let Some(front) = node.front() {
+            sweep_sphere_node(front, center, radius, motion, on_contact);
+        }
+        return;
+    }
+    if start < -radius && end < -radius {
+        if let Some(back) = node.back() {
+            sweep_sphere_node(back, center, radius, motion, on_contact);
+        }
+        return;
+    }
+
+    for polygon in node.all_coplanar() {
+        if let Some((t, normal)) = sweep_sphere_polygon(polygon, center, radius, motion) {
+            on_contact(polygon, t, normal);
+        }
+    }
+    if let Some(front) = node.front() {
+        sweep_sphere_node(front, center, radius, motion, on_contact);
+    }
+    if let Some(back) = node.back() {
+        sweep_sphere_node(back, center, radius, motion, on_contact);
+    }
+}
+
+/// Finds the first contact of a moving sphere with a polygon using
+/// conservative advancement: the sphere can safely move by its current gap
+/// to the polygon, since no point approaches faster than `|motion|`.
+fn sweep_sphere_polygon<T: Clone>(
+    polygon: &Polygon<T>,
+    center: Point3<f32>,
+    radius: f32,
+    motion: Vector3<f32>,
+) -> Option<(f32, Vector3<f32>)> {
+    let speed = motion.norm();
+    let mut t = 0.0;
+
+    for _ in 0..MAX_SWEEP_STEPS {
+        let position = center + motion * t;
+        let closest = polygon.closest_point(position);
+        let offset = position - closest;
+        let gap = offset.norm() - radius;
+
+        if gap <= PLANE_EPSILON {
+            let normal = offset.try_normalize(f32::EPSILON).unwrap_or_else(|| {
+                // Center lies on the polygon: oppose the motion
+                let n = polygon.plane().normal();
+                if n.dot(&motion) > 0.0 { -n } else { n }
+            });
+            return Some((t, normal));
+        }
+
+        if speed <= f32::EPSILON {
+            return None;
+        }
+        t += gap / speed;
+        if t > 1.0 {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// Returns the centroid of `polygon` and a point just inside each vertex.
+fn visibility_samples<T: Clone>(polygon: &Polygon<T>) -> Vec<Point3<f32>> {
+    let centroid = polygon.centroid();
+    let inset = polygon
+        .vertices()
+        .iter()
+        .map(|&v| v + (centroid - v) * 0.01);
+    std::iter::once(centroid).chain(inset).collect()
+}
+
+/// Returns `true` if the segment from `start` to `end` passes through a
+/// polygon in the subtree at `node`.
+///
+/// Only the children on the sides of the plane the segment reaches are
+/// searched, and the search stops at the first polygon crossed. The node's
+/// [leftover](BspNode::leftover) polygons are each tested against their own
+/// plane.
+fn segment_blocked_node<T: Clone>(node: &BspNode<T>, start: Point3<f32>, end: Point3<f32>) -> bool {
+    if node
+        .leftover()
+        .iter()
+        .any(|polygon| segment_crosses(polygon, start, end))
+    {
+        return true;
+    }
+
+    let plane = node.plane();
+    let sides = [plane.classify_point(start), plane.classify_point(end)];
+
+    if sides.contains(&PlaneSide::Front) && sides.contains(&PlaneSide::Back) {
+        let hit = plane.intersect_segment(start, end).map(|(_, hit)| hit);
+        if hit.is_some_and(|hit| {
+            node.all_coplanar()
+                .any(|polygon| (polygon.closest_point(hit) - hit).norm() < PLANE_EPSILON)
+        }) {
+            return true;
+        }
+    }
+
+    let blocked_in = |child: Option<&BspNode<T>>| {
+        child.is_some_and(|child| segment_blocked_node(child, start, end))
+    };
+    (sides != [PlaneSide::Back; 2] && blocked_in(node.front()))
+        || (sides != [PlaneSide::Front; 2] && blocked_in(node.back()))
+}
+
+/// Returns `true` if the segment from `start` to `end` passes through
+/// `polygon`, with its endpoints on opposite sides of the polygon's plane.
+fn segment_crosses<T: Clone>(polygon: &Polygon<T>, start: Point3<f32>, end: Point3<f32>) -> bool {
+    let plane = polygon.plane();
+    let sides = [plane.classify_point(start), plane.classify_point(end)];
+    sides.contains(&PlaneSide::Front)
+        && sides.contains(&PlaneSide::Back)
+        && plane
+            .intersect_segment(start, end)
+            .is_some_and(|(_, hit)| (polygon.closest_point(hit) - hit).norm() < PLANE_EPSILON)
+}
+
+/// Returns `true` if a vertex or centroid of `polygons` lies inside `tree`.
+fn has_point_inside<T: Clone>(polygons: &[Polygon<T>], tree: &BspTree<T>) -> bool {
+    polygons.iter().any(|polygon| {
+        tree.contains_point(polygon.centroid())
+            || polygon.vertices().iter().any(|&v| tree.contains_point(v))
+    })
+}
+
+/// Returns `true` if an edge of `polygons` passes through one of `targets`.
+fn has_edge_through<T: Clone>(polygons: &[Polygon<T>], targets: &[Polygon<T>]) -> bool {
+    polygons.iter().any(|polygon| {
+        let vertices = polygon.vertices();
+        (0..vertices.len()).any(|i| {
+            let (start, end) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            targets.iter().any(|target| {
+                target
+                    .plane()
+                    .intersect_segment(start, end)
+                    .is_some_and(|(_, hit)| {
+                        (target.closest_point(hit) - hit).norm() < PLANE_EPSILON
+                    })
+            })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::{BuildOptions, FirstPolygon};
+    use crate::test_fixtures::{cube, cuboid};
+
+    fn triangle_at(z: f32) -> Polygon {
+        Polygon::new(vec![
+            Point3::new(0.0, 0.0, z),
+            Point3::new(1.0, 0.0, z),
+            Point3::new(0.0, 1.0, z),
+        ])
+    }
+
+    #[test]
+    fn classify_polygon_finds_leaf_region() {
+        // Root at z = 0 with a front child at z = 2
+        let tree = BspTree::from_polygons(vec![triangle_at(0.0), triangle_at(2.0)]);
+
+        let above = tree.classify_polygon(&triangle_at(3.0)).unwrap();
+        assert_eq!(above.path(), &[Direction::Front]);
+        assert_eq!(above.classification(), Classification::Front);
+
+        let between = tree.classify_polygon(&triangle_at(1.0)).unwrap();
+        assert_eq!(between.path(), &[Direction::Front]);
+        assert_eq!(between.classification(), Classification::Back);
+
+        let below = tree.classify_polygon(&triangle_at(-1.0)).unwrap();
+        assert!(below.path().is_empty());
+        assert_eq!(below.classification(), Classification::Back);
+    }
+
+    #[test]
+    fn classify_polygon_stops_at_coplanar_or_spanning() {
+        let tree = BspTree::from_polygons(vec![triangle_at(0.0), triangle_at(2.0)]);
+        let spanning = Polygon::new(vec![
+            Point3::new(0.5, 0.0, 1.0),
+            Point3::new(0.5, 1.0, 1.0),
+            Point3::new(0.5, 0.0, 3.0),
+        ]);
+
+        let location = tree.classify_polygon(&spanning).unwrap();
+        assert_eq!(location.path(), &[Direction::Front]);
+        assert_eq!(location.classification(), Classification::Spanning);
+
+        let location = tree.classify_polygon(&triangle_at(2.0)).unwrap();
+        assert_eq!(location.classification(), Classification::Coplanar);
+        assert!(BspTree::new().classify_polygon(&triangle_at(0.0)).is_none());
+    }
+
+    #[test]
+    fn raycast_hits_nearest_face() {
+        let tree = BspTree::from_polygons(cube());
+        let dir = Vector3::new(-1.0, 0.0, 0.0);
+
+        let (t, polygon) = tree.raycast(Point3::new(5.0, 0.5, 1.5), dir).unwrap();
+        assert!((t - 3.0).abs() < 1e-5);
+        assert_eq!(polygon.plane().normal(), Vector3::new(1.0, 0.0, 0.0));
+
+        // From inside, the first face reached is the one ahead of the ray
+        let (t, polygon) = tree.raycast(Point3::new(0.5, 1.0, 1.0), dir).unwrap();
+        assert!((t - 0.5).abs() < 1e-5);
+        assert_eq!(polygon.plane().normal(), Vector3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn raycast_matches_brute_force() {
+        let mut polygons = cube();
+        polygons.extend(cuboid([3.0, 0.5, 0.5], [4.0, 1.5, 1.5]));
+        polygons.extend(cuboid([-1.0, -1.0, 3.0], [0.5, 0.5, 3.5]));
+        let tree = BspTree::from_polygons(polygons);
+        let all = tree.collect_polygons();
+
+        let origins = [
+            Point3::new(6.0, 1.0, 1.0),
+            Point3::new(-3.0, -2.0, 5.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(3.5, 1.0, -2.0),
+        ];
+        let dirs = [
+            Vector3::new(-1.0, 0.05, 0.02),
+            Vector3::new(0.5, 0.4, -0.6),
+            Vector3::new(0.3, -0.2, 1.0),
+            Vector3::new(-0.9, 0.1, 0.4),
+        ];
+        for origin in origins {
+            for dir in dirs {
+                let expected = all
+                    .iter()
+                    .filter_map(|polygon| polygon.ray_intersect(origin, dir))
+                    .min_by(f32::total_cmp);
+                let actual = tree.raycast(origin, dir).map(|(t, _)| t);
+                match (expected, actual) {
+                    (Some(e), Some(a)) => assert!((e - a).abs() < 1e-4, "{e} != {a}"),
+                    (e, a) => assert_eq!(e, a),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn raycast_misses() {
+        let tree = BspTree::from_polygons(cube());
+
+        let away = tree.raycast(Point3::new(5.0, 1.0, 1.0), Vector3::new(1.0, 0.0, 0.0));
+        let parallel = tree.raycast(Point3::new(-1.0, 1.0, 3.0), Vector3::new(1.0, 0.0, 0.0));
+        assert!(away.is_none());
+        assert!(parallel.is_none());
+        let empty = BspTree::new();
+        assert!(empty.raycast(Point3::origin(), Vector3::x()).is_none());
+    }
+
+    #[test]
+    fn sweep_sphere_hits_cube_face() {
+        let tree = BspTree::from_polygons(cube());
+
+        // Falling onto the top face (z = 2) from z = 5 with radius 0.5
+        let (t, normal) = tree
+            .sweep_sphere(
+                Point3::new(1.0, 1.0, 5.0),
+                0.5,
+                Vector3::new(0.0, 0.0, -5.0),
+            )
+            .expect("sphere should hit the cube");
+
+        // Contact when the center reaches z = 2.5, i.e. after 2.5 of 5 units
+        assert!((t - 0.5).abs() < 1e-3, "t was {t}");
+        assert!((normal - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-3);
+    }
+
+    #[test]
+    fn sweep_sphere_misses() {
+        let tree = BspTree::from_polygons(cube());
+
+        let hit = tree.sweep_sphere(
+            Point3::new(5.0, 5.0, 5.0),
+            0.5,
+            Vector3::new(0.0, 0.0, -10.0),
+        );
+
+        assert!(hit.is_none());
+    }
+
+    #[test]
+    fn sweep_sphere_with_reports_each_contact() {
+        let tree = BspTree::from_polygons(cube());
+
+        // Passing straight through the cube touches top and bottom faces
+        let mut contacts = 0;
+        tree.sweep_sphere_with(
+            Point3::new(1.0, 1.0, 5.0),
+            0.5,
+            Vector3::new(0.0, 0.0, -10.0),
+            |_, _, _| contacts += 1,
+        );
+
+        assert!(contacts >= 2);
+    }
+
+    #[test]
+    fn overlapping_cubes_overlap() {
+        let a = BspTree::from_polygons(cube());
+        let b = BspTree::from_polygons(cuboid([1.0, 1.0, 1.0], [3.0, 3.0, 3.0]));
+
+        assert!(a.overlaps(&b));
+        assert!(b.overlaps(&a));
+    }
+
+    #[test]
+    fn separated_cubes_do_not_overlap() {
+        let a = BspTree::from_polygons(cube());
+        let b = BspTree::from_polygons(cuboid([3.0, 0.0, 0.0], [5.0, 2.0, 2.0]));
+        // A tetrahedron beyond the corner (2, 2, 2): the bounding boxes
+        // overlap, but the solids do not
+        let c = BspTree::from_polygons(crate::hull::convex_hull(&[
+            Point3::new(1.8, 2.5, 2.5),
+            Point3::new(2.5, 1.8, 2.5),
+            Point3::new(2.5, 2.5, 1.8),
+            Point3::new(3.0, 3.0, 3.0),
+        ]));
+
+        assert!(!a.overlaps(&b));
+        assert!(!a.overlaps(&c));
+        assert!(!c.overlaps(&a));
+        assert!(!a.overlaps(&BspTree::new()));
+    }
+
+    #[test]
+    fn nested_and_pierced_cubes_overlap() {
+        let a = BspTree::from_polygons(cube());
+        let inner = BspTree::from_polygons(cuboid([0.5, 0.5, 0.5], [1.5, 1.5, 1.5]));
+        // A bar through the middle with no vertex inside the cube, and no
+        // cube vertex inside the bar
+        let bar = BspTree::from_polygons(cuboid([-1.0, 0.8, 0.8], [3.0, 1.2, 1.2]));
+
+        assert!(a.overlaps(&inner));
+        assert!(inner.overlaps(&a));
+        assert!(a.overlaps(&bar));
+        assert!(bar.overlaps(&a));
+    }
+
+    #[test]
+    fn interior_faces_are_never_visible_from_outside() {
+        // A closed cube with a two-sided partition wall at x = 1 inside it
+        let mut polygons = cube();
+        let wall = Polygon::new(vec![
+            Point3::new(1.0, 0.2, 0.2),
+            Point3::new(1.0, 1.8, 0.2),
+            Point3::new(1.0, 1.8, 1.8),
+            Point3::new(1.0, 0.2, 1.8),
+        ]);
+        let wall_back = Polygon::new(wall.vertices().iter().rev().copied().collect());
+        polygons.extend([wall.clone(), wall_back.clone()]);
+        let tree = BspTree::from_polygons(polygons);
+
+        let eyes = [
+            Point3::new(5.0, 1.0, 1.0),
+            Point3::new(-4.0, 1.5, 0.5),
+            Point3::new(1.0, 6.0, 3.0),
+            Point3::new(3.0, -3.0, -3.0),
+        ];
+        let visibility = tree.visibility_from(&eyes);
+        let ordered = tree.collect_polygons_ordered();
+
+        assert_eq!(visibility.len(), ordered.len());
+        for (index, polygon) in ordered.iter().enumerate() {
+            let interior = *polygon == wall || *polygon == wall_back;
+            assert_eq!(visibility[&index], !interior, "polygon {index}");
+        }
+    }
+
+    #[test]
+    fn occluded_polygon_is_not_visible() {
+        // Two parallel squares facing +Z, the lower one fully hidden
+        let square = |z: f32, size: f32| {
+            Polygon::new(vec![
+                Point3::new(-size, -size, z),
+                Point3::new(size, -size, z),
+                Point3::new(size, size, z),
+                Point3::new(-size, size, z),
+            ])
+        };
+        let tree = BspTree::from_polygons(vec![square(1.0, 2.0), square(0.0, 1.0)]);
+
+        let visibility = tree.visibility_from(&[Point3::new(0.0, 0.0, 3.0)]);
+        let ordered = tree.collect_polygons_ordered();
+
+        for (index, polygon) in ordered.iter().enumerate() {
+            let is_top = polygon.vertices()[0].z == 1.0;
+            assert_eq!(visibility[&index], is_top);
+        }
+        // Seen from below, both face away
+        let below = tree.visibility_from(&[Point3::new(0.0, 0.0, -3.0)]);
+        assert!(below.values().all(|&visible| !visible));
+        let empty = BspTree::new();
+        assert!(empty.visibility_from(&[Point3::origin()]).is_empty());
+    }
+
+    /// Nearest point over all polygons, without the tree.
+    fn brute_force_closest(polygons: &[Polygon], point: Point3<f32>) -> f32 {
+        polygons
+            .iter()
+            .map(|polygon| (polygon.closest_point(point) - point).norm())
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn closest_point_matches_brute_force_in_concave_gaps() {
+        // Two boxes with a narrow gap between them, and one on top bridging
+        // it, leaving a concave slot open to the front
+        let mut polygons = cuboid([0.0, 0.0, 0.0], [1.0, 2.0, 1.0]);
+        polygons.extend(cuboid([1.5, 0.0, 0.0], [2.5, 2.0, 1.0]));
+        polygons.extend(cuboid([0.0, 0.0, 1.0], [2.5, 2.0, 1.5]));
+        let tree = BspTree::from_polygons(polygons.clone());
+
+        for point in [
+            Point3::new(1.25, 1.0, 0.5),
+            Point3::new(1.25, -0.5, 0.2),
+            Point3::new(1.1, 1.9, 0.9),
+            Point3::new(-3.0, 4.0, 2.0),
+            Point3::new(0.5, 1.0, 0.5),
+        ] {
+            let (closest, distance) = tree.closest_point(point).unwrap();
+            assert!((distance - brute_force_closest(&polygons, point)).abs() < 1e-5);
+            assert!(((closest - point).norm() - distance).abs() < 1e-5);
+        }
+
+        // In the middle of the slot the side walls are 0.25 away
+        let (_, distance) = tree.closest_point(Point3::new(1.25, 1.0, 0.5)).unwrap();
+        assert!((distance - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn closest_point_on_surface_is_the_point_itself() {
+        let tree = BspTree::from_polygons(cube());
+        let on_face = Point3::new(2.0, 0.5, 1.5);
+
+        let (closest, distance) = tree.closest_point(on_face).unwrap();
+        assert!((closest - on_face).norm() < 1e-6);
+        assert!(distance < 1e-6);
+
+        assert!(BspTree::new().closest_point(on_face).is_none());
+    }
+
+    #[test]
+    fn overlapping_sphere_matches_brute_force() {
+        let mut polygons = cuboid([0.0, 0.0, 0.0], [1.0, 2.0, 1.0]);
+        polygons.extend(cuboid([1.5, 0.0, 0.0], [2.5, 2.0, 1.0]));
+        polygons.extend(cuboid([0.0, 0.0, 1.0], [2.5, 2.0, 1.5]));
+        let tree = BspTree::from_polygons(polygons.clone());
+
+        for (center, radius) in [
+            (Point3::new(1.25, 1.0, 0.5), 0.3),
+            (Point3::new(1.25, 1.0, 0.5), 0.2),
+            (Point3::new(-1.0, 1.0, 0.5), 1.5),
+            (Point3::new(5.0, 5.0, 5.0), 1.0),
+            (Point3::new(1.25, -0.5, 0.2), 0.6),
+        ] {
+            let overlapping = tree.overlapping_sphere(center, radius);
+            let expected = tree
+                .collect_polygons()
+                .iter()
+                .filter(|polygon| (polygon.closest_point(center) - center).norm() <= radius)
+                .count();
+            assert_eq!(overlapping.len(), expected);
+            assert!(
+                overlapping
+                    .iter()
+                    .all(|polygon| (polygon.closest_point(center) - center).norm() <= radius)
+            );
+            assert_eq!(tree.intersects_sphere(center, radius), expected > 0);
+        }
+    }
+
+    #[test]
+    fn intersects_sphere_touching_and_missing() {
+        let tree = BspTree::from_polygons(cube());
+
+        // Touching the x = 2 face from outside, and just short of it
+        assert!(tree.intersects_sphere(Point3::new(3.0, 1.0, 1.0), 1.0));
+        assert!(!tree.intersects_sphere(Point3::new(3.0, 1.0, 1.0), 0.9));
+        // Inside the cube, away from every face
+        assert!(!tree.intersects_sphere(Point3::new(1.0, 1.0, 1.0), 0.5));
+        let touching = tree.overlapping_sphere(Point3::new(1.0, 1.0, 1.0), 1.0);
+        assert_eq!(touching.len(), 6);
+        assert!(!BspTree::new().intersects_sphere(Point3::origin(), 10.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "radius must be non-negative")]
+    fn intersects_sphere_rejects_negative_radius() {
+        let tree = BspTree::from_polygons(cube());
+        tree.intersects_sphere(Point3::new(1.0, 1.0, 1.0), -1.0);
+    }
+
+    #[test]
+    fn segment_blocked_by_faces_but_not_grazing_or_touching() {
+        let tree = BspTree::from_polygons(cube());
+        let outside = Point3::new(-1.0, 1.0, 1.0);
+
+        // Through one face, and through the whole cube
+        assert!(tree.segment_blocked(outside, Point3::new(1.0, 1.0, 1.0)));
+        assert!(tree.segment_blocked(outside, Point3::new(3.0, 1.0, 1.0)));
+        assert!(tree.segment_blocked(Point3::new(3.0, 1.0, 1.0), outside));
+        // Passing beside the cube
+        assert!(!tree.segment_blocked(outside, Point3::new(1.0, 4.0, 1.0)));
+        // Grazing along the x = 0 face, inside it
+        assert!(!tree.segment_blocked(Point3::new(0.0, 0.5, 0.5), Point3::new(0.0, 1.5, 1.5)));
+        // Ending on a face, from either direction
+        let on_face = Point3::new(0.0, 1.0, 1.0);
+        assert!(!tree.segment_blocked(outside, on_face));
+        assert!(!tree.segment_blocked(on_face, outside));
+        // Starting on a face and continuing through the opposite one
+        assert!(tree.segment_blocked(on_face, Point3::new(3.0, 1.0, 1.0)));
+
+        assert!(!BspTree::new().segment_blocked(outside, Point3::new(3.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn queries_test_leftover_polygons_at_capped_nodes() {
+        let depth_capped = BuildOptions::new().with_max_depth(Some(1));
+        let budgeted = BuildOptions::new().with_max_leaf_polygons(Some(6));
+        for options in [depth_capped, budgeted] {
+            let tree = BspTree::build_with_options(cube(), &FirstPolygon, &options);
+            assert!(!tree.root().unwrap().leftover().is_empty());
+
+            let (a, b) = (Point3::new(-1.0, 1.0, 1.0), Point3::new(3.0, 1.0, 1.0));
+            assert!(tree.segment_blocked(a, b));
+            assert!(tree.segment_blocked(b, a));
+            assert!(!tree.segment_blocked(a, Point3::new(-1.0, 4.0, 1.0)));
+
+            let (t, normal) = tree
+                .sweep_sphere(
+                    Point3::new(5.0, 1.0, 1.0),
+                    0.5,
+                    Vector3::new(-5.0, 0.0, 0.0),
+                )
+                .expect("sphere should hit the cube");
+            assert!((t - 0.5).abs() < 1e-3, "t was {t}");
+            assert!((normal - Vector3::x()).norm() < 1e-3);
+
+            let (t, _) = tree
+                .raycast(Point3::new(5.0, 0.5, 1.5), -Vector3::x())
+                .unwrap();
+            assert!((t - 3.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn visibility_tests_leftover_polygons_at_capped_nodes() {
+        let square = |z: f32, size: f32| {
+            Polygon::new(vec![
+                Point3::new(-size, -size, z),
+                Point3::new(size, -size, z),
+                Point3::new(size, size, z),
+                Point3::new(-size, size, z),
+            ])
+        };
+        let depth_capped = BuildOptions::new().with_max_depth(Some(1));
+        let budgeted = BuildOptions::new().with_max_leaf_polygons(Some(2));
+        for options in [depth_capped, budgeted] {
+            // The hidden square becomes the root, leaving its occluder leftover
+            let polygons = vec![square(0.0, 1.0), square(1.0, 2.0)];
+            let tree = BspTree::build_with_options(polygons, &FirstPolygon, &options);
+            assert_eq!(tree.root().unwrap().leftover().len(), 1);
+
+            let visibility = tree.visibility_from(&[Point3::new(0.0, 0.0, 3.0)]);
+            let ordered = tree.collect_polygons_ordered();
+            for (index, polygon) in ordered.iter().enumerate() {
+                let is_top = polygon.vertices()[0].z == 1.0;
+                assert_eq!(visibility[&index], is_top);
+            }
+        }
+    }
+
+    #[test]
+    fn contains_point_in_hollow_room_walls() {
+        // A room whose walls are 1 thick: the outer box faces outward, the
+        // inner one inward, so the cavity is outside the solid
+        let mut polygons = cuboid([0.0, 0.0, 0.0], [6.0, 6.0, 6.0]);
+        polygons.extend(
+            cuboid([1.0, 1.0, 1.0], [5.0, 5.0, 5.0])
+                .iter()
+                .map(Polygon::flipped),
+        );
+        let tree = BspTree::from_polygons(polygons);
+
+        assert!(tree.contains_point(Point3::new(0.5, 3.0, 3.0)));
+        assert!(tree.contains_point(Point3::new(3.0, 3.0, 5.5)));
+        assert!(!tree.contains_point(Point3::new(3.0, 3.0, 3.0)));
+        assert!(!tree.contains_point(Point3::new(-1.0, 3.0, 3.0)));
+        assert!(!BspTree::new().contains_point(Point3::origin()));
+    }
+}
@@ -4,7 +4,9 @@
 //! polygon splits during construction. Different strategies offer different
 //! trade-offs between build time and tree quality.
 
-use crate::Polygon;
+use std::cell::Cell;
+
+use crate::{Classification, Polygon, PLANE_EPSILON};
 
 /// Strategy for selecting which polygon's plane to use for splitting.
 ///
@@ -13,12 +15,65 @@ use crate::Polygon;
 /// - Build speed (simple selection)
 /// - Tree balance (minimize depth)
 /// - Minimal splits (preserve original polygons)
-pub trait PlaneSelector {
+///
+/// `T` is the [payload](Polygon::data) type of the polygons. Selection only
+/// looks at their geometry, so the selectors in this crate accept any
+/// payload.
+pub trait PlaneSelector<T = ()> {
     /// Select a polygon from the slice to use as the splitting plane.
     ///
     /// Returns `None` if the slice is empty.
     /// The returned reference must be to an element in the provided slice.
-    fn select<'a>(&self, polygons: &'a [Polygon]) -> Option<&'a Polygon>;
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>>;
+
+    /// Select a polygon from the slice, considering only the polygons at
+    /// `candidates` as splitters.
+    ///
+    /// Selectors that score candidates still score them against all of
+    /// `polygons`, so restricting the candidates to a sample of `k` makes a
+    /// node cost `k * n` classifications instead of `n * n`. Returns `None`
+    /// if there are no candidates. The default ignores `candidates` and
+    /// calls [`select`](Self::select); the selectors in this crate all
+    /// restrict themselves to the candidates.
+    ///
+    /// # Panics
+    /// May panic if an index is out of range for `polygons`.
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        let _ = candidates;
+        self.select(polygons)
+    }
+
+    /// Like [`select`](Self::select), for a build that treats points within
+    /// `epsilon` of a plane as on it.
+    ///
+    /// Selectors that score candidates by how their planes partition the
+    /// polygons classify with `epsilon`, so the score matches the partition
+    /// the build makes. The default ignores `epsilon` and calls `select`.
+    fn select_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        let _ = epsilon;
+        self.select(polygons)
+    }
+
+    /// Like [`select_indexed`](Self::select_indexed), classifying with
+    /// `epsilon` as [`select_with_epsilon`](Self::select_with_epsilon) does.
+    /// The default ignores `epsilon` and calls `select_indexed`.
+    fn select_indexed_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        let _ = epsilon;
+        self.select_indexed(polygons, candidates)
+    }
 }
 
 /// Selects the first polygon in the list.
@@ -29,15 +84,533 @@ pub trait PlaneSelector {
 #[derive(Debug, Clone, Copy, Default)]
 pub struct FirstPolygon;
 
-impl PlaneSelector for FirstPolygon {
-    fn select<'a>(&self, polygons: &'a [Polygon]) -> Option<&'a Polygon> {
+impl<T> PlaneSelector<T> for FirstPolygon {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
         polygons.first()
     }
+
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        candidates.first().map(|&i| &polygons[i])
+    }
+}
+
+/// Selects the polygon whose plane splits the others most evenly.
+///
+/// Each candidate's plane is scored by the difference between the number of
+/// polygons in front of it and behind it (spanning polygons count on both
+/// sides), and the candidate closest to a 50/50 split wins. This keeps the
+/// tree shallow even for spatially sorted input, at the cost of a slower
+/// build: every candidate is tested against all polygons.
+///
+/// For large inputs, only `sample_size` candidates spread evenly over the
+/// list are scored, as [`SampledSelector`] would pass them.
+#[derive(Debug, Clone, Copy)]
+pub struct BalancedSelector {
+    sample_size: usize,
+}
+
+impl BalancedSelector {
+    /// Creates a selector scoring at most `sample_size` candidates per node.
+    ///
+    /// # Panics
+    /// Panics if `sample_size` is zero.
+    pub fn new(sample_size: usize) -> Self {
+        assert!(sample_size > 0, "sample size must be at least 1");
+        Self { sample_size }
+    }
+
+    /// Returns the maximum number of candidates scored per node.
+    #[inline]
+    pub fn sample_size(&self) -> usize {
+        self.sample_size
+    }
+
+    /// Returns the sampled candidate that splits `polygons` most evenly.
+    fn score_sample<'a, T>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        sample: &[usize],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        indexed(polygons, sample)
+            .min_by_key(|candidate| SplitCounts::of(candidate, polygons, epsilon).imbalance())
+    }
+}
+
+impl Default for BalancedSelector {
+    /// Scores every polygon as a candidate.
+    fn default() -> Self {
+        Self {
+            sample_size: usize::MAX,
+        }
+    }
+}
+
+impl<T> PlaneSelector<T> for BalancedSelector {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+        self.select_with_epsilon(polygons, PLANE_EPSILON)
+    }
+
+    /// Scores at most `sample_size` of the candidates.
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        self.select_indexed_with_epsilon(polygons, candidates, PLANE_EPSILON)
+    }
+
+    fn select_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        let sample = sample_evenly(0..polygons.len(), self.sample_size);
+        self.score_sample(polygons, &sample, epsilon)
+    }
+
+    /// Scores at most `sample_size` of the candidates.
+    fn select_indexed_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        let sample = sample_evenly(candidates.iter().copied(), self.sample_size);
+        self.score_sample(polygons, &sample, epsilon)
+    }
+}
+
+/// Selects the polygon whose plane cuts the fewest other polygons.
+///
+/// Each candidate's plane is scored by the number of polygons it classifies
+/// as [`Spanning`](Classification::Spanning); ties are broken in favor of the
+/// more even front/back partition, as in [`BalancedSelector`]. Fewer splits
+/// keep the final [`polygon_count`](crate::BspTree::polygon_count) close to
+/// the input size, which matters when the tree is kept in memory. Every
+/// candidate is tested against all polygons, so building is quadratic in the
+/// number of polygons per node.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinSplitSelector;
+
+impl MinSplitSelector {
+    fn score<T>(candidate: &Polygon<T>, polygons: &[Polygon<T>], epsilon: f32) -> (usize, usize) {
+        let counts = SplitCounts::of(candidate, polygons, epsilon);
+        (counts.spanning, counts.imbalance())
+    }
+}
+
+impl<T> PlaneSelector<T> for MinSplitSelector {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+        self.select_with_epsilon(polygons, PLANE_EPSILON)
+    }
+
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        self.select_indexed_with_epsilon(polygons, candidates, PLANE_EPSILON)
+    }
+
+    fn select_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        polygons
+            .iter()
+            .min_by_key(|candidate| Self::score(candidate, polygons, epsilon))
+    }
+
+    fn select_indexed_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        indexed(polygons, candidates)
+            .min_by_key(|candidate| Self::score(candidate, polygons, epsilon))
+    }
+}
+
+/// Selects the polygon minimizing a weighted mix of splits and imbalance.
+///
+/// Each candidate's plane is scored as
+/// `split_weight * spanning + balance_weight * |front - back|`, combining the
+/// heuristics of [`MinSplitSelector`] and [`BalancedSelector`]. Raising
+/// `split_weight` favors a lower [`polygon_count`](crate::BspTree::polygon_count);
+/// raising `balance_weight` favors a lower [`depth`](crate::BspTree::depth).
+/// Every candidate is tested against all polygons.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostSelector {
+    /// Cost of each polygon the plane would split.
+    pub split_weight: f32,
+    /// Cost of each polygon of difference between the two sides.
+    pub balance_weight: f32,
+}
+
+impl Default for CostSelector {
+    /// The classic 8:1 ratio of split cost to imbalance cost.
+    fn default() -> Self {
+        Self {
+            split_weight: 8.0,
+            balance_weight: 1.0,
+        }
+    }
+}
+
+impl CostSelector {
+    /// Returns the candidate with the lowest cost.
+    fn cheapest<'a, T>(
+        &self,
+        candidates: impl Iterator<Item = &'a Polygon<T>>,
+        polygons: &[Polygon<T>],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        let score = |candidate: &Polygon<T>| {
+            let counts = SplitCounts::of(candidate, polygons, epsilon);
+            self.split_weight * counts.spanning as f32
+                + self.balance_weight * counts.imbalance() as f32
+        };
+        candidates
+            .map(|candidate| (candidate, score(candidate)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(candidate, _)| candidate)
+    }
+}
+
+impl<T> PlaneSelector<T> for CostSelector {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+        self.cheapest(polygons.iter(), polygons, PLANE_EPSILON)
+    }
+
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        self.cheapest(indexed(polygons, candidates), polygons, PLANE_EPSILON)
+    }
+
+    fn select_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        self.cheapest(polygons.iter(), polygons, epsilon)
+    }
+
+    fn select_indexed_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        self.cheapest(indexed(polygons, candidates), polygons, epsilon)
+    }
+}
+
+/// Selects the first polygon whose plane is axis-aligned.
+///
+/// A polygon qualifies when its unit normal is within `tolerance` of one of
+/// ±X, ±Y or ±Z, i.e. when one of the normal's components has an absolute
+/// value of at least `1 - tolerance`. Splitting along the faces of
+/// axis-aligned boxes cuts far fewer polygons than splitting along a slanted
+/// plane through them. If no polygon qualifies, the first one is selected as
+/// with [`FirstPolygon`]. Like `FirstPolygon`, this does not change the
+/// order among qualifying polygons, so a scene of only axis-aligned boxes is
+/// split exactly as with `FirstPolygon`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisAlignedSelector {
+    /// How far a normal component may fall short of 1 in absolute value.
+    pub tolerance: f32,
+}
+
+impl Default for AxisAlignedSelector {
+    /// Accepts normals within about 2.5 degrees of an axis.
+    fn default() -> Self {
+        Self { tolerance: 1e-3 }
+    }
+}
+
+impl AxisAlignedSelector {
+    /// Returns `true` if `polygon`'s normal is within the tolerance of an
+    /// axis.
+    fn is_axis_aligned<T>(&self, polygon: &Polygon<T>) -> bool {
+        polygon
+            .unit_normal()
+            .is_some_and(|n| n.iter().any(|c| c.abs() >= 1.0 - self.tolerance))
+    }
+}
+
+impl<T> PlaneSelector<T> for AxisAlignedSelector {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+        polygons
+            .iter()
+            .find(|polygon| self.is_axis_aligned(polygon))
+            .or(polygons.first())
+    }
+
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        indexed(polygons, candidates)
+            .find(|polygon| self.is_axis_aligned(polygon))
+            .or_else(|| candidates.first().map(|&i| &polygons[i]))
+    }
+}
+
+/// Selects a uniformly random polygon.
+///
+/// Random splitters avoid the degenerate trees [`FirstPolygon`] builds from
+/// sorted input, at the speed of a single choice per node, and give
+/// expected logarithmic depth. Numbers come from a small linear
+/// congruential generator seeded by [`new`](Self::new), so building the same
+/// polygons with the same seed gives the same tree. Each call to
+/// [`select`](PlaneSelector::select) advances the generator, so a selector
+/// should not be shared between builds that are meant to be reproducible.
+#[derive(Debug, Clone)]
+pub struct RandomSelector {
+    state: Cell<u64>,
+}
+
+impl RandomSelector {
+    /// Creates a selector whose choices are determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Cell::new(seed),
+        }
+    }
+
+    /// Advances the generator and returns a number below `bound`.
+    fn next_below(&self, bound: usize) -> usize {
+        let state = self
+            .state
+            .get()
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.state.set(state);
+        // The high bits of an LCG are the most random; scaling them avoids
+        // the bias of a modulo
+        (((state >> 32) * bound as u64) >> 32) as usize
+    }
+}
+
+impl<T> PlaneSelector<T> for RandomSelector {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+        if polygons.is_empty() {
+            return None;
+        }
+        Some(&polygons[self.next_below(polygons.len())])
+    }
+
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(&polygons[candidates[self.next_below(candidates.len())]])
+    }
+}
+
+/// Restricts another selector to a sample of candidates at each node.
+///
+/// At most `sample_size` polygons spread evenly over each node's list are
+/// passed to the inner selector's
+/// [`select_indexed`](PlaneSelector::select_indexed) as candidates. For the
+/// scoring selectors ([`MinSplitSelector`], [`CostSelector`],
+/// [`BalancedSelector`]) this bounds the work per node to `sample_size`
+/// classifications per polygon, so large inputs build in about
+/// `O(n log n * sample_size)` rather than quadratic time, at the cost of
+/// sometimes missing the best splitter.
+#[derive(Debug, Clone, Copy)]
+pub struct SampledSelector<S> {
+    inner: S,
+    sample_size: usize,
+}
+
+impl<S> SampledSelector<S> {
+    /// Creates a selector passing at most `sample_size` candidates per node
+    /// to `inner`.
+    ///
+    /// # Panics
+    /// Panics if `sample_size` is zero.
+    pub fn new(inner: S, sample_size: usize) -> Self {
+        assert!(sample_size > 0, "sample size must be at least 1");
+        Self { inner, sample_size }
+    }
+
+    /// Returns the selector the candidates are passed to.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the maximum number of candidates per node.
+    #[inline]
+    pub fn sample_size(&self) -> usize {
+        self.sample_size
+    }
+}
+
+impl<T, S: PlaneSelector<T>> PlaneSelector<T> for SampledSelector<S> {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+        let sample = sample_evenly(0..polygons.len(), self.sample_size);
+        self.inner.select_indexed(polygons, &sample)
+    }
+
+    /// Samples from `candidates` instead of from all of `polygons`.
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        let sample = sample_evenly(candidates.iter().copied(), self.sample_size);
+        self.inner.select_indexed(polygons, &sample)
+    }
+
+    fn select_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        let sample = sample_evenly(0..polygons.len(), self.sample_size);
+        self.inner
+            .select_indexed_with_epsilon(polygons, &sample, epsilon)
+    }
+
+    fn select_indexed_with_epsilon<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+        epsilon: f32,
+    ) -> Option<&'a Polygon<T>> {
+        let sample = sample_evenly(candidates.iter().copied(), self.sample_size);
+        self.inner
+            .select_indexed_with_epsilon(polygons, &sample, epsilon)
+    }
+}
+
+/// Selects the polygon at the next index of a fixed script.
+///
+/// Each call to [`select`](PlaneSelector::select) consumes one index; once
+/// the script is exhausted the first polygon is selected.
+/// [`select_indexed`](PlaneSelector::select_indexed) consumes one index
+/// too, and picks among the candidates instead of all polygons. Used by
+/// [`BspTree::build_with_script`](crate::BspTree::build_with_script).
+#[derive(Debug)]
+pub(crate) struct ScriptedSelector<'a> {
+    script: &'a [usize],
+    step: Cell<usize>,
+}
+
+impl<'a> ScriptedSelector<'a> {
+    pub(crate) fn new(script: &'a [usize]) -> Self {
+        Self {
+            script,
+            step: Cell::new(0),
+        }
+    }
+}
+
+impl ScriptedSelector<'_> {
+    /// Consumes the next step and returns the index it selects out of
+    /// `count` choices, or `None` if there are none.
+    fn next_index(&self, count: usize) -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+        let step = self.step.replace(self.step.get() + 1);
+        let Some(&index) = self.script.get(step) else {
+            return Some(0);
+        };
+        assert!(
+            index < count,
+            "scripted splitter index {index} at step {step} is out of range for {count} polygons"
+        );
+        Some(index)
+    }
+}
+
+impl<T> PlaneSelector<T> for ScriptedSelector<'_> {
+    fn select<'a>(&self, polygons: &'a [Polygon<T>]) -> Option<&'a Polygon<T>> {
+        self.next_index(polygons.len()).map(|i| &polygons[i])
+    }
+
+    fn select_indexed<'a>(
+        &self,
+        polygons: &'a [Polygon<T>],
+        candidates: &[usize],
+    ) -> Option<&'a Polygon<T>> {
+        self.next_index(candidates.len())
+            .map(|i| &polygons[candidates[i]])
+    }
+}
+
+/// Returns at most `sample_size` of `candidates`, spread evenly over them.
+fn sample_evenly(
+    candidates: impl ExactSizeIterator<Item = usize>,
+    sample_size: usize,
+) -> Vec<usize> {
+    let stride = candidates.len().div_ceil(sample_size).max(1);
+    candidates.step_by(stride).collect()
+}
+
+/// The polygons at `candidates`, in order.
+fn indexed<'a, 'c, T>(
+    polygons: &'a [Polygon<T>],
+    candidates: &'c [usize],
+) -> impl Iterator<Item = &'a Polygon<T>> + use<'a, 'c, T> {
+    candidates.iter().map(move |&i| &polygons[i])
+}
+
+/// How a candidate's plane partitions a list of polygons.
+struct SplitCounts {
+    front: usize,
+    back: usize,
+    spanning: usize,
+}
+
+impl SplitCounts {
+    fn of<T>(candidate: &Polygon<T>, polygons: &[Polygon<T>], epsilon: f32) -> Self {
+        let plane = candidate.plane();
+        let mut counts = Self {
+            front: 0,
+            back: 0,
+            spanning: 0,
+        };
+        for polygon in polygons {
+            match polygon.classify_with_epsilon(&plane, epsilon) {
+                Classification::Front => counts.front += 1,
+                Classification::Back => counts.back += 1,
+                Classification::Spanning => counts.spanning += 1,
+                Classification::Coplanar => {}
+            }
+        }
+        counts
+    }
+
+    /// Difference between the two sides, counting spanning polygons on both.
+    fn imbalance(&self) -> usize {
+        self.front.abs_diff(self.back)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::cuboid;
+    use crate::BspTree;
     use nalgebra::Point3;
 
     fn make_triangle(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Polygon {
@@ -77,4 +650,295 @@ mod tests {
         assert!(selected.is_some());
         assert_eq!(selected.unwrap(), &poly1);
     }
+
+    /// Parallel triangles at heights `0..count`, in sorted order.
+    fn stacked_triangles(count: usize) -> Vec<Polygon> {
+        (0..count)
+            .map(|i| {
+                let z = i as f32;
+                make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn balanced_picks_median_plane() {
+        let polygons = stacked_triangles(5);
+
+        let selected = BalancedSelector::default().select(&polygons);
+
+        assert_eq!(selected, Some(&polygons[2]));
+    }
+
+    #[test]
+    fn balanced_scores_with_the_build_epsilon() {
+        let polygons: Vec<Polygon> = [0.0, 0.05, 1.0, 2.0]
+            .into_iter()
+            .map(|z| make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z]))
+            .collect();
+        let selector = BalancedSelector::default();
+
+        assert_eq!(selector.select(&polygons), Some(&polygons[1]));
+        // With a wider epsilon the two lowest triangles are coplanar, so
+        // neither of them splits the rest evenly
+        assert_eq!(
+            selector.select_with_epsilon(&polygons, 0.1),
+            Some(&polygons[2])
+        );
+        let sampled = SampledSelector::new(selector, 4);
+        assert_eq!(
+            sampled.select_with_epsilon(&polygons, 0.1),
+            Some(&polygons[2])
+        );
+    }
+
+    #[test]
+    fn balanced_empty_list() {
+        let polygons: Vec<Polygon> = vec![];
+        assert!(BalancedSelector::new(4).select(&polygons).is_none());
+    }
+
+    #[test]
+    fn balanced_tree_is_shallower_than_first_polygon() {
+        use crate::BspTree;
+
+        let first = BspTree::build(stacked_triangles(32), &FirstPolygon);
+        let balanced = BspTree::build(stacked_triangles(32), &BalancedSelector::default());
+        let sampled = BspTree::build(stacked_triangles(32), &BalancedSelector::new(8));
+
+        assert_eq!(balanced.polygon_count(), 32);
+        assert!(balanced.depth() <= 6, "depth was {}", balanced.depth());
+        assert!(sampled.depth() < first.depth());
+        assert!(balanced.depth() < first.depth());
+    }
+
+    /// Four stacked horizontal triangles, listed after a vertical one that
+    /// crosses them all but only reaches through the plane z = 1.
+    fn crossed_stack() -> Vec<Polygon> {
+        let mut polygons = vec![make_triangle(
+            [0.5, -1.0, 0.5],
+            [0.5, 2.0, 0.5],
+            [0.5, 0.0, 1.5],
+        )];
+        polygons.extend(stacked_triangles(4));
+        polygons
+    }
+
+    #[test]
+    fn min_split_avoids_spanning_plane() {
+        let polygons = crossed_stack();
+
+        let selected = MinSplitSelector.select(&polygons);
+
+        // z = 0, 2 and 3 cut nothing; z = 2 splits the rest most evenly
+        assert_eq!(selected, Some(&polygons[3]));
+    }
+
+    #[test]
+    fn min_split_empty_list() {
+        let polygons: Vec<Polygon> = vec![];
+        assert!(MinSplitSelector.select(&polygons).is_none());
+    }
+
+    #[test]
+    fn min_split_produces_fewer_polygons_than_first_polygon() {
+        use crate::BspTree;
+
+        let first = BspTree::build(crossed_stack(), &FirstPolygon);
+        let min_split = BspTree::build(crossed_stack(), &MinSplitSelector);
+
+        assert_eq!(first.polygon_count(), 9);
+        assert!(min_split.polygon_count() < first.polygon_count());
+    }
+
+    #[test]
+    fn cost_weights_choose_between_heuristics() {
+        // The vertical plane cuts every horizontal triangle into even halves
+        let polygons = crossed_stack();
+
+        let splits_only = CostSelector {
+            split_weight: 1.0,
+            balance_weight: 0.0,
+        };
+        let balance_only = CostSelector {
+            split_weight: 0.0,
+            balance_weight: 1.0,
+        };
+
+        assert_eq!(
+            CostSelector::default().select(&polygons),
+            MinSplitSelector.select(&polygons)
+        );
+        assert_eq!(splits_only.select(&polygons), Some(&polygons[1]));
+        assert_eq!(balance_only.select(&polygons), Some(&polygons[0]));
+    }
+
+    #[test]
+    fn cost_empty_list() {
+        let polygons: Vec<Polygon> = vec![];
+        assert!(CostSelector::default().select(&polygons).is_none());
+    }
+
+    #[test]
+    fn random_selector_is_reproducible() {
+        let polygons: Vec<Polygon> = (0..10)
+            .map(|i| {
+                let z = i as f32;
+                make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z])
+            })
+            .collect();
+
+        let picks = |seed| {
+            let selector = RandomSelector::new(seed);
+            (0..20)
+                .map(|_| {
+                    let selected = selector.select(&polygons).unwrap();
+                    polygons
+                        .iter()
+                        .position(|p| std::ptr::eq(p, selected))
+                        .unwrap()
+                })
+                .collect::<Vec<usize>>()
+        };
+
+        assert_eq!(picks(7), picks(7));
+        assert_ne!(picks(7), picks(8));
+        // Not stuck on one polygon
+        let mut distinct = picks(7);
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert!(distinct.len() > 3);
+    }
+
+    #[test]
+    fn random_selector_empty_list() {
+        assert!(RandomSelector::new(1).select(&[] as &[Polygon]).is_none());
+    }
+
+    #[test]
+    fn random_selector_balances_sorted_input() {
+        // FirstPolygon builds a chain of 64 nodes from sorted parallel planes
+        let polygons: Vec<Polygon> = (0..64)
+            .map(|i| {
+                let z = i as f32;
+                make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z])
+            })
+            .collect();
+
+        let tree = BspTree::build(polygons.clone(), &RandomSelector::new(42));
+        assert_eq!(tree.polygon_count(), 64);
+        assert!(tree.depth() < 32);
+
+        let again = BspTree::build(polygons, &RandomSelector::new(42));
+        assert_eq!(again.to_plane_tree(), tree.to_plane_tree());
+    }
+
+    #[test]
+    fn axis_aligned_selector_skips_slanted_polygons() {
+        let slanted = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        let tilted = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.1], [0.0, 1.0, 0.0]);
+        let floor = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let polygons = vec![slanted, tilted, floor];
+
+        let selected = AxisAlignedSelector::default().select(&polygons).unwrap();
+        assert!(std::ptr::eq(selected, &polygons[2]));
+
+        // A looser tolerance accepts the slightly tilted polygon
+        let loose = AxisAlignedSelector { tolerance: 0.01 };
+        let selected = loose.select(&polygons).unwrap();
+        assert!(std::ptr::eq(selected, &polygons[1]));
+    }
+
+    #[test]
+    fn axis_aligned_selector_falls_back_to_first() {
+        let selector = AxisAlignedSelector::default();
+        assert!(selector.select(&[] as &[Polygon]).is_none());
+
+        let polygons = vec![
+            make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]),
+            make_triangle([0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 1.0]),
+        ];
+        let selected = selector.select(&polygons).unwrap();
+        assert!(std::ptr::eq(selected, &polygons[0]));
+    }
+
+    #[test]
+    fn select_indexed_only_picks_candidates() {
+        let polygons = crossed_stack();
+        // Candidates z = 1, which cuts the vertical triangle but splits the
+        // rest more evenly, and z = 2, which cuts nothing
+        let candidates = [2, 3];
+
+        let pick = |selector: &dyn PlaneSelector| {
+            let selected = selector.select_indexed(&polygons, &candidates)?;
+            polygons.iter().position(|p| std::ptr::eq(p, selected))
+        };
+        assert_eq!(pick(&MinSplitSelector), Some(3));
+        assert_eq!(pick(&BalancedSelector::default()), Some(2));
+        assert_eq!(pick(&CostSelector::default()), Some(3));
+        assert_eq!(pick(&FirstPolygon), Some(2));
+        assert_eq!(pick(&AxisAlignedSelector::default()), Some(2));
+        assert!(matches!(pick(&RandomSelector::new(3)), Some(2 | 3)));
+        assert_eq!(pick(&ScriptedSelector::new(&[1])), Some(3));
+        // A sample of one keeps only the first candidate
+        assert_eq!(pick(&SampledSelector::new(MinSplitSelector, 1)), Some(2));
+        assert_eq!(pick(&BalancedSelector::new(1)), Some(2));
+
+        assert!(MinSplitSelector.select_indexed(&polygons, &[]).is_none());
+        let random = RandomSelector::new(3);
+        assert!(random.select_indexed(&polygons, &[]).is_none());
+    }
+
+    #[test]
+    fn sampled_selector_scores_only_the_sample() {
+        let polygons = crossed_stack();
+
+        // Candidates 0, 2 and 4, of which only z = 3 cuts nothing
+        let selected = SampledSelector::new(MinSplitSelector, 3).select(&polygons);
+        assert_eq!(selected, Some(&polygons[4]));
+
+        // A single candidate is always the first polygon
+        let selected = SampledSelector::new(MinSplitSelector, 1).select(&polygons);
+        assert_eq!(selected, Some(&polygons[0]));
+        let single = SampledSelector::new(MinSplitSelector, 1);
+        assert!(single.select(&[] as &[Polygon]).is_none());
+    }
+
+    #[test]
+    fn sampled_selector_balances_sorted_input() {
+        let first = BspTree::build(stacked_triangles(64), &FirstPolygon);
+        let sampled = BspTree::build(
+            stacked_triangles(64),
+            &SampledSelector::new(BalancedSelector::default(), 8),
+        );
+
+        assert_eq!(sampled.polygon_count(), 64);
+        assert!(sampled.depth() < first.depth());
+    }
+
+    #[test]
+    #[should_panic(expected = "sample size must be at least 1")]
+    fn sampled_selector_rejects_empty_sample() {
+        SampledSelector::new(FirstPolygon, 0);
+    }
+
+    #[test]
+    fn axis_aligned_selector_avoids_cutting_boxes() {
+        // A small slanted triangle beside a row of boxes comes first in the
+        // input; its plane cuts the last box, while no box plane cuts it
+        let mut polygons = vec![make_triangle(
+            [7.2, 0.2, 2.0],
+            [7.8, 0.2, 3.0],
+            [7.2, 0.8, 3.0],
+        )];
+        for i in 0..4 {
+            let x = 2.0 * i as f32;
+            polygons.extend(cuboid([x, 0.0, 0.0], [x + 1.0, 1.0, 1.0]));
+        }
+
+        let first = BspTree::build(polygons.clone(), &FirstPolygon);
+        let aligned = BspTree::build(polygons, &AxisAlignedSelector::default());
+        assert!(first.polygon_count() > 25);
+        assert_eq!(aligned.polygon_count(), 25);
+    }
 }
@@ -0,0 +1,269 @@
+//! Summary statistics of a built tree and of its construction.
+
+use std::fmt;
+
+use crate::Polygon;
+
+use super::options::BuildOptions;
+use super::selector::PlaneSelector;
+use super::tree::{build_node, without_degenerate, BspTree};
+
+/// Shape statistics of a [`BspTree`], for comparing plane selectors.
+///
+/// Depths count nodes as [`BspTree::depth`] does, so the root is at depth 1.
+/// A leaf is a node without children.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TreeStats {
+    /// Number of nodes.
+    pub node_count: usize,
+    /// Number of nodes without children.
+    pub leaf_count: usize,
+    /// Depth of the deepest node, as [`BspTree::depth`].
+    pub max_depth: usize,
+    /// Depth of the shallowest leaf.
+    pub min_leaf_depth: usize,
+    /// Mean depth of the leaves.
+    pub avg_leaf_depth: f32,
+    /// Number of polygons, including fragments created by splitting.
+    pub total_polygons: usize,
+    /// Largest number of polygons stored at a single node, as
+    /// [`BspTree::max_coplanar_group`].
+    pub max_coplanar_per_node: usize,
+    /// The smallest depth possible for `node_count` nodes divided by
+    /// `max_depth`: 1 for a perfectly balanced tree, approaching 0 for a
+    /// chain.
+    pub balance: f32,
+}
+
+/// What a tree build did to its input, returned by
+/// [`BspTree::build_with_report`].
+///
+/// The number of splits measures the cost of a [`PlaneSelector`]'s choices:
+/// each split cuts a polygon in two, adding a polygon to the tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Number of polygons passed to the build.
+    pub input_polygons: usize,
+    /// Number of polygons in the built tree.
+    pub output_polygons: usize,
+    /// Number of polygons cut by a splitting plane, counting fragments of
+    /// earlier cuts that were cut again.
+    pub splits: usize,
+}
+
+impl<T: Clone> BspTree<T> {
+    /// Builds a tree like [`build_with_options`](Self::build_with_options)
+    /// and reports how many polygons were cut.
+    ///
+    /// ```
+    /// use bsp_tree::{BspTree, BuildOptions, FirstPolygon, Polygon};
+    /// use nalgebra::Point3;
+    ///
+    /// let floor = Polygon::new(vec![
+    ///     Point3::new(-1.0, -1.0, 0.0),
+    ///     Point3::new(1.0, -1.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    /// ]);
+    /// let wall = Polygon::new(vec![
+    ///     Point3::new(0.0, 0.0, -1.0),
+    ///     Point3::new(0.0, 0.5, 1.0),
+    ///     Point3::new(0.0, -0.5, 1.0),
+    /// ]);
+    ///
+    /// let (tree, report) =
+    ///     BspTree::build_with_report(vec![floor, wall], &FirstPolygon, &BuildOptions::new());
+    /// assert_eq!(report.splits, 1);
+    /// assert_eq!(report.output_polygons, tree.polygon_count());
+    /// ```
+    pub fn build_with_report<S: PlaneSelector<T>>(
+        polygons: Vec<Polygon<T>>,
+        selector: &S,
+        options: &BuildOptions,
+    ) -> (BspTree<T>, BuildReport) {
+        let mut report = BuildReport {
+            input_polygons: polygons.len(),
+            ..BuildReport::default()
+        };
+        let polygons = without_degenerate(polygons);
+        let root = build_node(polygons, selector, options, 1, &mut report.splits);
+        let tree = BspTree::from_root(root);
+        report.output_polygons = tree.polygon_count();
+        (tree, report)
+    }
+
+    /// Computes [`TreeStats`] for the tree. All fields are 0 for an empty
+    /// tree.
+    ///
+    /// ```
+    /// use bsp_tree::{BspTree, Polygon};
+    /// use nalgebra::Point3;
+    ///
+    /// let tree = BspTree::from_polygons(vec![Polygon::new(vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    /// ])]);
+    ///
+    /// let stats = tree.stats();
+    /// assert_eq!(stats.node_count, 1);
+    /// println!("{stats}");
+    /// ```
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats::default();
+        let mut leaf_depth_sum = 0;
+
+        for (node, depth) in self.iter_nodes_bfs() {
+            stats.node_count += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+            stats.total_polygons += node.polygons().count();
+            stats.max_coplanar_per_node = stats.max_coplanar_per_node.max(node.coplanar_count());
+            if node.is_leaf() {
+                // Breadth-first order meets the shallowest leaf first
+                if stats.leaf_count == 0 {
+                    stats.min_leaf_depth = depth;
+                }
+                stats.leaf_count += 1;
+                leaf_depth_sum += depth;
+            }
+        }
+
+        if stats.node_count > 0 {
+            stats.avg_leaf_depth = leaf_depth_sum as f32 / stats.leaf_count as f32;
+            let min_depth = (stats.node_count + 1).next_power_of_two().trailing_zeros();
+            stats.balance = min_depth as f32 / stats.max_depth as f32;
+        }
+        stats
+    }
+}
+
+impl fmt::Display for TreeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "nodes: {} ({} leaves), polygons: {}",
+            self.node_count, self.leaf_count, self.total_polygons
+        )?;
+        writeln!(
+            f,
+            "depth: max {}, leaves min {} / avg {:.2}, balance {:.2}",
+            self.max_depth, self.min_leaf_depth, self.avg_leaf_depth, self.balance
+        )?;
+        write!(f, "max polygons per node: {}", self.max_coplanar_per_node)
+    }
+}
+
+impl fmt::Display for BuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "polygons: {} in, {} out, {} splits",
+            self.input_polygons, self.output_polygons, self.splits
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::{BalancedSelector, FirstPolygon};
+    use nalgebra::Point3;
+
+    /// Stack of parallel triangles at z = 0..n, all facing +Z.
+    fn stack(n: usize) -> Vec<Polygon> {
+        (0..n)
+            .map(|i| {
+                let z = i as f32;
+                Polygon::new(vec![
+                    Point3::new(0.0, 0.0, z),
+                    Point3::new(1.0, 0.0, z),
+                    Point3::new(0.0, 1.0, z),
+                ])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chain_stats() {
+        let stats = BspTree::from_polygons(stack(7)).stats();
+
+        assert_eq!(stats.node_count, 7);
+        assert_eq!(stats.leaf_count, 1);
+        assert_eq!(stats.max_depth, 7);
+        assert_eq!(stats.min_leaf_depth, 7);
+        assert_eq!(stats.avg_leaf_depth, 7.0);
+        assert_eq!(stats.total_polygons, 7);
+        assert_eq!(stats.max_coplanar_per_node, 1);
+        assert!((stats.balance - 3.0 / 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn balanced_stack_is_fully_balanced() {
+        let tree = BspTree::build(stack(7), &BalancedSelector::default());
+        let stats = tree.stats();
+
+        assert_eq!(stats.max_depth, tree.depth());
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.leaf_count, 4);
+        assert_eq!(stats.min_leaf_depth, 3);
+        assert_eq!(stats.balance, 1.0);
+    }
+
+    #[test]
+    fn report_counts_cuts_of_fragments() {
+        // Two crossing walls on the floor: the floor is cut by the first
+        // wall, and one of its fragments again by the second
+        let floor = Polygon::new(vec![
+            Point3::new(-2.0, -2.0, 0.0),
+            Point3::new(2.0, -2.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(-2.0, 2.0, 0.0),
+        ]);
+        let wall_x = Polygon::new(vec![
+            Point3::new(0.0, 1.0, -1.0),
+            Point3::new(0.0, 1.5, 1.0),
+            Point3::new(0.0, 0.5, 1.0),
+        ]);
+        let wall_y = Polygon::new(vec![
+            Point3::new(1.0, 1.0, -1.0),
+            Point3::new(0.5, 1.0, 1.0),
+            Point3::new(1.5, 1.0, 1.0),
+        ]);
+        let polygons = vec![wall_x, wall_y, floor];
+
+        let options = BuildOptions::new();
+        let (tree, report) = BspTree::build_with_report(polygons.clone(), &FirstPolygon, &options);
+        assert_eq!(report.input_polygons, 3);
+        assert_eq!(report.splits, 2);
+        assert_eq!(report.output_polygons, 5);
+        assert_eq!(report.to_string(), "polygons: 3 in, 5 out, 2 splits");
+
+        let plain = BspTree::build_with_options(polygons, &FirstPolygon, &options);
+        assert_eq!(tree.to_plane_tree(), plain.to_plane_tree());
+        assert_eq!(tree.collect_polygons(), plain.collect_polygons());
+    }
+
+    #[test]
+    fn report_without_cuts() {
+        let (_, report) = BspTree::build_with_report(stack(4), &FirstPolygon, &BuildOptions::new());
+        assert_eq!(
+            report,
+            BuildReport {
+                input_polygons: 4,
+                output_polygons: 4,
+                splits: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn empty_tree_stats() {
+        assert_eq!(BspTree::new().stats(), TreeStats::default());
+    }
+
+    #[test]
+    fn display_summarizes_stats() {
+        let text = BspTree::from_polygons(stack(3)).stats().to_string();
+        assert!(text.contains("nodes: 3 (1 leaves), polygons: 3"));
+        assert!(text.contains("balance 0.67"));
+    }
+}
@@ -1,12 +1,21 @@
 //! BSP tree container and construction.
 
-use nalgebra::Point3;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
-use crate::{Classification, Cuttable, Polygon};
+use nalgebra::{Isometry3, Point3, Vector3};
 
-use super::node::{faces_same_direction, BspNode};
-use super::selector::PlaneSelector;
-use super::visitor::BspVisitor;
+use crate::cuttable::seam;
+use crate::mesh::Welder;
+use crate::{Aabb, Classification, Cuttable, Frustum, Plane3D, PlaneSide, Polygon, SplitResult};
+
+use super::iter::OrderedNodes;
+use super::node::{
+    faces_same_direction, faces_same_direction_with_epsilon, BspNode, Direction, NodeId,
+};
+use super::options::BuildOptions;
+use super::selector::{FirstPolygon, PlaneSelector, ScriptedSelector};
+use super::visitor::{BspNodeVisitor, BspVisitor, PolygonVisitor};
 
 /// A Binary Space Partitioning tree for 3D polygons.
 ///
@@ -36,34 +45,186 @@ use super::visitor::BspVisitor;
 /// tree.traverse_back_to_front(eye_position, &mut visitor);
 /// tree.traverse_front_to_back(eye_position, &mut visitor);
 /// ```
-#[derive(Debug, Clone, Default)]
-pub struct BspTree {
-    root: Option<BspNode>,
+///
+/// # Solids
+///
+/// When the polygons form closed surfaces with outward-facing normals, the
+/// tree also describes the solid they bound. Each missing child of a node is
+/// a convex cell of space: a missing front child is an empty cell outside
+/// the solid, and a missing back child is a solid cell inside it. No extra
+/// marking is needed, so every tree built from closed surfaces is a solid
+/// tree, and [`contains_point`](Self::contains_point) and the CSG operations
+/// ([`union`](Self::union) and friends) rely on this convention.
+///
+/// Leaves built with a [maximum depth](BuildOptions::with_max_depth) or
+/// [leaf polygon budget](BuildOptions::with_max_leaf_polygons) hold polygons
+/// from both sides of their plane, so their cells are not purely inside or
+/// outside and solid queries are unreliable there.
+///
+/// # Payloads
+///
+/// A tree of polygons carrying a [payload](Polygon::with_data) of type `T`
+/// keeps every polygon's payload through building, insertion and CSG, so
+/// queries such as [`raycast`](Self::raycast) can tell, for example, the
+/// material of the surface they hit.
+#[derive(Debug, Clone)]
+pub struct BspTree<T = ()> {
+    root: Option<BspNode<T>>,
+}
+
+impl<T> Default for BspTree<T> {
+    fn default() -> Self {
+        Self { root: None }
+    }
 }
 
 impl BspTree {
     /// Creates an empty BSP tree.
+    ///
+    /// For a tree whose polygons carry a payload, use
+    /// [`BspTree::default`].
     pub fn new() -> Self {
-        Self { root: None }
+        Self::default()
+    }
+
+    /// Builds a BSP tree using the default plane selector ([`FirstPolygon`]).
+    ///
+    /// For polygons that carry a payload, use [`build`](Self::build) with
+    /// [`FirstPolygon`].
+    pub fn from_polygons(polygons: Vec<Polygon>) -> Self {
+        Self::build(polygons, &FirstPolygon)
+    }
+
+    /// Builds a tree from the zero isosurface of a signed-distance function.
+    ///
+    /// `sdf` is negative inside the solid and positive outside. It is sampled
+    /// on a grid of `resolution` cells along each axis of `bounds`, and the
+    /// surface is polygonized into outward-facing triangles with marching
+    /// tetrahedra before being passed to [`BspTree::from_polygons`]. The
+    /// surface is closed as long as the solid lies strictly inside `bounds`.
+    ///
+    /// # Panics
+    /// Panics if `resolution` is zero.
+    pub fn from_sdf<F: Fn(Point3<f32>) -> f32>(sdf: F, bounds: Aabb, resolution: usize) -> Self {
+        Self::from_polygons(crate::sdf::polygonize(sdf, &bounds, resolution))
     }
+}
 
+impl<T: Clone> BspTree<T> {
     /// Builds a BSP tree from a collection of polygons.
     ///
     /// Uses the provided [`PlaneSelector`] to choose splitting planes during
     /// construction. Polygons that span a splitting plane are automatically
-    /// split using the [`Cuttable`] trait.
+    /// split using the [`Cuttable`] trait. Degenerate polygons with zero
+    /// area, which have no [plane](Polygon::try_plane), are skipped.
     ///
     /// Returns an empty tree if the input is empty.
-    pub fn build<S: PlaneSelector>(polygons: Vec<Polygon>, selector: &S) -> Self {
-        Self {
-            root: build_node(polygons, selector),
+    pub fn build<S: PlaneSelector<T>>(polygons: Vec<Polygon<T>>, selector: &S) -> Self {
+        Self::build_with_options(polygons, selector, &BuildOptions::default())
+    }
+
+    /// Builds a BSP tree from a collection of polygons with custom [`BuildOptions`].
+    pub fn build_with_options<S: PlaneSelector<T>>(
+        polygons: Vec<Polygon<T>>,
+        selector: &S,
+        options: &BuildOptions,
+    ) -> Self {
+        let polygons = without_degenerate(polygons);
+        Self::from_root(build_node(polygons, selector, options, 1, &mut 0))
+    }
+
+    /// Builds a BSP tree whose splitters are dictated by a script instead of
+    /// a [`PlaneSelector`].
+    ///
+    /// Nodes are created in pre-order (node, front subtree, back subtree),
+    /// and the `i`-th node is split by the polygon at index
+    /// `splitter_indices[i]` of the polygons remaining for it, i.e. of the
+    /// slice a selector would receive at that step. Once the script runs
+    /// out, the first remaining polygon is used, as with [`FirstPolygon`].
+    ///
+    /// Useful for reproducing a known partition in tests and experiments.
+    ///
+    /// # Panics
+    /// Panics if a scripted index is out of range for the polygons remaining
+    /// at its step.
+    pub fn build_with_script(polygons: Vec<Polygon<T>>, splitter_indices: &[usize]) -> Self {
+        Self::build(polygons, &ScriptedSelector::new(splitter_indices))
+    }
+
+    /// Builds a BSP tree like [`from_polygons`](Self::from_polygons) from
+    /// anything convertible into polygons, such as [`Triangle`](crate::Triangle)s
+    /// or [`Rectangle`](crate::Rectangle)s, owned or borrowed.
+    ///
+    /// ```
+    /// use bsp_tree::{BspTree, Polygon, Rectangle, Triangle};
+    /// use nalgebra::{Point3, Vector3};
+    ///
+    /// let triangles = vec![Triangle::new(
+    ///     Point3::new(0.0, 0.0, 1.0),
+    ///     Point3::new(1.0, 0.0, 1.0),
+    ///     Point3::new(0.0, 1.0, 1.0),
+    /// )];
+    /// let floor = Rectangle::square(Point3::origin(), Vector3::z(), 2.0);
+    ///
+    /// let tree = BspTree::from_cuttables(&triangles);
+    /// assert_eq!(tree.polygon_count(), 1);
+    ///
+    /// // Mixed shapes are chained once converted
+    /// let mixed = triangles.iter().map(Polygon::from).chain([floor.into()]);
+    /// assert_eq!(BspTree::from_cuttables(mixed).polygon_count(), 2);
+    /// ```
+    pub fn from_cuttables<I>(items: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Polygon<T>>,
+    {
+        Self::build(items.into_iter().map(Into::into).collect(), &FirstPolygon)
+    }
+
+    /// Assembles a BSP tree from groups of polygons that were already
+    /// partitioned externally, without cutting anything.
+    ///
+    /// Each entry is a splitting plane together with the polygons lying on it.
+    /// Entries are attached in order: the first becomes the root, and each
+    /// later entry descends from the root by the side its polygons' centroid
+    /// falls on, becoming a new child where the path ends. An entry whose
+    /// polygons are coplanar with a node already on the path is merged into
+    /// that node instead. Entries without polygons are routed by the point of
+    /// their plane closest to the origin.
+    ///
+    /// The caller is responsible for the partition being valid: every polygon
+    /// must already lie entirely on one side of each plane above it.
+    pub fn assemble_presplit(nodes: Vec<(Plane3D, Vec<Polygon<T>>)>) -> Self {
+        let mut root: Option<BspNode<T>> = None;
+
+        for (plane, polygons) in nodes {
+            match root {
+                None => root = Some(presplit_node(plane, polygons)),
+                Some(ref mut root) => attach_presplit(root, plane, polygons),
+            }
         }
+
+        let mut tree = Self { root };
+        tree.number_nodes();
+        tree
     }
 
-    /// Builds a BSP tree using the default plane selector ([`FirstPolygon`]).
-    pub fn from_polygons(polygons: Vec<Polygon>) -> Self {
-        use super::selector::FirstPolygon;
-        Self::build(polygons, &FirstPolygon)
+    /// Wraps an already built root node, numbering its nodes.
+    pub(crate) fn from_root(root: Option<BspNode<T>>) -> Self {
+        let mut tree = Self { root };
+        tree.number_nodes();
+        tree
+    }
+
+    /// Assigns node ids in depth-first pre-order.
+    fn number_nodes(&mut self) {
+        if let Some(ref mut root) = self.root {
+            let mut next = 0;
+            root.for_each_node_mut(|node| {
+                node.set_id(NodeId(next));
+                next += 1;
+            });
+        }
     }
 
     /// Returns `true` if the tree contains no polygons.
@@ -74,28 +235,120 @@ impl BspTree {
 
     /// Returns a reference to the root node, if any.
     #[inline]
-    pub fn root(&self) -> Option<&BspNode> {
+    pub fn root(&self) -> Option<&BspNode<T>> {
         self.root.as_ref()
     }
 
     /// Returns a mutable reference to the root node, if any.
     ///
-    /// This is primarily for future insert operations.
+    /// To add polygons, prefer [`insert`](Self::insert), which keeps the
+    /// partition and the nodes' cached [`bounds`](BspNode::bounds)
+    /// consistent.
     #[inline]
-    pub fn root_mut(&mut self) -> Option<&mut BspNode> {
+    pub fn root_mut(&mut self) -> Option<&mut BspNode<T>> {
         self.root.as_mut()
     }
 
+    /// Returns the tree with every polygon's [payload](Polygon::data)
+    /// replaced by `f` applied to it.
+    ///
+    /// The partition, node ids and bounds are unchanged. Mapping to `()`
+    /// drops the payloads.
+    pub fn map_data<U, F: FnMut(T) -> U>(self, f: F) -> BspTree<U> {
+        BspTree {
+            root: self.root.map(|root| root.map_data(f)),
+        }
+    }
+
     /// Returns the total number of polygons in the tree.
     pub fn polygon_count(&self) -> usize {
         self.root.as_ref().map_or(0, |n| n.polygon_count())
     }
 
+    /// Computes the bounding box of all polygons in the tree.
+    ///
+    /// Returns `None` for an empty tree.
+    pub fn aabb(&self) -> Option<Aabb> {
+        self.root.as_ref().and_then(|n| n.subtree_aabb())
+    }
+
     /// Returns the maximum depth of the tree (0 for empty tree).
     pub fn depth(&self) -> usize {
         self.root.as_ref().map_or(0, |n| n.depth())
     }
 
+    /// Computes a surface area heuristic (SAH) cost for the tree.
+    ///
+    /// The probability of a query reaching a node is estimated by the ratio of
+    /// its [region surface area](BspNode::region_surface_area) to the root's,
+    /// and each polygon stored at a reached leaf costs one test. The cost sums
+    /// `region_area * polygon_count` over the leaves, normalized by the
+    /// root's area. Lower is better; returns 0 for an empty tree.
+    pub fn sah_cost(&self) -> f32 {
+        let Some(ref root) = self.root else {
+            return 0.0;
+        };
+        let root_area = root.region_surface_area();
+        if root_area <= f32::EPSILON {
+            return root.polygon_count() as f32;
+        }
+        sah_cost_recursive(root) / root_area
+    }
+
+    /// Returns the largest number of coplanar polygons stored at any node.
+    ///
+    /// A high value relative to [`polygon_count`](Self::polygon_count) means
+    /// many polygons share one plane (e.g. a large tiled floor), which the
+    /// tree cannot partition further. Returns 0 for an empty tree.
+    pub fn max_coplanar_group(&self) -> usize {
+        self.root.as_ref().map_or(0, max_coplanar_group_recursive)
+    }
+
+    /// Returns the depth of the leaf cell containing `point`.
+    ///
+    /// This is the number of splitting planes tested while descending from
+    /// the root to the point's leaf, so regions that are subdivided more
+    /// finely report greater depths. Points on a plane descend to its front
+    /// side. Returns 0 for an empty tree.
+    pub fn leaf_depth(&self, point: Point3<f32>) -> usize {
+        let mut depth = 0;
+        let mut current = self.root.as_ref();
+        while let Some(node) = current {
+            depth += 1;
+            current = match node.plane().classify_point(point) {
+                PlaneSide::Front | PlaneSide::OnPlane => node.front(),
+                PlaneSide::Back => node.back(),
+            };
+        }
+        depth
+    }
+
+    /// Returns the front/back steps leading from the root to the node with
+    /// the given id, or `None` if no node has that id.
+    ///
+    /// The root's path is empty.
+    pub fn path_to(&self, id: NodeId) -> Option<Vec<Direction>> {
+        let mut path = Vec::new();
+        let root = self.root.as_ref()?;
+        path_to_recursive(root, id, &mut path).then_some(path)
+    }
+
+    /// Returns a copy of the subtree reached by following `path` from the
+    /// root, as a standalone tree.
+    ///
+    /// Node ids are reassigned, so the new root is `NodeId(0)`. Returns an
+    /// empty tree if `path` leads past a leaf.
+    pub fn with_root_at(&self, path: &[Direction]) -> BspTree<T> {
+        let mut current = self.root.as_ref();
+        for direction in path {
+            current = current.and_then(|node| match direction {
+                Direction::Front => node.front(),
+                Direction::Back => node.back(),
+            });
+        }
+        BspTree::from_root(current.cloned())
+    }
+
     /// Traverses the tree front-to-back relative to the given viewpoint.
     ///
     /// Useful for early-Z occlusion culling in modern renderers with depth
@@ -104,10 +357,58 @@ impl BspTree {
     ///
     /// The visitor's `visit` method is called for each group of coplanar
     /// polygons, in front-to-back order (nearest first).
-    pub fn traverse_front_to_back<V: BspVisitor>(&self, eye: Point3<f32>, visitor: &mut V) {
-        if let Some(ref root) = self.root {
-            traverse_front_to_back_node(root, eye, visitor);
-        }
+    pub fn traverse_front_to_back<V: BspVisitor<T>>(&self, eye: Point3<f32>, visitor: &mut V) {
+        visit_nodes(
+            OrderedNodes::new(self.root(), eye, true),
+            &mut PolygonVisitor(visitor),
+        );
+    }
+
+    /// Traverses an instance of the tree placed in the world by `instance`,
+    /// front-to-back relative to the world-space `eye`.
+    ///
+    /// The eye is moved into the tree's local space, so the order matches
+    /// that of a copy [transformed](Self::transform) by `instance`, but the
+    /// visitor receives the untransformed polygons. One tree can then be
+    /// drawn at many positions, with the instance transform applied on the
+    /// GPU.
+    pub fn traverse_front_to_back_transformed<V: BspVisitor<T>>(
+        &self,
+        instance: &Isometry3<f32>,
+        eye: Point3<f32>,
+        visitor: &mut V,
+    ) {
+        self.traverse_front_to_back(instance.inverse_transform_point(&eye), visitor);
+    }
+
+    /// Traverses the tree front-to-back like
+    /// [`traverse_front_to_back`](Self::traverse_front_to_back), passing
+    /// each node's plane and depth along with its polygons.
+    ///
+    /// Unlike [`BspVisitor`], the visitor is called for every node, including
+    /// those without polygons.
+    pub fn traverse_nodes<V: BspNodeVisitor<T>>(&self, eye: Point3<f32>, visitor: &mut V) {
+        visit_nodes(OrderedNodes::new(self.root(), eye, true), visitor);
+    }
+
+    /// Traverses the tree front-to-back like
+    /// [`traverse_front_to_back`](Self::traverse_front_to_back), skipping
+    /// subtrees that cannot be visible.
+    ///
+    /// A subtree is skipped when its cached [`bounds`](BspNode::bounds) lie
+    /// entirely behind one of the frustum's planes. Subtrees whose box
+    /// straddles the frustum are still visited, with all of their nodes'
+    /// polygons.
+    pub fn traverse_front_to_back_culled<V: BspVisitor<T>>(
+        &self,
+        eye: Point3<f32>,
+        frustum: &Frustum,
+        visitor: &mut V,
+    ) {
+        visit_nodes(
+            OrderedNodes::new(self.root(), eye, true).culled(frustum),
+            &mut PolygonVisitor(visitor),
+        );
     }
 
     /// Traverses the tree back-to-front relative to the given viewpoint.
@@ -115,197 +416,948 @@ impl BspTree {
     /// This is the classic painter's algorithm ordering: far polygons are
     /// visited first, then closer polygons, so they can be drawn on top.
     /// Also useful for correct alpha blending of transparent surfaces.
-    pub fn traverse_back_to_front<V: BspVisitor>(&self, eye: Point3<f32>, visitor: &mut V) {
+    pub fn traverse_back_to_front<V: BspVisitor<T>>(&self, eye: Point3<f32>, visitor: &mut V) {
+        visit_nodes(
+            OrderedNodes::new(self.root(), eye, false),
+            &mut PolygonVisitor(visitor),
+        );
+    }
+
+    /// Collects all polygons in the tree into a vector.
+    ///
+    /// The order of polygons is not guaranteed; see
+    /// [`collect_polygons_ordered`](Self::collect_polygons_ordered) for a
+    /// documented order, and
+    /// [`collect_polygons_sorted`](Self::collect_polygons_sorted) for an
+    /// order that does not depend on the tree's shape.
+    pub fn collect_polygons(&self) -> Vec<Polygon<T>> {
+        self.collect_polygons_ordered()
+    }
+
+    /// Collects all polygons in the tree in a stable order.
+    ///
+    /// Nodes are visited in pre-order (a node, then its front subtree, then
+    /// its back subtree), and each node contributes its front-facing
+    /// coplanar polygons, then its back-facing ones, then its
+    /// [leftover](BspNode::leftover) ones. Indices into the
+    /// result are what [`Provenance`](super::Provenance) refers to.
+    pub fn collect_polygons_ordered(&self) -> Vec<Polygon<T>> {
+        self.iter_nodes_dfs()
+            .flat_map(|(node, _)| node.polygons())
+            .cloned()
+            .collect()
+    }
+
+    /// Collects all polygons in the tree sorted by their geometry alone, for
+    /// reproducible output such as snapshot tests.
+    ///
+    /// Polygons are ordered by centroid (lexicographically by x, y, then
+    /// z), then by their vertex coordinates, then by vertex count, so trees
+    /// holding the same polygons give the same result whatever their
+    /// structure.
+    pub fn collect_polygons_sorted(&self) -> Vec<Polygon<T>> {
+        let mut polygons = self.collect_polygons();
+        polygons.sort_by(canonical_order);
+        polygons
+    }
+
+    /// Collects the edges of all polygons in the tree, e.g. to draw it as a
+    /// wireframe.
+    ///
+    /// An edge shared by two polygons is returned once for each; see
+    /// [`collect_unique_edges`](Self::collect_unique_edges).
+    pub fn collect_edges(&self) -> Vec<(Point3<f32>, Point3<f32>)> {
+        self.iter_nodes_dfs()
+            .flat_map(|(node, _)| node.polygons())
+            .flat_map(Polygon::edges)
+            .collect()
+    }
+
+    /// Collects the edges of all polygons in the tree like
+    /// [`collect_edges`](Self::collect_edges), returning each shared edge
+    /// only once.
+    ///
+    /// Endpoints closer than [`PLANE_EPSILON`](crate::PLANE_EPSILON) are
+    /// treated as the same point, and an edge matches another in either
+    /// direction. Only whole edges are merged: where a split left a
+    /// T-junction, the long edge and the shorter ones along it are all kept.
+    pub fn collect_unique_edges(&self) -> Vec<(Point3<f32>, Point3<f32>)> {
+        let mut positions = Vec::new();
+        let mut welder = Welder::new(crate::PLANE_EPSILON);
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+        for (a, b) in self.collect_edges() {
+            let (i, j) = (
+                welder.weld(a, &mut positions),
+                welder.weld(b, &mut positions),
+            );
+            if i != j && seen.insert((i.min(j), i.max(j))) {
+                edges.push((positions[i as usize], positions[j as usize]));
+            }
+        }
+        edges
+    }
+
+    /// Returns a copy of the tree reflected across `mirror`.
+    ///
+    /// Every plane and polygon is reflected, with polygon winding reversed so
+    /// faces keep facing outward. Front and back stay front and back, so the
+    /// tree keeps its exact structure and node ids and nothing is re-split.
+    pub fn mirrored_across(&self, mirror: &Plane3D) -> BspTree<T> {
+        Self::from_root(self.root.as_ref().map(|root| mirror_node(root, mirror)))
+    }
+
+    /// Calls `f` on every polygon in the tree, in place.
+    ///
+    /// The partition is not updated: a polygon moved off its node's plane,
+    /// or across another node's plane, leaves the tree invalid and it must
+    /// be rebuilt from [`collect_polygons`](Self::collect_polygons). Use
+    /// [`transform`](Self::transform) to move the whole tree rigidly.
+    /// Node [splitters](BspNode::splitter) are left as they were; the cached
+    /// bounds are recomputed.
+    pub fn for_each_polygon_mut<F: FnMut(&mut Polygon<T>)>(&mut self, mut f: F) {
+        if let Some(ref mut root) = self.root {
+            root.for_each_node_mut(|node| node.polygons_mut().for_each(&mut f));
+            root.update_subtree_bounds();
+        }
+    }
+
+    /// Moves the whole tree by a rigid motion, without rebuilding it.
+    ///
+    /// Every plane, polygon and splitter is transformed, so front and back
+    /// stay front and back and the tree keeps its exact structure and node
+    /// ids. Much cheaper than rebuilding when a whole object moves:
+    ///
+    /// ```
+    /// use bsp_tree::{BspTree, Polygon};
+    /// use nalgebra::{Isometry3, Point3, Vector3};
+    ///
+    /// let mut tree = BspTree::from_polygons(vec![Polygon::new(vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    /// ])]);
+    /// let quarter_turn = Vector3::new(0.0, std::f32::consts::FRAC_PI_2, 0.0);
+    /// tree.transform(&Isometry3::new(Vector3::new(0.0, 0.0, 5.0), quarter_turn));
+    ///
+    /// let plane = tree.root().unwrap().plane();
+    /// assert!((plane.normal() - Vector3::x()).norm() < 1e-6);
+    /// assert!(plane.signed_distance(Point3::new(0.0, 0.0, 5.0)).abs() < 1e-6);
+    /// ```
+    pub fn transform(&mut self, iso: &Isometry3<f32>) {
+        if let Some(ref mut root) = self.root {
+            root.for_each_node_mut(|node| node.transform_local(iso));
+            root.update_subtree_bounds();
+        }
+    }
+
+    /// Buckets the tree's polygons by the axis direction they face most.
+    ///
+    /// Each polygon goes into the bucket of the signed axis closest to its
+    /// normal, in the order `[+X, -X, +Y, -Y, +Z, -Z]`. Whole buckets can then
+    /// be skipped when their direction faces away from the viewer. Polygons
+    /// without a valid normal are omitted.
+    pub fn polygons_by_facing(&self) -> [Vec<&Polygon<T>>; 6] {
+        let mut buckets: [Vec<&Polygon<T>>; 6] = Default::default();
+        let mut stack: Vec<&BspNode<T>> = self.root.iter().collect();
+
+        while let Some(node) = stack.pop() {
+            for polygon in node.polygons() {
+                let Some(normal) = polygon.unit_normal() else {
+                    continue;
+                };
+                let axis = normal.iamax();
+                let bucket = 2 * axis + usize::from(normal[axis] < 0.0);
+                buckets[bucket].push(polygon);
+            }
+            stack.extend(node.front());
+            stack.extend(node.back());
+        }
+
+        buckets
+    }
+
+    /// Groups the tree's polygons by the leaf region they bound.
+    ///
+    /// Leaves are the convex cells of space left at the bottom of the tree,
+    /// one for every missing child of a node. Each polygon is assigned to
+    /// exactly one leaf: the one on the side its face points into. Polygons
+    /// stored on interior nodes are carried down the path until they reach
+    /// their leaf, so every leaf list contains all the surfaces visible from
+    /// inside that cell.
+    ///
+    /// Leaves are returned in depth-first order (front before back), including
+    /// leaves that no polygon faces into. An empty tree has no leaves.
+    pub fn leaf_polygons(&self) -> Vec<Vec<Polygon<T>>> {
+        let mut leaves = Vec::new();
         if let Some(ref root) = self.root {
-            traverse_back_to_front_node(root, eye, visitor);
+            leaf_polygons_recursive(root, Vec::new(), &mut leaves);
         }
+        leaves
     }
 
-    /// Collects all polygons in the tree into a vector.
+    /// Computes the convex hull of all polygon vertices in the tree.
+    ///
+    /// Returns the hull as outward-facing triangles, useful as a coarse
+    /// collision proxy or visual bound. Returns an empty list if the tree's
+    /// vertices do not span a volume (e.g. an empty tree or a single plane).
+    pub fn convex_hull(&self) -> Vec<Polygon> {
+        let points: Vec<Point3<f32>> = self
+            .collect_polygons()
+            .iter()
+            .flat_map(|p| p.vertices().iter().copied())
+            .collect();
+        crate::hull::convex_hull(&points)
+    }
+
+    /// Returns the volume enclosed by the tree's polygons.
+    ///
+    /// Only meaningful for a closed surface with outward-facing polygons;
+    /// for an open surface the result depends on the position of the origin.
+    pub fn volume(&self) -> f32 {
+        self.collect_polygons()
+            .iter()
+            .map(|polygon| {
+                let v = polygon.vertices();
+                (1..v.len() - 1)
+                    .map(|i| v[0].coords.dot(&v[i].coords.cross(&v[i + 1].coords)))
+                    .sum::<f32>()
+            })
+            .sum::<f32>()
+            / 6.0
+    }
+
+    /// Inserts a polygon into an existing tree.
+    ///
+    /// The polygon descends from the root like during construction: it is
+    /// classified against each node's plane, cut with [`Cuttable::cut`] where
+    /// it spans one (recording the seam in [`BspNode::cut_edges`]), and
+    /// stored with a node whose plane it lies on according to
+    /// [`faces_same_direction`]. A fragment reaching a missing child
+    /// becomes a new leaf split by its own plane. Nodes are renumbered
+    /// afterwards, so existing [`NodeId`]s may change.
+    ///
+    /// A degenerate polygon with zero area is skipped, as in
+    /// [`build`](Self::build).
+    pub fn insert(&mut self, polygon: Polygon<T>) {
+        if polygon.try_plane().is_none() {
+            return;
+        }
+        match self.root {
+            Some(ref mut root) => insert_into(root, polygon),
+            None => self.root = leaf_node(polygon),
+        }
+        self.number_nodes();
+    }
+
+    /// Removes every polygon matching `pred` and returns how many were
+    /// removed.
+    ///
+    /// Only the affected parts of the tree are rebuilt: a node that keeps
+    /// some of its polygons keeps its plane and children, while a node left
+    /// without polygons of its own loses the plane they defined, and its
+    /// subtree is rebuilt from the remaining polygons with `selector` and
+    /// `options`, as in [`build_with_options`](Self::build_with_options).
+    /// Nodes are renumbered afterwards, so existing [`NodeId`]s may change.
+    ///
+    /// `pred` sees the stored polygons, so a polygon that was split during
+    /// construction is matched fragment by fragment. A kept node drops the
+    /// [`BspNode::cut_edges`] no longer bordering any polygon in its subtree.
+    pub fn remove_matching<F, S>(&mut self, pred: F, selector: &S, options: &BuildOptions) -> usize
+    where
+        F: Fn(&Polygon<T>) -> bool,
+        S: PlaneSelector<T>,
+    {
+        let Some(ref mut root) = self.root else {
+            return 0;
+        };
+        let (removed, rebuild) = remove_matching_from(root, &pred, selector, options, 1);
+        if let Some(polygons) = rebuild {
+            self.root = build_node(polygons, selector, options, 1, &mut 0);
+        }
+        self.number_nodes();
+        removed
+    }
+
+    /// Rebuilds the tree from its current polygons with `selector`, e.g.
+    /// after many [`insert`](Self::insert)s have left it unbalanced.
+    ///
+    /// Polygons split during earlier builds stay split.
+    pub fn rebuild<S: PlaneSelector<T>>(&mut self, selector: &S) {
+        *self = Self::build(self.collect_polygons(), selector);
+    }
+
+    /// Returns the union of the solids bounded by the two trees.
+    ///
+    /// Both trees must bound closed solids with outward-facing polygons, as
+    /// for [`contains_point`](Self::contains_point). Each tree's polygons are
+    /// clipped to the outside of the other, and where faces of both solids
+    /// coincide only one copy is kept.
+    pub fn union(&self, other: &BspTree<T>) -> BspTree<T> {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        a.clip_to(&b);
+        b.clip_to(&a);
+        b.invert();
+        b.clip_to(&a);
+        b.invert();
+        a.absorb(b);
+        a
+    }
+
+    /// Returns the intersection of the solids bounded by the two trees.
+    ///
+    /// Both trees must bound closed solids, as for [`union`](Self::union).
+    /// Faces of both solids that coincide and face the same way are kept
+    /// once; coinciding faces facing opposite ways (solids that only touch)
+    /// are dropped.
+    pub fn intersection(&self, other: &BspTree<T>) -> BspTree<T> {
+        if self.is_empty() || other.is_empty() {
+            return BspTree::default();
+        }
+        let (mut a, mut b) = (self.clone(), other.clone());
+        a.invert();
+        b.clip_to(&a);
+        b.invert();
+        a.clip_to(&b);
+        b.clip_to(&a);
+        a.absorb(b);
+        a.invert();
+        a
+    }
+
+    /// Returns the solid bounded by this tree with the solid bounded by
+    /// `other` removed.
+    ///
+    /// Both trees must bound closed solids, as for [`union`](Self::union).
+    /// Faces of `other` that coincide with faces of this solid are dropped,
+    /// so subtracting a doorway that is flush with a wall leaves no interior
+    /// polygons behind.
+    pub fn difference(&self, other: &BspTree<T>) -> BspTree<T> {
+        if self.is_empty() || other.is_empty() {
+            return self.clone();
+        }
+        let (mut a, mut b) = (self.clone(), other.clone());
+        a.invert();
+        a.clip_to(&b);
+        b.clip_to(&a);
+        b.invert();
+        b.clip_to(&a);
+        b.invert();
+        a.absorb(b);
+        a.invert();
+        a
+    }
+
+    /// Turns the solid inside out, so its inside becomes its outside.
+    ///
+    /// Every node is inverted with [`BspNode::invert`]. Because front and
+    /// back children swap places, the nodes are renumbered afterwards.
+    /// Inverting twice restores the original polygons and node ids.
+    pub fn invert(&mut self) {
+        if let Some(ref mut root) = self.root {
+            root.invert();
+        }
+        self.number_nodes();
+    }
+
+    /// Joins adjacent coplanar polygons at every node into larger convex
+    /// polygons, see [`BspNode::merge_coplanar`].
     ///
-    /// The order of polygons is not guaranteed.
-    pub fn collect_polygons(&self) -> Vec<Polygon> {
-        let mut result = Vec::with_capacity(self.polygon_count());
-        collect_polygons_recursive(self.root.as_ref(), &mut result);
-        result
+    /// Merged polygons cover the same surface, so the partition, node ids
+    /// and bounds are unchanged, but polygon indices, such as those of a
+    /// [`Provenance`](super::Provenance), no longer apply. Run it after
+    /// [`difference`](Self::difference) and the other CSG operations, which
+    /// leave many fragments along each face. Only polygons with equal
+    /// payloads are merged.
+    pub fn merge_coplanar(&mut self)
+    where
+        T: PartialEq,
+    {
+        if let Some(ref mut root) = self.root {
+            root.for_each_node_mut(|node| node.merge_coplanar(crate::PLANE_EPSILON));
+        }
+    }
+
+    /// Removes the parts of this tree's polygons that lie inside `other`.
+    fn clip_to(&mut self, other: &BspTree<T>) {
+        if let (Some(root), Some(other_root)) = (self.root.as_mut(), other.root()) {
+            clip_node_to(root, other_root);
+        }
+    }
+
+    /// Inserts all polygons of `other` into this tree and renumbers it.
+    fn absorb(&mut self, other: BspTree<T>) {
+        for polygon in other.collect_polygons() {
+            match self.root {
+                Some(ref mut root) => insert_into(root, polygon),
+                None => self.root = leaf_node(polygon),
+            }
+        }
+        self.number_nodes();
     }
+}
+
+/// Removes degenerate polygons with zero area, which have no plane to split
+/// by or be classified against, before a build.
+pub(crate) fn without_degenerate<T: Clone>(mut polygons: Vec<Polygon<T>>) -> Vec<Polygon<T>> {
+    polygons.retain(|p| p.try_plane().is_some());
+    polygons
+}
 
-    // TODO: Future insert operation
-    // pub fn insert(&mut self, polygon: Polygon) { ... }
+/// Recursively builds a BSP node at `depth` (1 for the root) from a list of
+/// polygons, adding the number of polygons cut to `splits`.
+pub(crate) fn build_node<T: Clone, S: PlaneSelector<T>>(
+    polygons: Vec<Polygon<T>>,
+    selector: &S,
+    options: &BuildOptions,
+    depth: usize,
+    splits: &mut usize,
+) -> Option<BspNode<T>> {
+    let NodeSplit {
+        mut node,
+        front,
+        back,
+        splits: node_splits,
+    } = split_node(polygons, selector, options, depth)?;
+    *splits += node_splits;
+    node.set_front(build_node(front, selector, options, depth + 1, splits));
+    node.set_back(build_node(back, selector, options, depth + 1, splits));
+    Some(node)
+}
+
+/// A childless node split off by [`split_node`], with the polygons for its
+/// front and back subtrees.
+pub(crate) struct NodeSplit<T> {
+    pub(crate) node: BspNode<T>,
+    pub(crate) front: Vec<Polygon<T>>,
+    pub(crate) back: Vec<Polygon<T>>,
+    /// Number of polygons cut by the node's plane.
+    pub(crate) splits: usize,
 }
 
-/// Recursively builds a BSP node from a list of polygons.
-fn build_node<S: PlaneSelector>(mut polygons: Vec<Polygon>, selector: &S) -> Option<BspNode> {
+/// Builds a single childless node at `depth` (1 for the root) from a list of
+/// polygons.
+///
+/// Returns the node together with the polygons for its front and back
+/// subtrees, or `None` if there are no polygons. Both lists are empty if the
+/// node is at the [maximum depth](BuildOptions::with_max_depth) or within the
+/// [leaf polygon budget](BuildOptions::with_max_leaf_polygons); the node then
+/// keeps the polygons off its plane unsplit as its
+/// [leftover](BspNode::leftover). Both fragments of a split polygon keep its
+/// [payload](Polygon::data).
+///
+/// Every polygon must have a [plane](Polygon::try_plane); build entry points
+/// drop the others once with [`without_degenerate`]. Fragments always
+/// inherit a plane, so the lists returned for the children need no filtering.
+pub(crate) fn split_node<T: Clone, S: PlaneSelector<T>>(
+    mut polygons: Vec<Polygon<T>>,
+    selector: &S,
+    options: &BuildOptions,
+    depth: usize,
+) -> Option<NodeSplit<T>> {
     if polygons.is_empty() {
         return None;
     }
 
     // Select the splitting polygon and derive the plane
-    let splitter_idx = polygons
-        .iter()
-        .position(|p| Some(p) == selector.select(&polygons))?;
+    let epsilon = options.plane_epsilon();
+    let selected = selector.select_with_epsilon(&polygons, epsilon)?;
+    let splitter_idx = polygons.iter().position(|p| std::ptr::eq(p, selected))?;
+
+    let within_budget = options
+        .max_leaf_polygons()
+        .is_some_and(|max| polygons.len() <= max)
+        && !any_spanning(&polygons, epsilon);
 
     let splitter = polygons.swap_remove(splitter_idx);
     let plane = splitter.plane();
+    let splitter_record = splitter.clone();
 
     // Initialize lists
     let mut coplanar_front = Vec::new();
     let mut coplanar_back = Vec::new();
-    let mut front_list = Vec::new();
-    let mut back_list = Vec::new();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
 
     // The splitter itself is coplanar - determine its facing
-    if faces_same_direction(&splitter, &plane) {
+    if faces_same_direction_with_epsilon(&splitter, &plane, options.facing_epsilon()) {
         coplanar_front.push(splitter);
     } else {
         coplanar_back.push(splitter);
     }
 
+    // At the depth limit or within the leaf budget the node keeps everything
+    // off its plane, unsplit, instead of getting children
+    let at_max_depth = options.max_depth().is_some_and(|max| depth >= max);
+    let keep_unsplit = at_max_depth || within_budget;
+    let mut leftover = Vec::new();
+
     // Classify and partition remaining polygons
+    let mut splits = 0;
+    let mut cut_edges = Vec::new();
     for polygon in polygons {
-        match polygon.classify(&plane) {
-            Classification::Front => {
-                front_list.push(polygon);
-            }
-            Classification::Back => {
-                back_list.push(polygon);
-            }
-            Classification::Coplanar => {
-                if faces_same_direction(&polygon, &plane) {
-                    coplanar_front.push(polygon);
-                } else {
-                    coplanar_back.push(polygon);
-                }
-            }
-            Classification::Spanning => {
-                let (front_part, back_part) = polygon.cut(&plane);
-                if let Some(f) = front_part {
-                    front_list.push(f);
-                }
-                if let Some(b) = back_part {
-                    back_list.push(b);
+        if keep_unsplit
+            && polygon.classify_with_epsilon(&plane, epsilon) != Classification::Coplanar
+        {
+            leftover.push(polygon);
+            continue;
+        }
+        match polygon.split_with_epsilon(&plane, epsilon, options.facing_epsilon()) {
+            SplitResult::Front => front.push(polygon),
+            SplitResult::Back => back.push(polygon),
+            SplitResult::Coplanar { same_facing: true } => coplanar_front.push(polygon),
+            SplitResult::Coplanar { same_facing: false } => coplanar_back.push(polygon),
+            SplitResult::Spanning {
+                front: front_part,
+                back: back_part,
+            } => {
+                splits += 1;
+                if options.record_cut_edges() {
+                    let fragment = front_part.as_ref().or(back_part.as_ref());
+                    cut_edges.extend(fragment.and_then(|f| seam(f, &plane, epsilon)));
                 }
+                front.extend(front_part);
+                back.extend(back_part);
             }
         }
     }
 
-    // Build the node with children
-    let mut node = BspNode::with_coplanar(plane, coplanar_front, coplanar_back);
-    node.set_front(build_node(front_list, selector));
-    node.set_back(build_node(back_list, selector));
+    if options.sort_coplanar() {
+        coplanar_front.sort_by(canonical_order);
+        coplanar_back.sort_by(canonical_order);
+        leftover.sort_by(canonical_order);
+    }
 
-    Some(node)
+    let mut node = BspNode::with_coplanar(plane, coplanar_front, coplanar_back);
+    node.set_leftover(leftover);
+    node.set_splitter(Some(splitter_record));
+    node.set_cut_edges(cut_edges);
+
+    Some(NodeSplit {
+        node,
+        front,
+        back,
+        splits,
+    })
 }
 
-/// Traverses a node subtree front-to-back.
-fn traverse_front_to_back_node<V: BspVisitor>(node: &BspNode, eye: Point3<f32>, visitor: &mut V) {
-    let side = node.plane().classify_point(eye);
+/// Returns `true` if any of the polygons spans the plane of another.
+fn any_spanning<T: Clone>(polygons: &[Polygon<T>], epsilon: f32) -> bool {
+    polygons.iter().any(|splitter| {
+        let plane = splitter.plane();
+        polygons
+            .iter()
+            .any(|p| p.classify_with_epsilon(&plane, epsilon) == Classification::Spanning)
+    })
+}
 
-    // Collect coplanar polygons for visiting
-    let coplanar: Vec<Polygon> = node.all_coplanar().cloned().collect();
+/// Canonical ordering of polygons by centroid, then by vertex coordinates.
+fn canonical_order<T: Clone>(a: &Polygon<T>, b: &Polygon<T>) -> Ordering {
+    let coords = |p: &Point3<f32>| [p.x, p.y, p.z];
+    let (ca, cb) = (a.centroid(), b.centroid());
+    let by_centroid = coords(&ca)
+        .iter()
+        .zip(coords(&cb).iter())
+        .map(|(x, y)| x.total_cmp(y))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal);
+
+    by_centroid.then_with(|| {
+        a.vertices()
+            .iter()
+            .flat_map(coords)
+            .zip(b.vertices().iter().flat_map(coords))
+            .map(|(x, y)| x.total_cmp(&y))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len()))
+    })
+}
 
-    match side {
-        crate::PlaneSide::Front | crate::PlaneSide::OnPlane => {
-            // Eye is in front: front subtree is closer
-            if let Some(front) = node.front() {
-                traverse_front_to_back_node(front, eye, visitor);
-            }
-            if !coplanar.is_empty() {
-                visitor.visit(&coplanar);
-            }
-            if let Some(back) = node.back() {
-                traverse_front_to_back_node(back, eye, visitor);
-            }
+/// Inserts `polygon` into the subtree rooted at `node`.
+fn insert_into<T: Clone>(node: &mut BspNode<T>, polygon: Polygon<T>) {
+    let (front, back) = match polygon.classify(node.plane()) {
+        Classification::Front => (Some(polygon), None),
+        Classification::Back => (None, Some(polygon)),
+        Classification::Coplanar => {
+            add_coplanar(node, vec![polygon]);
+            return;
         }
-        crate::PlaneSide::Back => {
-            // Eye is behind: back subtree is closer
-            if let Some(back) = node.back() {
-                traverse_front_to_back_node(back, eye, visitor);
-            }
-            if !coplanar.is_empty() {
-                visitor.visit(&coplanar);
-            }
-            if let Some(front) = node.front() {
-                traverse_front_to_back_node(front, eye, visitor);
+        Classification::Spanning => {
+            let (front, back) = polygon.cut(node.plane());
+            let fragment = front.as_ref().or(back.as_ref());
+            if let Some(edge) = fragment.and_then(|f| seam(f, node.plane(), crate::PLANE_EPSILON)) {
+                node.add_cut_edge(edge);
             }
+            (front, back)
+        }
+    };
+
+    if let Some(polygon) = front {
+        match node.front_mut() {
+            Some(child) => insert_into(child, polygon),
+            None => node.set_front(leaf_node(polygon)),
+        }
+    }
+    if let Some(polygon) = back {
+        match node.back_mut() {
+            Some(child) => insert_into(child, polygon),
+            None => node.set_back(leaf_node(polygon)),
         }
     }
+    node.update_bounds();
 }
 
-/// Traverses a node subtree back-to-front.
-fn traverse_back_to_front_node<V: BspVisitor>(node: &BspNode, eye: Point3<f32>, visitor: &mut V) {
-    let side = node.plane().classify_point(eye);
-
-    let coplanar: Vec<Polygon> = node.all_coplanar().cloned().collect();
-
-    match side {
-        crate::PlaneSide::Front | crate::PlaneSide::OnPlane => {
-            // Eye is in front: back subtree is farther
-            if let Some(back) = node.back() {
-                traverse_back_to_front_node(back, eye, visitor);
-            }
-            if !coplanar.is_empty() {
-                visitor.visit(&coplanar);
-            }
-            if let Some(front) = node.front() {
-                traverse_back_to_front_node(front, eye, visitor);
+/// Removes the polygons matching `pred` from the subtree rooted at `node`,
+/// at `depth`, rebuilding emptied child subtrees with `selector` and
+/// `options`.
+///
+/// Returns the number removed, and the polygons left in the subtree if
+/// `node` lost all of its own and must be rebuilt by the caller.
+fn remove_matching_from<T, F, S>(
+    node: &mut BspNode<T>,
+    pred: &F,
+    selector: &S,
+    options: &BuildOptions,
+    depth: usize,
+) -> (usize, Option<Vec<Polygon<T>>>)
+where
+    T: Clone,
+    F: Fn(&Polygon<T>) -> bool,
+    S: PlaneSelector<T>,
+{
+    let (front, back) = node.take_coplanar();
+    let leftover = node.take_leftover();
+    let mut removed = front.len() + back.len() + leftover.len();
+    for polygon in front.into_iter().filter(|p| !pred(p)) {
+        node.add_coplanar_front(polygon);
+    }
+    for polygon in back.into_iter().filter(|p| !pred(p)) {
+        node.add_coplanar_back(polygon);
+    }
+    node.set_leftover(leftover.into_iter().filter(|p| !pred(p)).collect());
+    removed -= node.coplanar_count() + node.leftover().len();
+
+    if removed > 0 && node.coplanar_count() == 0 {
+        // The plane came from removed polygons, so the subtree is rebuilt
+        let mut remaining = node.take_leftover();
+        for child in [node.front(), node.back()].into_iter().flatten() {
+            for polygon in child.descendants().flat_map(|(n, _)| n.polygons()) {
+                if pred(polygon) {
+                    removed += 1;
+                } else {
+                    remaining.push(polygon.clone());
+                }
             }
         }
-        crate::PlaneSide::Back => {
-            // Eye is behind: front subtree is farther
-            if let Some(front) = node.front() {
-                traverse_back_to_front_node(front, eye, visitor);
-            }
-            if !coplanar.is_empty() {
-                visitor.visit(&coplanar);
-            }
-            if let Some(back) = node.back() {
-                traverse_back_to_front_node(back, eye, visitor);
-            }
+        return (removed, Some(remaining));
+    }
+    if node.splitter().is_some_and(pred) {
+        node.set_splitter(None);
+    }
+
+    if let Some(child) = node.front_mut() {
+        let (count, rebuild) = remove_matching_from(child, pred, selector, options, depth + 1);
+        removed += count;
+        if let Some(polygons) = rebuild {
+            node.set_front(build_node(polygons, selector, options, depth + 1, &mut 0));
+        }
+    }
+    if let Some(child) = node.back_mut() {
+        let (count, rebuild) = remove_matching_from(child, pred, selector, options, depth + 1);
+        removed += count;
+        if let Some(polygons) = rebuild {
+            node.set_back(build_node(polygons, selector, options, depth + 1, &mut 0));
         }
     }
+    if removed > 0 {
+        prune_cut_edges(node);
+    }
+    node.update_bounds();
+    (removed, None)
 }
 
-/// Recursively collects all polygons from a node subtree.
-fn collect_polygons_recursive(node: Option<&BspNode>, result: &mut Vec<Polygon>) {
-    if let Some(n) = node {
-        result.extend(n.all_coplanar().cloned());
-        collect_polygons_recursive(n.front(), result);
-        collect_polygons_recursive(n.back(), result);
-    }
+/// Drops the cut edges of `node` that no longer run along an edge of a
+/// polygon left in its subtree.
+fn prune_cut_edges<T: Clone>(node: &mut BspNode<T>) {
+    let mut cut_edges = node.cut_edges().to_vec();
+    cut_edges.retain(|&(start, end)| {
+        node.descendants()
+            .flat_map(|(n, _)| n.polygons())
+            .flat_map(Polygon::edges)
+            .any(|(a, b)| on_segment(a, start, end) && on_segment(b, start, end))
+    });
+    node.set_cut_edges(cut_edges);
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::bsp::visitor::CollectingVisitor;
-    use nalgebra::Point3;
+/// Returns `true` if `point` is within
+/// [`PLANE_EPSILON`](crate::PLANE_EPSILON) of the segment from `start` to
+/// `end`.
+fn on_segment(point: Point3<f32>, start: Point3<f32>, end: Point3<f32>) -> bool {
+    let direction = end - start;
+    let length_squared = direction.norm_squared();
+    let t = if length_squared > 0.0 {
+        ((point - start).dot(&direction) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point - (start + direction * t)).norm() <= crate::PLANE_EPSILON
+}
 
-    fn make_triangle(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Polygon {
-        Polygon::new(vec![
-            Point3::new(a[0], a[1], a[2]),
-            Point3::new(b[0], b[1], b[2]),
-            Point3::new(c[0], c[1], c[2]),
-        ])
+/// Clips the polygons of every node below `node` to the outside of the
+/// solid bounded by `solid`.
+fn clip_node_to<T: Clone>(node: &mut BspNode<T>, solid: &BspNode<T>) {
+    let (front, back) = node.take_coplanar();
+    for polygon in clip_polygons(solid, front) {
+        node.add_coplanar_front(polygon);
     }
-
-    #[test]
-    fn empty_tree() {
-        let tree = BspTree::new();
-        assert!(tree.is_empty());
-        assert_eq!(tree.polygon_count(), 0);
-        assert_eq!(tree.depth(), 0);
+    for polygon in clip_polygons(solid, back) {
+        node.add_coplanar_back(polygon);
     }
-
-    #[test]
-    fn build_empty() {
-        let tree = BspTree::from_polygons(vec![]);
-        assert!(tree.is_empty());
+    let leftover = node.take_leftover();
+    node.set_leftover(clip_polygons(solid, leftover));
+    if let Some(child) = node.front_mut() {
+        clip_node_to(child, solid);
+    }
+    if let Some(child) = node.back_mut() {
+        clip_node_to(child, solid);
     }
+    node.update_bounds();
+}
 
-    #[test]
-    fn build_single_polygon() {
-        let poly = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+/// Returns the parts of `polygons` outside the solid bounded by `solid`.
+///
+/// Coplanar polygons facing the same way as a node's plane are treated as
+/// in front of it, others as behind it. Fragments that end behind a node
+/// without a back child are inside the solid and dropped.
+fn clip_polygons<T: Clone>(solid: &BspNode<T>, polygons: Vec<Polygon<T>>) -> Vec<Polygon<T>> {
+    let plane = solid.plane();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    for polygon in polygons {
+        match polygon.split(plane) {
+            SplitResult::Front | SplitResult::Coplanar { same_facing: true } => front.push(polygon),
+            SplitResult::Back | SplitResult::Coplanar { same_facing: false } => back.push(polygon),
+            SplitResult::Spanning {
+                front: front_part,
+                back: back_part,
+            } => {
+                front.extend(front_part);
+                back.extend(back_part);
+            }
+        }
+    }
+
+    let mut outside = match solid.front() {
+        Some(child) => clip_polygons(child, front),
+        None => front,
+    };
+    if let Some(child) = solid.back() {
+        outside.extend(clip_polygons(child, back));
+    }
+    outside
+}
+
+/// Creates a childless node split by `polygon`'s own plane.
+fn leaf_node<T: Clone>(polygon: Polygon<T>) -> Option<BspNode<T>> {
+    let plane = polygon.try_plane()?;
+    let mut node = BspNode::with_coplanar(plane, Vec::new(), Vec::new());
+    node.set_splitter(Some(polygon.clone()));
+    node.add_coplanar_front(polygon);
+    Some(node)
+}
+
+/// Creates a node for a presplit group, sorting polygons by facing.
+fn presplit_node<T: Clone>(plane: Plane3D, polygons: Vec<Polygon<T>>) -> BspNode<T> {
+    let mut node = BspNode::with_coplanar(plane, Vec::new(), Vec::new());
+    add_coplanar(&mut node, polygons);
+    node
+}
+
+/// Adds polygons to a node's coplanar lists according to their facing.
+fn add_coplanar<T: Clone>(node: &mut BspNode<T>, polygons: Vec<Polygon<T>>) {
+    for polygon in polygons {
+        if faces_same_direction(&polygon, node.plane()) {
+            node.add_coplanar_front(polygon);
+        } else {
+            node.add_coplanar_back(polygon);
+        }
+    }
+}
+
+/// Descends from `node` to the place where a presplit group belongs and
+/// attaches it there.
+fn attach_presplit<T: Clone>(node: &mut BspNode<T>, plane: Plane3D, polygons: Vec<Polygon<T>>) {
+    let anchor = if polygons.is_empty() {
+        Point3::from(plane.normal() * plane.offset())
+    } else {
+        let sum: Vector3<f32> = polygons.iter().map(|p| p.centroid().coords).sum();
+        Point3::from(sum / polygons.len() as f32)
+    };
+    attach_presplit_at(node, plane, polygons, anchor);
+}
+
+fn attach_presplit_at<T: Clone>(
+    node: &mut BspNode<T>,
+    plane: Plane3D,
+    polygons: Vec<Polygon<T>>,
+    anchor: Point3<f32>,
+) {
+    if !polygons.is_empty()
+        && polygons
+            .iter()
+            .all(|p| p.classify(node.plane()) == Classification::Coplanar)
+    {
+        add_coplanar(node, polygons);
+        return;
+    }
+
+    match node.plane().classify_point(anchor) {
+        PlaneSide::Front | PlaneSide::OnPlane => match node.front_mut() {
+            Some(front) => attach_presplit_at(front, plane, polygons, anchor),
+            None => node.set_front(Some(presplit_node(plane, polygons))),
+        },
+        PlaneSide::Back => match node.back_mut() {
+            Some(back) => attach_presplit_at(back, plane, polygons, anchor),
+            None => node.set_back(Some(presplit_node(plane, polygons))),
+        },
+    }
+    node.update_bounds();
+}
+
+/// Passes each of `nodes` with its polygons and depth to `visitor`.
+fn visit_nodes<T: Clone, V: BspNodeVisitor<T>>(nodes: OrderedNodes<'_, T>, visitor: &mut V) {
+    for (node, depth) in nodes {
+        let polygons: Vec<Polygon<T>> = node.polygons().cloned().collect();
+        visitor.visit_node(node.plane(), &polygons, depth);
+    }
+}
+
+/// Distributes a node's polygons and those inherited from its ancestors into
+/// the leaves below it.
+fn leaf_polygons_recursive<T: Clone>(
+    node: &BspNode<T>,
+    inherited: Vec<Polygon<T>>,
+    leaves: &mut Vec<Vec<Polygon<T>>>,
+) {
+    let plane = node.plane();
+    let mut front_set: Vec<Polygon<T>> = node.coplanar_front().to_vec();
+    let mut back_set: Vec<Polygon<T>> = node.coplanar_back().to_vec();
+
+    // Leftover polygons are off the plane, like those from the ancestors
+    for polygon in inherited.into_iter().chain(node.leftover().iter().cloned()) {
+        let in_front = match plane.classify_point(polygon.centroid()) {
+            PlaneSide::Front => true,
+            PlaneSide::Back => false,
+            PlaneSide::OnPlane => faces_same_direction(&polygon, plane),
+        };
+        if in_front {
+            front_set.push(polygon);
+        } else {
+            back_set.push(polygon);
+        }
+    }
+
+    match node.front() {
+        Some(front) => leaf_polygons_recursive(front, front_set, leaves),
+        None => leaves.push(front_set),
+    }
+    match node.back() {
+        Some(back) => leaf_polygons_recursive(back, back_set, leaves),
+        None => leaves.push(back_set),
+    }
+}
+
+/// Sums `region_area * polygon_count` over a subtree's leaves.
+fn sah_cost_recursive<T: Clone>(node: &BspNode<T>) -> f32 {
+    if node.is_leaf() {
+        return node.region_surface_area() * node.polygons().count() as f32;
+    }
+    node.front().map_or(0.0, sah_cost_recursive) + node.back().map_or(0.0, sah_cost_recursive)
+}
+
+fn max_coplanar_group_recursive<T: Clone>(node: &BspNode<T>) -> usize {
+    let children = [node.front(), node.back()]
+        .into_iter()
+        .flatten()
+        .map(max_coplanar_group_recursive);
+    children.fold(node.coplanar_count(), usize::max)
+}
+
+fn mirror_node<T: Clone>(node: &BspNode<T>, mirror: &Plane3D) -> BspNode<T> {
+    let mirror_all = |polygons: &[Polygon<T>]| -> Vec<Polygon<T>> {
+        polygons.iter().map(|p| p.mirrored_across(mirror)).collect()
+    };
+    let mut mirrored = BspNode::with_coplanar(
+        node.plane().mirrored_across(mirror),
+        mirror_all(node.coplanar_front()),
+        mirror_all(node.coplanar_back()),
+    );
+    mirrored.set_leftover(mirror_all(node.leftover()));
+    mirrored.set_splitter(node.splitter().map(|s| s.mirrored_across(mirror)));
+    mirrored.set_cut_edges(
+        node.cut_edges()
+            .iter()
+            .map(|&(a, b)| (mirror.reflect_point(a), mirror.reflect_point(b)))
+            .collect(),
+    );
+    mirrored.set_front(node.front().map(|n| mirror_node(n, mirror)));
+    mirrored.set_back(node.back().map(|n| mirror_node(n, mirror)));
+    mirrored
+}
+
+/// Searches a subtree for `id`, leaving the steps taken to it in `path`.
+fn path_to_recursive<T: Clone>(node: &BspNode<T>, id: NodeId, path: &mut Vec<Direction>) -> bool {
+    if node.id() == id {
+        return true;
+    }
+    for (direction, child) in [
+        (Direction::Front, node.front()),
+        (Direction::Back, node.back()),
+    ] {
+        if let Some(child) = child {
+            path.push(direction);
+            if path_to_recursive(child, id, path) {
+                return true;
+            }
+            path.pop();
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsp::selector::{BalancedSelector, FirstPolygon};
+    use crate::bsp::visitor::CollectingVisitor;
+    use crate::test_fixtures::{cube, cuboid};
+    use nalgebra::Point3;
+
+    fn make_triangle(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Polygon {
+        Polygon::new(vec![
+            Point3::new(a[0], a[1], a[2]),
+            Point3::new(b[0], b[1], b[2]),
+            Point3::new(c[0], c[1], c[2]),
+        ])
+    }
+
+    #[test]
+    fn empty_tree() {
+        let tree = BspTree::new();
+        assert!(tree.is_empty());
+        assert_eq!(tree.polygon_count(), 0);
+        assert_eq!(tree.depth(), 0);
+    }
+
+    #[test]
+    fn build_empty() {
+        let tree = BspTree::from_polygons(vec![]);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn build_single_polygon() {
+        let poly = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
         let tree = BspTree::from_polygons(vec![poly]);
 
         assert!(!tree.is_empty());
@@ -357,6 +1409,401 @@ mod tests {
         assert_eq!(tree.polygon_count(), 3);
     }
 
+    #[test]
+    fn build_records_selected_splitter() {
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        let input = vec![poly1.clone(), poly2.clone()];
+
+        let expected = FirstPolygon.select(&input).cloned();
+        let tree = BspTree::build(input, &FirstPolygon);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.splitter(), expected.as_ref());
+        assert_eq!(root.splitter(), Some(&poly1));
+        assert_eq!(root.front().unwrap().splitter(), Some(&poly2));
+    }
+
+    #[test]
+    fn cut_edges_follow_the_splitting_plane() {
+        // The first triangle lies in z = 0 and the second spans it
+        let polygons = vec![
+            make_triangle([-5.0, -5.0, 0.0], [5.0, -5.0, 0.0], [0.0, 5.0, 0.0]),
+            make_triangle([0.0, 0.0, -1.0], [2.0, 0.0, 1.0], [0.0, 2.0, 1.0]),
+        ];
+        let options = BuildOptions::new().with_cut_edges(true);
+        let tree =
+            BspTree::build_with_options(polygons.clone(), &crate::bsp::FirstPolygon, &options);
+        let root = tree.root().unwrap();
+        assert_eq!(root.cut_edges().len(), 1);
+        let (a, b) = root.cut_edges()[0];
+        assert!(root.plane().signed_distance(a).abs() < 1e-5);
+        assert!(root.plane().signed_distance(b).abs() < 1e-5);
+        let mut ends = [a, b];
+        ends.sort_by(|p, q| p.x.total_cmp(&q.x));
+        assert!((ends[0] - Point3::new(0.0, 1.0, 0.0)).norm() < 1e-5);
+        assert!((ends[1] - Point3::new(1.0, 0.0, 0.0)).norm() < 1e-5);
+
+        let tree = BspTree::build(polygons, &crate::bsp::FirstPolygon);
+        assert!(tree.root().unwrap().cut_edges().is_empty());
+    }
+
+    #[test]
+    fn mirrored_keeps_cut_edges() {
+        let polygons = vec![
+            make_triangle([-5.0, -5.0, 0.0], [5.0, -5.0, 0.0], [0.0, 5.0, 0.0]),
+            make_triangle([0.0, 0.0, -1.0], [2.0, 0.0, 1.0], [0.0, 2.0, 1.0]),
+        ];
+        let options = BuildOptions::new().with_cut_edges(true);
+        let tree = BspTree::build_with_options(polygons, &crate::bsp::FirstPolygon, &options);
+        let mirror = Plane3D::new(Vector3::new(1.0, 0.0, 0.0), 0.0);
+        let mirrored = tree.mirrored_across(&mirror);
+
+        let (a, b) = tree.root().unwrap().cut_edges()[0];
+        let expected = (mirror.reflect_point(a), mirror.reflect_point(b));
+        assert_eq!(mirrored.root().unwrap().cut_edges(), &[expected]);
+    }
+
+    #[test]
+    fn insert_records_cut_edges() {
+        let floor = make_triangle([-5.0, -5.0, 0.0], [5.0, -5.0, 0.0], [0.0, 5.0, 0.0]);
+        let mut tree = BspTree::build(vec![floor], &crate::bsp::FirstPolygon);
+        let spanning = make_triangle([0.0, 0.0, -1.0], [2.0, 0.0, 1.0], [0.0, 2.0, 1.0]);
+        tree.insert(spanning);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.cut_edges().len(), 1);
+        let (a, b) = root.cut_edges()[0];
+        assert!(root.plane().signed_distance(a).abs() < 1e-5);
+        assert!(root.plane().signed_distance(b).abs() < 1e-5);
+    }
+
+    #[test]
+    fn remove_matching_drops_cut_edges_of_removed_polygons() {
+        let polygons = vec![
+            make_triangle([-5.0, -5.0, 0.0], [5.0, -5.0, 0.0], [0.0, 5.0, 0.0]),
+            make_triangle([0.0, 0.0, -1.0], [2.0, 0.0, 1.0], [0.0, 2.0, 1.0]),
+            make_triangle([-3.0, 0.0, -1.0], [-1.0, 0.0, 1.0], [-3.0, 2.0, 1.0]),
+        ];
+        let options = BuildOptions::new().with_cut_edges(true);
+        let mut tree = BspTree::build_with_options(polygons, &crate::bsp::FirstPolygon, &options);
+        assert_eq!(tree.root().unwrap().cut_edges().len(), 2);
+
+        // Both fragments of the second triangle lie at x >= 0
+        let on_right = |p: &Polygon| p.vertices().iter().all(|v| v.x >= -1e-5);
+        let removed = tree.remove_matching(on_right, &FirstPolygon, &options);
+        assert_eq!(removed, 2);
+        let root = tree.root().unwrap();
+        assert_eq!(root.cut_edges().len(), 1);
+        assert!(root.cut_edges()[0].0.x < 0.0);
+    }
+
+    #[test]
+    fn build_with_script_follows_script() {
+        let at_0 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let at_1 = make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        let at_minus_1 = make_triangle([0.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 1.0, -1.0]);
+        let input = vec![at_0.clone(), at_1.clone(), at_minus_1.clone()];
+
+        // Root splits at z = 1, leaving [z = 0, z = -1] behind it; the back
+        // node then splits at z = -1, leaving z = 0 in front of it
+        let tree = BspTree::build_with_script(input, &[1, 1]);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.splitter(), Some(&at_1));
+        assert!(root.front().is_none());
+        let back = root.back().unwrap();
+        assert_eq!(back.splitter(), Some(&at_minus_1));
+        assert!(back.back().is_none());
+        let leaf = back.front().unwrap();
+        assert_eq!(leaf.splitter(), Some(&at_0));
+        assert!(leaf.is_leaf());
+        assert_eq!(tree.depth(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn build_with_script_rejects_bad_index() {
+        let poly = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        BspTree::build_with_script(vec![poly], &[1]);
+    }
+
+    #[test]
+    fn sorted_coplanar_is_input_order_independent() {
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [1.0, 1.0, 0.0]);
+        let poly3 = make_triangle([0.0, 2.0, 0.0], [1.0, 2.0, 0.0], [0.0, 3.0, 0.0]);
+        let options = BuildOptions::new().with_sorted_coplanar(true);
+
+        let tree_a = BspTree::build_with_options(
+            vec![poly1.clone(), poly2.clone(), poly3.clone()],
+            &FirstPolygon,
+            &options,
+        );
+        let tree_b =
+            BspTree::build_with_options(vec![poly3, poly1, poly2], &FirstPolygon, &options);
+
+        let root_a = tree_a.root().unwrap();
+        let root_b = tree_b.root().unwrap();
+        assert_eq!(root_a.coplanar_front().len(), 3);
+        assert_eq!(root_a.coplanar_front(), root_b.coplanar_front());
+        assert_eq!(root_a.coplanar_back(), root_b.coplanar_back());
+    }
+
+    #[test]
+    fn facing_epsilon_sorts_perpendicular_sliver_deterministically() {
+        let splitter = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        // Thin enough to be coplanar with z = 0, but its normal is -Y
+        let sliver = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.5, 0.0, 5e-5]);
+        let input = vec![splitter, sliver];
+
+        let tree = BspTree::from_polygons(input.clone());
+        assert_eq!(tree.root().unwrap().coplanar_front().len(), 2);
+
+        let options = BuildOptions::new().with_facing_epsilon(0.0);
+        let tree = BspTree::build_with_options(input, &FirstPolygon, &options);
+        assert_eq!(tree.root().unwrap().coplanar_front().len(), 1);
+        assert_eq!(tree.root().unwrap().coplanar_back().len(), 1);
+    }
+
+    #[test]
+    fn plane_epsilon_treats_jittered_faces_as_coplanar() {
+        // Kilometer-scale ground with a neighbouring face off by centimeters
+        let ground = make_triangle([0.0, 0.0, 0.0], [1000.0, 0.0, 0.0], [0.0, 1000.0, 0.0]);
+        let jittered = make_triangle([0.0, 0.0, 0.01], [-1000.0, 0.0, -0.01], [0.0, -1000.0, 0.0]);
+        let input = vec![ground, jittered];
+
+        let tree = BspTree::from_polygons(input.clone());
+        assert_eq!(tree.polygon_count(), 3);
+        assert!(!tree.root().unwrap().is_leaf());
+
+        let options = BuildOptions::new().with_plane_epsilon(0.1);
+        let tree = BspTree::build_with_options(input, &FirstPolygon, &options);
+        assert_eq!(tree.polygon_count(), 2);
+        assert!(tree.root().unwrap().is_leaf());
+        assert_eq!(tree.root().unwrap().coplanar_front().len(), 2);
+    }
+
+    #[test]
+    fn max_depth_keeps_remaining_polygons_at_capped_nodes() {
+        // Stack of parallel planes at z = 0..8, a chain of 8 nodes uncapped
+        let polygons: Vec<Polygon> = (0..8)
+            .map(|i| {
+                let z = i as f32;
+                make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z])
+            })
+            .collect();
+        assert_eq!(BspTree::from_polygons(polygons.clone()).depth(), 8);
+
+        let options = BuildOptions::new().with_max_depth(Some(3));
+        let tree = BspTree::build_with_options(polygons.clone(), &FirstPolygon, &options);
+        assert_eq!(tree.depth(), 3);
+        assert_eq!(tree.polygon_count(), 8);
+        assert_eq!(tree.aabb(), BspTree::from_polygons(polygons.clone()).aabb());
+
+        let mut capped = tree.root().unwrap();
+        while let Some(child) = capped.front().or(capped.back()) {
+            capped = child;
+        }
+        assert_eq!(capped.coplanar_count(), 1);
+        assert_eq!(capped.leftover().len(), 5);
+
+        let options = BuildOptions::new().with_max_depth(Some(1));
+        let tree = BspTree::build_with_options(polygons, &FirstPolygon, &options);
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.polygon_count(), 8);
+    }
+
+    #[test]
+    fn max_depth_leaves_spanning_polygons_unsplit() {
+        let floor = make_triangle([-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [0.0, 1.0, 0.0]);
+        let wall = make_triangle([0.0, 0.0, -1.0], [0.0, 0.5, 1.0], [0.0, -0.5, 1.0]);
+
+        let options = BuildOptions::new()
+            .with_max_depth(Some(1))
+            .with_cut_edges(true);
+        let (tree, report) =
+            BspTree::build_with_report(vec![floor, wall.clone()], &FirstPolygon, &options);
+        assert_eq!(report.splits, 0);
+        assert_eq!(tree.polygon_count(), 2);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.coplanar_count(), 1);
+        assert_eq!(root.leftover(), std::slice::from_ref(&wall));
+        assert!(root.cut_edges().is_empty());
+    }
+
+    #[test]
+    fn leaf_budget_keeps_closed_shape_in_one_leaf() {
+        let cube = cuboid([0.0; 3], [1.0; 3]);
+        assert_eq!(BspTree::from_polygons(cube.clone()).depth(), 6);
+
+        let options = BuildOptions::new().with_max_leaf_polygons(Some(6));
+        let tree = BspTree::build_with_options(cube.clone(), &FirstPolygon, &options);
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.root().unwrap().leaf_contents().count(), 6);
+
+        // Two cubes exceed the budget, so they are separated first
+        let mut polygons = cube;
+        polygons.extend(cuboid([2.0, 0.0, 0.0], [3.0, 1.0, 1.0]));
+        let tree = BspTree::build_with_options(polygons, &FirstPolygon, &options);
+        assert_eq!(tree.polygon_count(), 12);
+        assert_eq!(tree.root().unwrap().leaf_contents().count(), 0);
+        assert!(tree.depth() < 6);
+    }
+
+    #[test]
+    fn leaf_budget_still_splits_spanning_polygons() {
+        let horizontal = make_triangle([-1.0, -1.0, 0.0], [2.0, -1.0, 0.0], [-1.0, 2.0, 0.0]);
+        let vertical = make_triangle([0.2, 0.2, -1.0], [0.3, 0.2, 1.0], [0.2, 0.3, 1.0]);
+
+        let options = BuildOptions::new().with_max_leaf_polygons(Some(8));
+        let tree = BspTree::build_with_options(vec![horizontal, vertical], &FirstPolygon, &options);
+        assert_eq!(tree.polygon_count(), 3);
+        assert!(!tree.root().unwrap().is_leaf());
+    }
+
+    #[test]
+    fn leaf_depth_reflects_subdivision() {
+        // Stack of parallel planes at z = 0..4, all facing +Z
+        let polygons: Vec<Polygon> = (0..5)
+            .map(|i| {
+                let z = i as f32;
+                make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z])
+            })
+            .collect();
+        let tree = BspTree::from_polygons(polygons);
+
+        // Behind the root plane there is nothing else to split
+        let sparse = tree.leaf_depth(Point3::new(0.0, 0.0, -5.0));
+        // Between z = 0 and z = 1 every plane has to be passed
+        let dense = tree.leaf_depth(Point3::new(0.0, 0.0, 0.5));
+
+        assert_eq!(sparse, 1);
+        assert_eq!(dense, 5);
+        assert!(dense > sparse);
+        assert_eq!(BspTree::new().leaf_depth(Point3::origin()), 0);
+    }
+
+    #[test]
+    fn aabb_bounds_all_polygons() {
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([0.0, 0.0, 2.0], [3.0, 0.0, 2.0], [0.0, 1.0, 2.0]);
+        let poly3 = make_triangle([-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, -2.0, -1.0]);
+        let tree = BspTree::from_polygons(vec![poly1, poly2, poly3]);
+
+        let aabb = tree.aabb().unwrap();
+        assert_eq!(aabb.min(), Point3::new(-1.0, -2.0, -1.0));
+        assert_eq!(aabb.max(), Point3::new(3.0, 1.0, 2.0));
+        assert!(BspTree::new().aabb().is_none());
+    }
+
+    #[test]
+    fn with_root_at_isolates_subtree() {
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        let poly3 = make_triangle([0.0, 0.0, 2.0], [1.0, 0.0, 2.0], [0.0, 1.0, 2.0]);
+        let below = make_triangle([0.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 1.0, -1.0]);
+        let tree = BspTree::from_polygons(vec![poly1, poly2.clone(), poly3.clone(), below]);
+
+        let isolated = tree.with_root_at(&[Direction::Front]);
+
+        let front = tree.root().unwrap().front().unwrap();
+        let expected: Vec<Polygon> = front
+            .descendants()
+            .flat_map(|(node, _)| node.all_coplanar())
+            .cloned()
+            .collect();
+        assert_eq!(isolated.collect_polygons(), expected);
+        assert_eq!(isolated.root().unwrap().id(), NodeId(0));
+
+        // The isolated tree holds z = 1 and z = 2 and traverses on its own
+        let mut visitor = CollectingVisitor::new();
+        isolated.traverse_front_to_back(Point3::new(0.2, 0.2, 5.0), &mut visitor);
+        assert_eq!(visitor.into_polygons(), vec![poly3, poly2]);
+
+        let past_leaf = tree.with_root_at(&[Direction::Back, Direction::Back]);
+        assert!(past_leaf.is_empty());
+    }
+
+    #[test]
+    fn path_to_reproduces_every_node_path() {
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        let poly3 = make_triangle([0.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 1.0, -1.0]);
+        let spanning = make_triangle([-0.5, -1.0, -2.0], [0.5, 1.0, 2.0], [0.5, -1.0, 2.0]);
+        let tree = BspTree::from_polygons(vec![poly1, poly2, poly3, spanning]);
+
+        // Walk every node, recording the path used to reach it
+        let mut stack = vec![(tree.root().unwrap(), Vec::new())];
+        let mut visited = 0;
+        while let Some((node, path)) = stack.pop() {
+            assert_eq!(tree.path_to(node.id()), Some(path.clone()));
+            visited += 1;
+            for (direction, child) in [
+                (Direction::Front, node.front()),
+                (Direction::Back, node.back()),
+            ] {
+                if let Some(child) = child {
+                    let mut child_path = path.clone();
+                    child_path.push(direction);
+                    stack.push((child, child_path));
+                }
+            }
+        }
+
+        assert!(visited > 2);
+        assert_eq!(tree.root().unwrap().id(), NodeId(0));
+        assert_eq!(tree.path_to(NodeId(visited)), None);
+    }
+
+    #[test]
+    fn sah_cost_lower_for_balanced_tree() {
+        // Cluster of parallel squares stacked along Z, given in an order that
+        // makes FirstPolygon build a long chain. With the depth capped, the
+        // chain ends in one large leaf holding most of the squares.
+        let polygons: Vec<Polygon> = (0..16)
+            .map(|i| {
+                let z = i as f32 * 0.1;
+                let s = 1.0 + i as f32;
+                Polygon::new(vec![
+                    Point3::new(0.0, 0.0, z),
+                    Point3::new(s, 0.0, z),
+                    Point3::new(s, s, z),
+                    Point3::new(0.0, s, z),
+                ])
+            })
+            .collect();
+
+        let options = BuildOptions::new().with_max_depth(Some(4));
+        let first = BspTree::build_with_options(polygons.clone(), &FirstPolygon, &options);
+        let balanced =
+            BspTree::build_with_options(polygons, &BalancedSelector::default(), &options);
+
+        assert!(
+            balanced.sah_cost() < first.sah_cost(),
+            "balanced {} should be below first {}",
+            balanced.sah_cost(),
+            first.sah_cost()
+        );
+        assert_eq!(BspTree::new().sah_cost(), 0.0);
+    }
+
+    #[test]
+    fn build_skips_degenerate_polygons() {
+        let sliver = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(2.0, 2.0, 2.0),
+        ]);
+        let valid = make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+
+        let tree = BspTree::from_polygons(vec![sliver.clone(), valid.clone()]);
+        assert_eq!(tree.collect_polygons(), vec![valid]);
+
+        assert!(BspTree::from_polygons(vec![sliver]).is_empty());
+    }
+
     #[test]
     fn traverse_front_to_back_single() {
         let poly = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
@@ -420,6 +1867,790 @@ mod tests {
         );
     }
 
+    #[test]
+    fn traverse_nodes_reports_plane_and_depth() {
+        #[derive(Default)]
+        struct Recorder {
+            nodes: Vec<(Plane3D, usize, usize)>,
+            polygons: Vec<Polygon>,
+        }
+
+        impl BspNodeVisitor for Recorder {
+            fn visit_node(&mut self, plane: &Plane3D, coplanar: &[Polygon], depth: usize) {
+                self.nodes.push((plane.clone(), coplanar.len(), depth));
+                self.polygons.extend_from_slice(coplanar);
+            }
+        }
+
+        let tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]));
+        let eye = Point3::new(3.0, 0.5, 0.5);
+        let mut recorder = Recorder::default();
+        tree.traverse_nodes(eye, &mut recorder);
+
+        let expected: Vec<_> = tree
+            .iter_nodes_dfs()
+            .map(|(node, depth)| (node.plane().clone(), node.all_coplanar().count(), depth))
+            .collect();
+        assert_eq!(recorder.nodes.len(), expected.len());
+        for visited in &recorder.nodes {
+            assert!(expected.contains(visited), "unexpected node {visited:?}");
+        }
+        assert!(recorder.nodes.iter().any(|&(_, _, depth)| depth > 1));
+        assert!(recorder.nodes.iter().all(|&(_, _, depth)| depth >= 1));
+
+        // The polygons come in the same order as the polygon-only traversal
+        let mut visitor = CollectingVisitor::new();
+        tree.traverse_front_to_back(eye, &mut visitor);
+        assert_eq!(recorder.polygons, visitor.into_polygons());
+    }
+
+    #[test]
+    fn transform_matches_transforming_the_input() {
+        let mut tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 2.0, 3.0]));
+        let original = tree.collect_polygons();
+        let iso = Isometry3::new(Vector3::new(5.0, -2.0, 1.0), Vector3::new(0.4, 0.7, -0.2));
+
+        tree.transform(&iso);
+
+        let moved = tree.collect_polygons();
+        assert_eq!(moved.len(), original.len());
+        for (moved, original) in moved.iter().zip(&original) {
+            let expected = original.transformed(&iso);
+            for (a, b) in moved.vertices().iter().zip(expected.vertices()) {
+                assert!((a - b).norm() < 1e-5);
+            }
+        }
+
+        // The moved eye sees the moved polygons in the original order
+        let eye = Point3::new(4.0, 1.0, -2.0);
+        let mut before = CollectingVisitor::new();
+        let original = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 2.0, 3.0]));
+        original.traverse_back_to_front(eye, &mut before);
+        let mut after = CollectingVisitor::new();
+        tree.traverse_back_to_front(iso * eye, &mut after);
+        let before: Vec<_> = before
+            .polygons()
+            .iter()
+            .map(|p| p.transformed(&iso))
+            .collect();
+        let after = after.into_polygons();
+        assert_eq!(after.len(), before.len());
+        for (a, b) in after.iter().zip(&before) {
+            assert!((a.centroid() - b.centroid()).norm() < 1e-5);
+        }
+
+        let bounds = tree.root().and_then(BspNode::bounds).unwrap();
+        for polygon in &moved {
+            for vertex in polygon.vertices() {
+                assert!(bounds.contains_point(*vertex));
+            }
+        }
+        for (node, _) in tree.iter_nodes_dfs() {
+            for polygon in node.all_coplanar() {
+                let distance = node.plane().signed_distance(polygon.centroid());
+                assert!(distance.abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn for_each_polygon_mut_updates_bounds() {
+        let mut tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]));
+        let mut visited = 0;
+        tree.for_each_polygon_mut(|polygon| {
+            *polygon = polygon.flipped();
+            visited += 1;
+        });
+        assert_eq!(visited, 6);
+        // Flipped faces of the cube point inward
+        let center = Point3::new(0.5, 0.5, 0.5);
+        for polygon in tree.collect_polygons() {
+            assert!(polygon.normal().dot(&(polygon.centroid() - center)) < 0.0);
+        }
+
+        let shift = Isometry3::translation(0.0, 0.0, 10.0);
+        tree.for_each_polygon_mut(|polygon| *polygon = polygon.transformed(&shift));
+        let bounds = tree.root().and_then(BspNode::bounds).unwrap();
+        assert!((bounds.min() - Point3::new(0.0, 0.0, 10.0)).norm() < 1e-6);
+        assert!((bounds.max() - Point3::new(1.0, 1.0, 11.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn deep_chain_does_not_overflow_the_stack() {
+        const DEPTH: usize = 100_000;
+
+        // A chain of parallel triangles, as FirstPolygon builds from them,
+        // assembled bottom-up to avoid the recursive build. Run on a small
+        // stack so any recursion per level would overflow.
+        let run = || {
+            let mut chain: Option<BspNode> = None;
+            for i in (0..DEPTH).rev() {
+                let z = i as f32;
+                let polygon = make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z]);
+                let mut node = leaf_node(polygon).unwrap();
+                node.set_front(chain);
+                chain = Some(node);
+            }
+            let tree = BspTree::from_root(chain);
+
+            assert_eq!(tree.depth(), DEPTH);
+            assert_eq!(tree.polygon_count(), DEPTH);
+            assert_eq!(tree.collect_polygons().len(), DEPTH);
+            assert_eq!(
+                tree.iter_nodes_dfs().last().unwrap().0.id(),
+                NodeId(DEPTH - 1)
+            );
+
+            let eye = Point3::new(0.25, 0.25, -1.0);
+            let mut visitor = CollectingVisitor::new();
+            tree.traverse_front_to_back(eye, &mut visitor);
+            let collected = visitor.into_polygons();
+            assert_eq!(collected.len(), DEPTH);
+            assert_eq!(collected[1].centroid().z, 1.0);
+
+            let mut visitor = CollectingVisitor::new();
+            tree.traverse_back_to_front(eye, &mut visitor);
+            assert_eq!(visitor.into_polygons()[0].centroid().z, (DEPTH - 1) as f32);
+        };
+        std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(run)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn assemble_presplit_two_nodes() {
+        let poly_far = make_triangle([0.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 1.0, -1.0]);
+        let poly_near = make_triangle([0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+
+        let tree = BspTree::assemble_presplit(vec![
+            (poly_far.plane(), vec![poly_far.clone()]),
+            (poly_near.plane(), vec![poly_near.clone()]),
+        ]);
+
+        assert_eq!(tree.polygon_count(), 2);
+        assert_eq!(tree.depth(), 2);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.coplanar_front(), std::slice::from_ref(&poly_far));
+        assert!(root.back().is_none());
+        assert_eq!(
+            root.front().unwrap().coplanar_front(),
+            std::slice::from_ref(&poly_near)
+        );
+
+        let mut visitor = CollectingVisitor::new();
+        tree.traverse_back_to_front(Point3::new(0.5, 0.5, 10.0), &mut visitor);
+        assert_eq!(visitor.into_polygons(), vec![poly_far, poly_near]);
+    }
+
+    #[test]
+    fn assemble_presplit_merges_coplanar_groups() {
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [1.0, 1.0, 0.0]);
+
+        let tree = BspTree::assemble_presplit(vec![
+            (poly1.plane(), vec![poly1]),
+            (poly2.plane(), vec![poly2]),
+        ]);
+
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.root().unwrap().coplanar_count(), 2);
+    }
+
+    #[test]
+    fn leaf_polygons_partition_all_polygons() {
+        // Two opposing walls and a spanning polygon that gets split
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([0.0, 0.0, 2.0], [0.0, 1.0, 2.0], [1.0, 0.0, 2.0]);
+        let spanning = make_triangle([-0.5, 0.5, -1.0], [0.5, 0.5, 3.0], [0.5, -0.5, -1.0]);
+
+        let tree = BspTree::from_polygons(vec![poly1, poly2, spanning]);
+        let leaves = tree.leaf_polygons();
+        let all = tree.collect_polygons();
+
+        let flattened: Vec<Polygon> = leaves.into_iter().flatten().collect();
+        assert_eq!(flattened.len(), all.len());
+        for polygon in &all {
+            let occurrences = flattened.iter().filter(|p| *p == polygon).count();
+            assert_eq!(
+                occurrences, 1,
+                "{polygon:?} should appear in exactly one leaf"
+            );
+        }
+    }
+
+    #[test]
+    fn leaf_polygons_groups_facing_walls() {
+        // poly1 faces +Z, poly2 at z=2 faces -Z: both bound the cell between them
+        let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let poly2 = make_triangle([0.0, 0.0, 2.0], [0.0, 1.0, 2.0], [1.0, 0.0, 2.0]);
+
+        let tree = BspTree::from_polygons(vec![poly1, poly2]);
+        let leaves = tree.leaf_polygons();
+
+        // root front -> node(z=2) gives 2 leaves, root back gives 1
+        assert_eq!(leaves.len(), 3);
+        assert_eq!(leaves.iter().filter(|l| l.len() == 2).count(), 1);
+    }
+
+    #[test]
+    fn convex_hull_of_cube() {
+        let tree = BspTree::from_polygons(cube());
+        let hull = tree.convex_hull();
+
+        assert_eq!(hull.len(), 12);
+        let volume: f32 = hull
+            .iter()
+            .map(|f| {
+                let v = f.vertices();
+                v[0].coords.dot(&v[1].coords.cross(&v[2].coords)) / 6.0
+            })
+            .sum();
+        assert!((volume - 8.0).abs() < 1e-4, "volume was {volume}");
+    }
+
+    #[test]
+    fn convex_hull_of_empty_tree() {
+        assert!(BspTree::new().convex_hull().is_empty());
+    }
+
+    #[test]
+    fn insert_skips_degenerate_polygons() {
+        let sliver = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]);
+        let mut tree = BspTree::new();
+        tree.insert(sliver.clone());
+        assert!(tree.is_empty());
+
+        let triangle = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        tree.insert(triangle);
+        tree.insert(sliver);
+        assert_eq!(tree.polygon_count(), 1);
+    }
+
+    #[test]
+    fn insert_into_empty_tree() {
+        let mut tree = BspTree::new();
+        let poly = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+
+        tree.insert(poly.clone());
+
+        assert_eq!(tree.polygon_count(), 1);
+        let root = tree.root().unwrap();
+        assert_eq!(root.coplanar_front().len(), 1);
+        assert_eq!(root.coplanar_front()[0], poly);
+        assert_eq!(root.splitter(), Some(&poly));
+    }
+
+    #[test]
+    fn insert_coplanar_polygons_by_facing() {
+        let mut tree = BspTree::from_polygons(vec![make_triangle(
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+        )]);
+
+        let same_facing = make_triangle([2.0, 0.0, 0.0], [3.0, 0.0, 0.0], [2.0, 1.0, 0.0]);
+        let opposite = make_triangle([2.0, 0.0, 0.0], [2.0, 1.0, 0.0], [3.0, 0.0, 0.0]);
+        tree.insert(same_facing);
+        tree.insert(opposite);
+
+        let root = tree.root().unwrap();
+        assert_eq!(tree.polygon_count(), 3);
+        assert_eq!(root.coplanar_front().len(), 2);
+        assert_eq!(root.coplanar_back().len(), 1);
+        assert!(root.is_leaf());
+    }
+
+    #[test]
+    fn insert_splits_spanning_polygon() {
+        // Root plane z = 0; the inserted triangle crosses it
+        let mut tree = BspTree::from_polygons(vec![make_triangle(
+            [-5.0, -5.0, 0.0],
+            [5.0, -5.0, 0.0],
+            [0.0, 5.0, 0.0],
+        )]);
+
+        let spanning = make_triangle([0.0, 0.0, -1.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+        tree.insert(spanning);
+
+        let root = tree.root().unwrap();
+        assert_eq!(tree.polygon_count(), 3);
+        assert_eq!(root.front().unwrap().polygon_count(), 1);
+        assert_eq!(root.back().unwrap().polygon_count(), 1);
+        assert_eq!(root.front().unwrap().id(), NodeId(1));
+        assert_eq!(root.back().unwrap().id(), NodeId(2));
+    }
+
+    #[test]
+    fn inserting_cube_faces_builds_solid() {
+        let mut tree = BspTree::new();
+        for face in cube() {
+            tree.insert(face);
+        }
+
+        assert_eq!(tree.polygon_count(), 6);
+        assert!(tree.contains_point(Point3::new(1.0, 1.0, 1.0)));
+        assert!(!tree.contains_point(Point3::new(3.0, 1.0, 1.0)));
+        assert!((tree.volume() - 8.0).abs() < 1e-4);
+    }
+
+    /// Asserts that every node's cached bounds match its subtree's polygons.
+    fn assert_bounds_fresh(node: &BspNode) {
+        let polygons = node.descendants().flat_map(|(n, _)| n.polygons());
+        let expected = Aabb::from_points(polygons.flat_map(|p| p.vertices()));
+        assert_eq!(node.bounds().copied(), expected);
+        for child in [node.front(), node.back()].into_iter().flatten() {
+            assert_bounds_fresh(child);
+        }
+    }
+
+    #[test]
+    fn culled_traversal_skips_subtrees_outside_frustum() {
+        let mut visible = cuboid([-1.0, -1.0, -6.0], [1.0, 1.0, -4.0]);
+        let behind = cuboid([-1.0, -1.0, 4.0], [1.0, 1.0, 6.0]);
+        // Splitting first at the visible cube's +z face puts the other cube
+        // alone in the root's front subtree
+        let facing_eye = visible.iter().position(|p| p.normal().z > 0.0).unwrap();
+        visible.swap(0, facing_eye);
+        let tree = BspTree::from_polygons([visible.clone(), behind].concat());
+        let eye = Point3::origin();
+        let frustum = Frustum::from_camera(
+            eye,
+            Point3::new(0.0, 0.0, -1.0),
+            Vector3::y(),
+            90.0_f32.to_radians(),
+            1.0,
+            0.1,
+            100.0,
+        );
+
+        let mut all = CollectingVisitor::new();
+        tree.traverse_front_to_back(eye, &mut all);
+        let mut culled = CollectingVisitor::new();
+        tree.traverse_front_to_back_culled(eye, &frustum, &mut culled);
+        let (all, culled) = (all.into_polygons(), culled.into_polygons());
+
+        assert_eq!(culled.len(), visible.len());
+        assert!(visible.iter().all(|p| culled.contains(p)));
+        // Culling only drops polygons, keeping the order of the rest
+        let mut remaining = all.iter();
+        assert!(culled.iter().all(|p| remaining.any(|q| q == p)));
+    }
+
+    #[test]
+    fn bounds_stay_fresh_through_build_insert_and_csg() {
+        let mut tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0]));
+        assert_bounds_fresh(tree.root().unwrap());
+
+        let sticking_out = make_triangle([-1.0, 0.5, 1.0], [5.0, 0.5, 1.0], [-1.0, 1.5, 1.0]);
+        tree.insert(sticking_out);
+        assert_bounds_fresh(tree.root().unwrap());
+        assert_eq!(tree.aabb().unwrap().max().x, 5.0);
+
+        let a = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0]));
+        let b = BspTree::from_polygons(cuboid([1.0, -1.0, -1.0], [3.0, 3.0, 3.0]));
+        let difference = a.difference(&b);
+        assert_bounds_fresh(difference.root().unwrap());
+        assert_eq!(difference.aabb().unwrap().max().x, 1.0);
+    }
+
+    /// Asserts that every polygon of `tree` separates its inside from its
+    /// outside, i.e. no polygon is left dangling inside or outside the solid.
+    fn assert_is_boundary(tree: &BspTree) {
+        for polygon in tree.collect_polygons() {
+            let centroid = polygon.centroid();
+            let normal = polygon.unit_normal().unwrap();
+            assert!(
+                tree.contains_point(centroid - normal * 0.01),
+                "polygon at {centroid:?} has nothing behind it"
+            );
+            assert!(
+                !tree.contains_point(centroid + normal * 0.01),
+                "polygon at {centroid:?} has solid in front of it"
+            );
+        }
+    }
+
+    #[test]
+    fn remove_matching_leaves_the_other_solid() {
+        let mut polygons = cuboid([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+        polygons.extend(cuboid([3.0, 0.0, 0.0], [4.0, 1.0, 1.0]));
+        let mut tree = BspTree::from_polygons(polygons);
+        let count = tree.polygon_count();
+
+        let options = BuildOptions::default();
+        let removed = tree.remove_matching(|p| p.centroid().x > 2.0, &FirstPolygon, &options);
+
+        assert_eq!(removed, count - 6);
+        assert_eq!(tree.polygon_count(), 6);
+        assert_is_boundary(&tree);
+        assert!(tree.contains_point(Point3::new(0.5, 0.5, 0.5)));
+        assert!(!tree.contains_point(Point3::new(3.5, 0.5, 0.5)));
+        let ids: Vec<NodeId> = tree.iter_nodes_dfs().map(|(node, _)| node.id()).collect();
+        assert_eq!(ids, (0..ids.len()).map(NodeId).collect::<Vec<_>>());
+        let first = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]));
+        assert_eq!(tree.aabb(), first.aabb());
+    }
+
+    #[test]
+    fn remove_matching_keeps_nodes_that_keep_polygons() {
+        let mut tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]));
+        let decal = make_triangle([0.2, 0.2, 0.0], [0.4, 0.2, 0.0], [0.2, 0.4, 0.0]);
+        tree.insert(decal);
+        let planes = tree.to_plane_tree();
+        let options = BuildOptions::default();
+
+        assert_eq!(
+            tree.remove_matching(|p| p.area() < 0.1, &FirstPolygon, &options),
+            1
+        );
+        assert_eq!(tree.to_plane_tree(), planes);
+        assert_eq!(tree.remove_matching(|_| false, &FirstPolygon, &options), 0);
+        assert_eq!(tree.to_plane_tree(), planes);
+
+        assert_eq!(tree.remove_matching(|_| true, &FirstPolygon, &options), 6);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn remove_matching_rebuilds_with_the_given_options() {
+        // The second cube is entirely in front of the first cube's first face
+        let mut polygons = cuboid([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+        polygons.extend(cuboid([3.0, 3.0, 3.0], [4.0, 4.0, 4.0]));
+        let mut tree = BspTree::from_polygons(polygons);
+
+        let options = BuildOptions::new().with_max_leaf_polygons(Some(6));
+        let removed = tree.remove_matching(|p| p.centroid().x < 2.0, &FirstPolygon, &options);
+
+        assert_eq!(removed, 6);
+        assert_eq!(tree.polygon_count(), 6);
+        assert_eq!(tree.depth(), 1);
+    }
+
+    #[test]
+    fn rebuild_keeps_the_polygons() {
+        let mut tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 2.0, 3.0]));
+        let polygons = tree.collect_polygons_sorted();
+
+        tree.rebuild(&crate::bsp::BalancedSelector::default());
+
+        assert_eq!(tree.collect_polygons_sorted(), polygons);
+        assert_is_boundary(&tree);
+    }
+
+    #[test]
+    fn cube_has_twelve_unique_edges() {
+        let tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 2.0, 3.0]));
+
+        assert_eq!(tree.collect_edges().len(), 24);
+        let edges = tree.collect_unique_edges();
+        assert_eq!(edges.len(), 12);
+        for (a, b) in edges {
+            // Every cube edge runs along one axis
+            let along = (b - a).map(|c| (c.abs() > 1e-6) as u32).sum();
+            assert_eq!(along, 1);
+        }
+    }
+
+    #[test]
+    fn unique_edges_merge_the_edge_between_split_fragments() {
+        let square = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ]);
+        let (left, right) = square.cut(&Plane3D::new(Vector3::x(), 1.0));
+        let tree = BspTree::from_polygons(vec![left.unwrap(), right.unwrap()]);
+
+        // Two quads with four edges each, sharing the cut at x = 1
+        assert_eq!(tree.collect_edges().len(), 8);
+        assert_eq!(tree.collect_unique_edges().len(), 7);
+    }
+
+    #[test]
+    fn double_invert_is_identity() {
+        let tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 1.0, 3.0]));
+        let mut inverted = tree.clone();
+
+        inverted.invert();
+        assert_ne!(inverted.collect_polygons(), tree.collect_polygons());
+        assert!(!inverted.contains_point(Point3::new(1.0, 0.5, 1.5)));
+        assert!(inverted.contains_point(Point3::new(5.0, 0.5, 1.5)));
+
+        inverted.invert();
+        assert_eq!(inverted.collect_polygons(), tree.collect_polygons());
+        assert_eq!(
+            inverted.collect_polygons_ordered(),
+            tree.collect_polygons_ordered()
+        );
+        assert!(inverted.contains_point(Point3::new(1.0, 0.5, 1.5)));
+    }
+
+    #[test]
+    fn union_of_overlapping_cubes() {
+        let a = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0]));
+        let b = BspTree::from_polygons(cuboid([1.0, 1.0, 1.0], [3.0, 3.0, 3.0]));
+
+        let union = a.union(&b);
+
+        let volume = union.volume();
+        assert!((volume - 15.0).abs() < 1e-3, "volume was {volume}");
+        assert!(union.contains_point(Point3::new(0.5, 0.5, 0.5)));
+        assert!(union.contains_point(Point3::new(2.5, 2.5, 2.5)));
+        assert!(!union.contains_point(Point3::new(2.5, 0.5, 0.5)));
+        assert_is_boundary(&union);
+    }
+
+    #[test]
+    fn payloads_survive_csg() {
+        let a = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0])).map_data(|()| 'a');
+        let b = BspTree::from_polygons(cuboid([1.0, 1.0, 1.0], [3.0, 3.0, 3.0])).map_data(|()| 'b');
+
+        let union = a.union(&b);
+
+        // Faces of either cube are cut by the other, and every fragment
+        // keeps the payload of its cube
+        for polygon in union.collect_polygons() {
+            let vertices = polygon.vertices();
+            let from_a = vertices.iter().all(|v| v.iter().all(|&c| c <= 2.0));
+            let from_b = vertices.iter().all(|v| v.iter().all(|&c| c >= 1.0));
+            match polygon.data() {
+                'a' => assert!(from_a),
+                'b' => assert!(from_b),
+                other => panic!("unexpected payload {other}"),
+            }
+        }
+        let (_, hit) = union
+            .raycast(Point3::new(2.5, 2.5, 5.0), -Vector3::z())
+            .unwrap();
+        assert_eq!(*hit.data(), 'b');
+        let (_, hit) = union
+            .raycast(Point3::new(0.5, 0.5, 5.0), -Vector3::z())
+            .unwrap();
+        assert_eq!(*hit.data(), 'a');
+    }
+
+    #[test]
+    fn map_data_keeps_the_partition() {
+        let tree = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0]));
+        let mapped = tree.clone().map_data(|()| 1_u8);
+
+        let ids: Vec<NodeId> = tree.iter_nodes_dfs().map(|(node, _)| node.id()).collect();
+        let mapped_ids: Vec<NodeId> = mapped.iter_nodes_dfs().map(|(node, _)| node.id()).collect();
+        assert_eq!(ids, mapped_ids);
+        let polygons = mapped.collect_polygons();
+        assert!(polygons.iter().all(|p| *p.data() == 1));
+        let unmapped: Vec<Polygon> = polygons.into_iter().map(|p| p.with_data(())).collect();
+        assert_eq!(unmapped, tree.collect_polygons());
+        assert_eq!(tree.aabb(), mapped.aabb());
+    }
+
+    #[test]
+    fn intersection_of_overlapping_cubes() {
+        let a = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0]));
+        let b = BspTree::from_polygons(cuboid([1.0, 1.0, 1.0], [3.0, 3.0, 3.0]));
+
+        let intersection = a.intersection(&b);
+
+        let volume = intersection.volume();
+        assert!((volume - 1.0).abs() < 1e-3, "volume was {volume}");
+        assert!(intersection.contains_point(Point3::new(1.5, 1.5, 1.5)));
+        assert!(!intersection.contains_point(Point3::new(0.5, 0.5, 0.5)));
+        assert!(!intersection.contains_point(Point3::new(2.5, 2.5, 2.5)));
+        assert_is_boundary(&intersection);
+        assert!(a.intersection(&BspTree::new()).is_empty());
+    }
+
+    #[test]
+    fn difference_of_overlapping_cubes() {
+        let a = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0]));
+        let b = BspTree::from_polygons(cuboid([1.0, 1.0, 1.0], [3.0, 3.0, 3.0]));
+
+        let difference = a.difference(&b);
+
+        let volume = difference.volume();
+        assert!((volume - 7.0).abs() < 1e-3, "volume was {volume}");
+        assert!(difference.contains_point(Point3::new(0.5, 0.5, 0.5)));
+        assert!(!difference.contains_point(Point3::new(1.5, 1.5, 1.5)));
+        assert!(!difference.contains_point(Point3::new(2.5, 2.5, 2.5)));
+        assert_is_boundary(&difference);
+        assert_eq!(a.difference(&BspTree::new()).polygon_count(), 6);
+        assert!(BspTree::new().difference(&a).is_empty());
+    }
+
+    #[test]
+    fn collect_polygons_sorted_ignores_tree_shape() {
+        let polygons: Vec<Polygon> = [2.0, 0.0, 3.0, 1.0]
+            .iter()
+            .map(|&z| make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z]))
+            .collect();
+        let mut reversed = polygons.clone();
+        reversed.reverse();
+
+        let tree = BspTree::from_polygons(polygons);
+        let other = BspTree::build(reversed, &crate::bsp::BalancedSelector::default());
+        assert_ne!(tree.collect_polygons(), other.collect_polygons());
+
+        let sorted = tree.collect_polygons_sorted();
+        assert_eq!(sorted, other.collect_polygons_sorted());
+        let heights: Vec<f32> = sorted.iter().map(|p| p.vertices()[0].z).collect();
+        assert_eq!(heights, [0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn merge_coplanar_after_difference_keeps_the_solid() {
+        let a = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [2.0, 2.0, 2.0]));
+        let b = BspTree::from_polygons(cuboid([1.0, 1.0, 1.0], [3.0, 3.0, 3.0]));
+        let difference = a.difference(&b);
+
+        let mut merged = difference.clone();
+        merged.merge_coplanar();
+
+        assert!(merged.polygon_count() < difference.polygon_count());
+        assert_eq!(merged.to_plane_tree(), difference.to_plane_tree());
+        assert_eq!(merged.aabb(), difference.aabb());
+        assert!((merged.volume() - 7.0).abs() < 1e-3);
+        assert_is_boundary(&merged);
+    }
+
+    #[test]
+    fn difference_with_flush_doorway_leaves_no_interior_faces() {
+        // The doorway's bottom face lies on the wall's bottom face
+        let wall = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [4.0, 1.0, 3.0]));
+        let doorway = BspTree::from_polygons(cuboid([1.0, -1.0, 0.0], [2.0, 2.0, 2.0]));
+
+        let result = wall.difference(&doorway);
+
+        let volume = result.volume();
+        assert!((volume - 10.0).abs() < 1e-3, "volume was {volume}");
+        assert!(!result.contains_point(Point3::new(1.5, 0.5, 1.0)));
+        assert!(result.contains_point(Point3::new(1.5, 0.5, 2.5)));
+        assert!(result.contains_point(Point3::new(0.5, 0.5, 1.0)));
+        assert_is_boundary(&result);
+    }
+
+    #[test]
+    fn union_with_shared_face_keeps_no_interior_faces() {
+        let a = BspTree::from_polygons(cuboid([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]));
+        let b = BspTree::from_polygons(cuboid([1.0, 0.0, 0.0], [2.0, 1.0, 1.0]));
+
+        let union = a.union(&b);
+
+        assert!((union.volume() - 2.0).abs() < 1e-3);
+        assert_is_boundary(&union);
+    }
+
+    #[test]
+    fn from_sdf_sphere_approximates_volume() {
+        let bounds = Aabb::new(Point3::new(-1.5, -1.5, -1.5), Point3::new(1.5, 1.5, 1.5));
+        let tree = BspTree::from_sdf(|p| p.coords.norm() - 1.0, bounds, 12);
+
+        let expected = 4.0 / 3.0 * std::f32::consts::PI;
+        let volume = tree.volume();
+        assert!(
+            (volume - expected).abs() < 0.05 * expected,
+            "volume was {volume}"
+        );
+
+        assert!(tree.contains_point(Point3::origin()));
+        assert!(tree.contains_point(Point3::new(0.5, -0.4, 0.3)));
+        assert!(!tree.contains_point(Point3::new(1.2, 0.0, 0.0)));
+        assert!(!tree.contains_point(Point3::new(-0.8, 0.8, 0.8)));
+    }
+
+    #[test]
+    fn volume_of_empty_tree_is_zero() {
+        assert_eq!(BspTree::new().volume(), 0.0);
+
+        let bounds = Aabb::new(Point3::origin(), Point3::new(1.0, 1.0, 1.0));
+        assert!(BspTree::from_sdf(|_| 1.0, bounds, 2).is_empty());
+    }
+
+    #[test]
+    fn polygons_by_facing_buckets_cube_faces() {
+        // The -Z, +Z, -Y, +Y, -X, +X faces
+        let polygons = cuboid([0.0; 3], [1.0; 3]);
+
+        let tree = BspTree::from_polygons(polygons.clone());
+        let buckets = tree.polygons_by_facing();
+
+        // Buckets run +X, -X, +Y, -Y, +Z, -Z
+        for (bucket, expected) in buckets.iter().zip(polygons.iter().rev()) {
+            assert_eq!(bucket.len(), 1);
+            assert_eq!(bucket[0], expected);
+        }
+    }
+
+    #[test]
+    fn max_coplanar_group_finds_large_floor() {
+        let mut polygons = Vec::new();
+        // A few parallel polygons above the floor
+        for z in [1.0, 2.0, 3.0] {
+            polygons.push(make_triangle([0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z]));
+        }
+        // A floor of 50 triangles in the plane z = 0
+        for i in 0..50 {
+            let x = i as f32;
+            polygons.push(make_triangle(
+                [x, 0.0, 0.0],
+                [x + 1.0, 0.0, 0.0],
+                [x, 1.0, 0.0],
+            ));
+        }
+
+        let tree = BspTree::from_polygons(polygons);
+
+        assert_eq!(tree.polygon_count(), 53);
+        assert_eq!(tree.max_coplanar_group(), 50);
+        assert_eq!(BspTree::new().max_coplanar_group(), 0);
+    }
+
+    #[test]
+    fn mirrored_cube_is_symmetric_and_outward_facing() {
+        let tree = BspTree::from_polygons(cube());
+        // The plane x = -1
+        let mirror = Plane3D::new(Vector3::new(1.0, 0.0, 0.0), -1.0);
+
+        let mirrored = tree.mirrored_across(&mirror);
+
+        assert_eq!(mirrored.polygon_count(), 6);
+        assert_eq!(mirrored.depth(), tree.depth());
+
+        // Every face has its reflection in the mirrored tree
+        let sorted = |mut vertices: Vec<Point3<f32>>| {
+            vertices.sort_by(|a, b| a.iter().partial_cmp(b.iter()).unwrap());
+            vertices
+        };
+        let mirrored_polygons = mirrored.collect_polygons();
+        for polygon in tree.collect_polygons() {
+            let reflected = sorted(
+                polygon
+                    .vertices()
+                    .iter()
+                    .map(|v| mirror.reflect_point(*v))
+                    .collect(),
+            );
+            assert!(
+                mirrored_polygons
+                    .iter()
+                    .any(|m| sorted(m.vertices().to_vec()) == reflected)
+            );
+        }
+
+        // The mirrored cube spans [-4, -2] on x, with outward normals
+        let center = Point3::new(-3.0, 1.0, 1.0);
+        for polygon in &mirrored_polygons {
+            let outward = polygon.centroid() - center;
+            assert!(polygon.unit_normal().unwrap().dot(&outward) > 0.0);
+        }
+        assert!(mirrored.contains_point(center));
+        assert!(!mirrored.contains_point(Point3::new(1.0, 1.0, 1.0)));
+    }
+
     #[test]
     fn collect_polygons() {
         let poly1 = make_triangle([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
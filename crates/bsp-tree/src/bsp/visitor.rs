@@ -3,7 +3,7 @@
 //! Visitors allow custom processing of polygons during tree traversal
 //! without coupling traversal logic to specific use cases.
 
-use crate::Polygon;
+use crate::{Plane3D, Polygon};
 
 /// Visitor for processing polygons during BSP tree traversal.
 ///
@@ -12,66 +12,101 @@ use crate::Polygon;
 /// - Rendering (painter's algorithm)
 /// - Collecting polygons in sorted order
 /// - Computing visibility
-pub trait BspVisitor {
+///
+/// `T` is the [payload](Polygon::data) type of the visited polygons.
+pub trait BspVisitor<T = ()> {
     /// Called for each group of coplanar polygons during traversal.
     ///
-    /// The polygons passed to this method are all coplanar with each other
-    /// and belong to the same BSP node.
-    fn visit(&mut self, polygons: &[Polygon]);
+    /// The polygons passed to this method belong to the same BSP node and
+    /// are all coplanar with each other, unless the node also holds
+    /// [leftover](super::BspNode::leftover) polygons.
+    fn visit(&mut self, polygons: &[Polygon<T>]);
+}
+
+/// Visitor that also receives the node each group of polygons belongs to.
+///
+/// Where [`BspVisitor`] only sees polygons, this trait is told which
+/// splitting plane produced them and how deep in the tree it sits, e.g. to
+/// color a debug overlay by depth.
+pub trait BspNodeVisitor<T = ()> {
+    /// Called once for each node during traversal.
+    ///
+    /// `coplanar` holds the node's polygons (facing either way) and may be
+    /// empty. `depth` counts nodes as [`BspTree::depth`](super::BspTree::depth)
+    /// does, so the root is at depth 1.
+    fn visit_node(&mut self, plane: &Plane3D, coplanar: &[Polygon<T>], depth: usize);
+}
+
+/// Drives a [`BspVisitor`] from a node traversal, skipping nodes without
+/// polygons.
+pub(crate) struct PolygonVisitor<'v, V: ?Sized>(pub(crate) &'v mut V);
+
+impl<T, V: BspVisitor<T> + ?Sized> BspNodeVisitor<T> for PolygonVisitor<'_, V> {
+    fn visit_node(&mut self, _plane: &Plane3D, coplanar: &[Polygon<T>], _depth: usize) {
+        if !coplanar.is_empty() {
+            self.0.visit(coplanar);
+        }
+    }
 }
 
 /// A simple visitor that collects all visited polygons.
-#[derive(Debug, Default)]
-pub struct CollectingVisitor {
-    collected: Vec<Polygon>,
+#[derive(Debug)]
+pub struct CollectingVisitor<T = ()> {
+    collected: Vec<Polygon<T>>,
+}
+
+impl<T> Default for CollectingVisitor<T> {
+    fn default() -> Self {
+        Self {
+            collected: Vec::new(),
+        }
+    }
 }
 
 impl CollectingVisitor {
     /// Creates a new empty collecting visitor.
+    ///
+    /// For polygons carrying a payload, use [`CollectingVisitor::default`].
     pub fn new() -> Self {
         Self::default()
     }
+}
 
+impl<T> CollectingVisitor<T> {
     /// Returns the collected polygons.
-    pub fn into_polygons(self) -> Vec<Polygon> {
+    pub fn into_polygons(self) -> Vec<Polygon<T>> {
         self.collected
     }
 
     /// Returns a reference to the collected polygons.
-    pub fn polygons(&self) -> &[Polygon] {
+    pub fn polygons(&self) -> &[Polygon<T>] {
         &self.collected
     }
 }
 
-impl BspVisitor for CollectingVisitor {
-    fn visit(&mut self, polygons: &[Polygon]) {
+impl<T: Clone> BspVisitor<T> for CollectingVisitor<T> {
+    fn visit(&mut self, polygons: &[Polygon<T>]) {
         self.collected.extend(polygons.iter().cloned());
     }
 }
 
 /// A visitor that calls a closure for each polygon group.
-pub struct FnVisitor<F>
-where
-    F: FnMut(&[Polygon]),
-{
+pub struct FnVisitor<F> {
     func: F,
 }
 
-impl<F> FnVisitor<F>
-where
-    F: FnMut(&[Polygon]),
-{
+impl<F> FnVisitor<F> {
     /// Creates a new visitor from a closure.
     pub fn new(func: F) -> Self {
         Self { func }
     }
 }
 
-impl<F> BspVisitor for FnVisitor<F>
+impl<T, F> BspVisitor<T> for FnVisitor<F>
 where
-    F: FnMut(&[Polygon]),
+    F: FnMut(&[Polygon<T>]),
 {
-    fn visit(&mut self, polygons: &[Polygon]) {
+    fn visit(&mut self, polygons: &[Polygon<T>]) {
         (self.func)(polygons);
     }
 }
@@ -1,9 +1,13 @@
 //! Polygon cutting/splitting operations for BSP trees.
 
-use crate::{Classification, Plane3D, PlaneSide, Polygon, Rectangle, Triangle};
+use nalgebra::{Point3, Vector2};
 
-/// Trait for geometry that can be cut by a plane.
-pub trait Cuttable {
+use crate::bsp::{faces_same_direction_with_epsilon, FACING_EPSILON};
+use crate::{Classification, Plane3D, PlaneSide, Polygon, Rectangle, Triangle, PLANE_EPSILON};
+
+/// Trait for geometry that can be cut by a plane, into polygons carrying a
+/// payload of type `T`.
+pub trait Cuttable<T = ()> {
     /// Cuts the geometry by a plane.
     ///
     /// Returns `(front, back)` where:
@@ -16,11 +20,12 @@ pub trait Cuttable {
     /// - **Back**: `(None, Some(self))` - entire geometry is behind
     /// - **Coplanar**: `(Some(self), None)` - treated as front
     /// - **Spanning**: `(Some(front_part), Some(back_part))` - split into two pieces
-    fn cut(&self, plane: &Plane3D) -> (Option<Polygon>, Option<Polygon>);
+    fn cut(&self, plane: &Plane3D) -> (Option<Polygon<T>>, Option<Polygon<T>>);
 }
 
-impl Cuttable for Polygon {
-    fn cut(&self, plane: &Plane3D) -> (Option<Polygon>, Option<Polygon>) {
+/// Both fragments of a split polygon get a clone of its payload.
+impl<T: Clone> Cuttable<T> for Polygon<T> {
+    fn cut(&self, plane: &Plane3D) -> (Option<Polygon<T>>, Option<Polygon<T>>) {
         match self.classify(plane) {
             Classification::Front | Classification::Coplanar => {
                 (Some(self.clone()), None)
@@ -29,28 +34,158 @@ impl Cuttable for Polygon {
                 (None, Some(self.clone()))
             }
             Classification::Spanning => {
-                split_polygon(self, plane)
+                split_polygon(self, plane, PLANE_EPSILON)
             }
         }
     }
 }
 
+/// Where a polygon lies relative to a plane, with the fragments if the
+/// plane cuts it.
+///
+/// Returned by [`Polygon::split`]. Unlike [`Cuttable::cut`], which puts
+/// coplanar polygons in front, this keeps the coplanar case and its facing
+/// apart, as BSP construction and CSG need them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SplitResult<T = ()> {
+    /// Entirely in front of the plane.
+    Front,
+    /// Entirely behind the plane.
+    Back,
+    /// On the plane.
+    Coplanar {
+        /// Whether the polygon faces the same direction as the plane normal,
+        /// see [`faces_same_direction`](crate::bsp::faces_same_direction).
+        same_facing: bool,
+    },
+    /// Crossing the plane. A fragment is `None` if it was degenerate once
+    /// [cleaned](Polygon::cleaned).
+    Spanning {
+        front: Option<Polygon<T>>,
+        back: Option<Polygon<T>>,
+    },
+}
+
+impl<T: Clone> Polygon<T> {
+    /// Classifies the polygon against a plane and cuts it if it spans the
+    /// plane, in a single call.
+    ///
+    /// Uses [`PLANE_EPSILON`] and [`FACING_EPSILON`], see
+    /// [`split_with_epsilon`](Self::split_with_epsilon).
+    pub fn split(&self, plane: &Plane3D) -> SplitResult<T> {
+        self.split_with_epsilon(plane, PLANE_EPSILON, FACING_EPSILON)
+    }
+
+    /// Like [`split`](Self::split), treating vertices within `plane_epsilon`
+    /// of the plane as on it, and sorting coplanar polygons by facing with
+    /// the tie band `facing_epsilon` as
+    /// [`faces_same_direction_with_epsilon`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polygon is coplanar and has a degenerate normal.
+    pub fn split_with_epsilon(
+        &self,
+        plane: &Plane3D,
+        plane_epsilon: f32,
+        facing_epsilon: f32,
+    ) -> SplitResult<T> {
+        match self.classify_with_epsilon(plane, plane_epsilon) {
+            Classification::Front => SplitResult::Front,
+            Classification::Back => SplitResult::Back,
+            Classification::Coplanar => SplitResult::Coplanar {
+                same_facing: faces_same_direction_with_epsilon(self, plane, facing_epsilon),
+            },
+            Classification::Spanning => {
+                let (front, back) = split_polygon(self, plane, plane_epsilon);
+                SplitResult::Spanning { front, back }
+            }
+        }
+    }
+
+    /// Clips the polygon to both sides of a plane, returning `(front, back)`,
+    /// treating vertices within `epsilon` of the plane as on it.
+    ///
+    /// Unlike [`Cuttable::cut`], a coplanar polygon goes to the side its
+    /// normal faces: front if it faces the same direction as the plane,
+    /// back otherwise. Clipping to the region in front of several planes,
+    /// such as a view frustum with inward-facing planes, chains the front
+    /// sides:
+    ///
+    /// ```
+    /// use bsp_tree::{Plane3D, Polygon};
+    /// use nalgebra::{Point3, Vector3};
+    ///
+    /// let square = Polygon::new(vec![
+    ///     Point3::new(-1.0, -1.0, 0.0),
+    ///     Point3::new(1.0, -1.0, 0.0),
+    ///     Point3::new(1.0, 1.0, 0.0),
+    ///     Point3::new(-1.0, 1.0, 0.0),
+    /// ]);
+    /// // Keep the part with x > 0 and y > 0
+    /// let planes = [
+    ///     Plane3D::from_point_and_normal(Point3::origin(), Vector3::x()),
+    ///     Plane3D::from_point_and_normal(Point3::origin(), Vector3::y()),
+    /// ];
+    ///
+    /// let quarter = planes
+    ///     .iter()
+    ///     .try_fold(square, |kept, plane| kept.clip(plane, 1e-3).0)
+    ///     .unwrap();
+    /// assert!((quarter.area() - 1.0).abs() < 1e-6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polygon is coplanar and has a degenerate normal.
+    pub fn clip(&self, plane: &Plane3D, epsilon: f32) -> (Option<Polygon<T>>, Option<Polygon<T>>) {
+        match self.split_with_epsilon(plane, epsilon, FACING_EPSILON) {
+            SplitResult::Front | SplitResult::Coplanar { same_facing: true } => {
+                (Some(self.clone()), None)
+            }
+            SplitResult::Back | SplitResult::Coplanar { same_facing: false } => {
+                (None, Some(self.clone()))
+            }
+            SplitResult::Spanning { front, back } => (front, back),
+        }
+    }
+}
+
 /// Splits a spanning polygon into front and back parts.
 ///
 /// Uses a variant of the Sutherland-Hodgman algorithm:
 /// walks the polygon edges and builds two vertex lists,
-/// adding intersection points when edges cross the plane.
-fn split_polygon(polygon: &Polygon, plane: &Plane3D) -> (Option<Polygon>, Option<Polygon>) {
+/// adding intersection points when edges cross the plane. Both fragments get
+/// a clone of the polygon's payload.
+///
+/// Vertices within `epsilon` of the plane are on it. Fragment vertices are
+/// merged within the smaller of `epsilon` and [`MERGE_EPSILON`], so a wide
+/// plane band does not also weld distinct vertices together.
+fn split_polygon<T: Clone>(
+    polygon: &Polygon<T>,
+    plane: &Plane3D,
+    epsilon: f32,
+) -> (Option<Polygon<T>>, Option<Polygon<T>>) {
+    // A zero-area polygon has no plane for its fragments to inherit, and
+    // would only leave slivers on either side
+    let Some(parent_plane) = polygon.try_plane() else {
+        return (None, None);
+    };
     let vertices = polygon.vertices();
     let n = vertices.len();
+    // Texture coordinates are tracked alongside the vertices either way and
+    // only attached to the fragments if the polygon has them
+    let uvs = polygon.uvs();
+    let uv = |i: usize| uvs.map_or(Vector2::zeros(), |uvs| uvs[i]);
+    let merge_epsilon = epsilon.min(MERGE_EPSILON);
 
-    let mut front_verts = Vec::with_capacity(n + 1);
-    let mut back_verts = Vec::with_capacity(n + 1);
+    let mut front = Fragment::with_capacity(n + 1);
+    let mut back = Fragment::with_capacity(n + 1);
 
     // Classify all vertices upfront
     let sides: Vec<PlaneSide> = vertices
         .iter()
-        .map(|v| plane.classify_point(*v))
+        .map(|v| plane.classify_point_with_epsilon(*v, epsilon))
         .collect();
 
     for i in 0..n {
@@ -62,12 +197,12 @@ fn split_polygon(polygon: &Polygon, plane: &Plane3D) -> (Option<Polygon>, Option
 
         // Add current vertex to appropriate list(s)
         match current_side {
-            PlaneSide::Front => front_verts.push(current),
-            PlaneSide::Back => back_verts.push(current),
+            PlaneSide::Front => front.push(current, uv(i)),
+            PlaneSide::Back => back.push(current, uv(i)),
             PlaneSide::OnPlane => {
                 // On-plane vertices go to both sides
-                front_verts.push(current);
-                back_verts.push(current);
+                front.push(current, uv(i));
+                back.push(current, uv(i));
             }
         }
 
@@ -78,28 +213,97 @@ fn split_polygon(polygon: &Polygon, plane: &Plane3D) -> (Option<Polygon>, Option
         );
 
         if crosses {
-            // Compute intersection point
-            if let Some((_, intersection)) = plane.intersect_segment(current, next) {
-                front_verts.push(intersection);
-                back_verts.push(intersection);
+            // Compute intersection point, interpolating the texture
+            // coordinates at the same parameter
+            if let Some((t, intersection)) = plane.intersect_segment(current, next) {
+                let intersection_uv = uv(i).lerp(&uv(next_idx), t);
+                front.push_intersection(intersection, intersection_uv, merge_epsilon);
+                back.push_intersection(intersection, intersection_uv, merge_epsilon);
             }
         }
     }
 
-    // Build result polygons (only if they have enough vertices)
-    let front = if front_verts.len() >= 3 {
-        Some(Polygon::new(front_verts))
-    } else {
-        None
-    };
+    // Build result polygons (only if they have enough vertices).
+    // Fragments inherit the parent's plane rather than recomputing it.
+    let has_uvs = uvs.is_some();
+    let data = polygon.data();
+    (
+        front.into_polygon(parent_plane.clone(), has_uvs, data.clone(), merge_epsilon),
+        back.into_polygon(parent_plane, has_uvs, data.clone(), merge_epsilon),
+    )
+}
 
-    let back = if back_verts.len() >= 3 {
-        Some(Polygon::new(back_verts))
-    } else {
-        None
-    };
+/// The vertices of one side of a split, with their texture coordinates.
+struct Fragment {
+    vertices: Vec<Point3<f32>>,
+    uvs: Vec<Vector2<f32>>,
+}
+
+impl Fragment {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            vertices: Vec::with_capacity(capacity),
+            uvs: Vec::with_capacity(capacity),
+        }
+    }
 
-    (front, back)
+    fn push(&mut self, vertex: Point3<f32>, uv: Vector2<f32>) {
+        self.vertices.push(vertex);
+        self.uvs.push(uv);
+    }
+
+    /// Appends an intersection point unless it coincides with the vertex
+    /// just added, which happens when an on-plane vertex sits next to a
+    /// crossing edge.
+    fn push_intersection(
+        &mut self,
+        intersection: Point3<f32>,
+        uv: Vector2<f32>,
+        merge_epsilon: f32,
+    ) {
+        if self
+            .vertices
+            .last()
+            .is_none_or(|&last| !coincides(last, intersection, merge_epsilon))
+        {
+            self.push(intersection, uv);
+        }
+    }
+
+    /// Builds the fragment's polygon, or `None` if it is degenerate.
+    fn into_polygon<T: Clone>(
+        mut self,
+        plane: Plane3D,
+        has_uvs: bool,
+        data: T,
+        merge_epsilon: f32,
+    ) -> Option<Polygon<T>> {
+        // The last intersection may also land on the first vertex
+        if let [first, .., last] = self.vertices[..]
+            && coincides(first, last, merge_epsilon)
+        {
+            self.vertices.pop();
+            self.uvs.pop();
+        }
+        if self.vertices.len() < 3 {
+            return None;
+        }
+
+        let mut polygon = Polygon::with_plane(self.vertices, plane).with_data(data);
+        if has_uvs {
+            polygon = polygon.with_uvs(self.uvs);
+        }
+        // A grazing cut can leave collinear or duplicate vertices, or a sliver
+        polygon.cleaned_with_epsilon(merge_epsilon)
+    }
+}
+
+/// Largest distance within which the vertices of a cut's fragments are
+/// merged, whatever the plane epsilon of the cut.
+const MERGE_EPSILON: f32 = 1e-4;
+
+fn coincides(a: Point3<f32>, b: Point3<f32>, merge_epsilon: f32) -> bool {
+    (a - b).norm() <= merge_epsilon
 }
 
 impl Cuttable for Triangle {
@@ -114,10 +318,126 @@ impl Cuttable for Rectangle {
     }
 }
 
+/// Returns the seam segments that cutting `polygons` by `plane` would create.
+///
+/// Each spanning polygon contributes the edge shared by its front and back
+/// fragments, which lies on the plane. Polygons entirely on one side of the
+/// plane, or coplanar with it, contribute nothing.
+pub fn compute_cut_faces<T: Clone>(
+    polygons: &[Polygon<T>],
+    plane: &Plane3D,
+) -> Vec<(Point3<f32>, Point3<f32>)> {
+    compute_cut_faces_with_epsilon(polygons, plane, PLANE_EPSILON)
+}
+
+/// Like [`compute_cut_faces`], with `epsilon` as the distance within which a
+/// point counts as on the plane.
+pub fn compute_cut_faces_with_epsilon<T: Clone>(
+    polygons: &[Polygon<T>],
+    plane: &Plane3D,
+    epsilon: f32,
+) -> Vec<(Point3<f32>, Point3<f32>)> {
+    polygons
+        .iter()
+        .filter(|polygon| polygon.classify_with_epsilon(plane, epsilon) == Classification::Spanning)
+        .filter_map(|polygon| {
+            let (front, back) = split_polygon(polygon, plane, epsilon);
+            seam(&front.or(back)?, plane, epsilon)
+        })
+        .collect()
+}
+
+/// Returns the edge of a cut fragment that lies on the cutting `plane`, or
+/// `None` unless exactly two of its vertices are within `epsilon` of it.
+pub(crate) fn seam<T>(
+    fragment: &Polygon<T>,
+    plane: &Plane3D,
+    epsilon: f32,
+) -> Option<(Point3<f32>, Point3<f32>)> {
+    let mut on_plane = fragment
+        .vertices()
+        .iter()
+        .filter(|&&v| plane.signed_distance(v).abs() <= epsilon);
+    match (on_plane.next(), on_plane.next(), on_plane.next()) {
+        (Some(&start), Some(&end), None) => Some((start, end)),
+        _ => None,
+    }
+}
+
+/// A piece of geometry produced by a type-preserving cut.
+///
+/// Returned by [`Triangle::cut_preserving`] and
+/// [`Rectangle::cut_preserving`]: geometry that lies entirely on one side of
+/// the plane keeps its original type, and only the fragments of a genuine
+/// split become [`Polygon`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CutPiece {
+    /// An unsplit triangle.
+    Triangle(Triangle),
+    /// An unsplit rectangle.
+    Rectangle(Rectangle),
+    /// A fragment of a split.
+    Polygon(Polygon),
+}
+
+impl From<CutPiece> for Polygon {
+    fn from(piece: CutPiece) -> Self {
+        match piece {
+            CutPiece::Triangle(triangle) => triangle.into(),
+            CutPiece::Rectangle(rectangle) => rectangle.into(),
+            CutPiece::Polygon(polygon) => polygon,
+        }
+    }
+}
+
+/// Cuts geometry as [`Cuttable::cut`] does, but returns `whole` unchanged on
+/// the side it lies on when no split is needed.
+fn cut_preserving<T: Cuttable>(
+    geometry: &T,
+    classification: Classification,
+    plane: &Plane3D,
+    whole: impl FnOnce() -> CutPiece,
+) -> (Option<CutPiece>, Option<CutPiece>) {
+    match classification {
+        Classification::Front | Classification::Coplanar => (Some(whole()), None),
+        Classification::Back => (None, Some(whole())),
+        Classification::Spanning => {
+            let (front, back) = geometry.cut(plane);
+            (front.map(CutPiece::Polygon), back.map(CutPiece::Polygon))
+        }
+    }
+}
+
+impl Triangle {
+    /// Cuts the triangle by a plane, keeping it a [`Triangle`] when it lies
+    /// entirely on one side.
+    ///
+    /// Returns `(front, back)` with the same sides as [`Cuttable::cut`]; only
+    /// a spanning triangle yields [`CutPiece::Polygon`] fragments.
+    pub fn cut_preserving(&self, plane: &Plane3D) -> (Option<CutPiece>, Option<CutPiece>) {
+        cut_preserving(self, self.classify(plane), plane, || {
+            CutPiece::Triangle(self.clone())
+        })
+    }
+}
+
+impl Rectangle {
+    /// Cuts the rectangle by a plane, keeping it a [`Rectangle`] when it lies
+    /// entirely on one side.
+    ///
+    /// Returns `(front, back)` with the same sides as [`Cuttable::cut`]; only
+    /// a spanning rectangle yields [`CutPiece::Polygon`] fragments.
+    pub fn cut_preserving(&self, plane: &Plane3D) -> (Option<CutPiece>, Option<CutPiece>) {
+        cut_preserving(self, self.classify(plane), plane, || {
+            CutPiece::Rectangle(self.clone())
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use nalgebra::{Point3, Vector3};
+    use nalgebra::{Point3, Vector2, Vector3};
 
     // =========================================================================
     // Helper functions
@@ -662,6 +982,36 @@ mod tests {
         assert_vertex_count(&back.unwrap(), 3);
     }
 
+    #[test]
+    fn triangle_cut_preserving_keeps_type_when_unsplit() {
+        let triangle = Triangle::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 2.0, 0.0),
+            Point3::new(0.5, 1.5, 1.0),
+        );
+        let plane = horizontal_plane(0.0);
+
+        let (front, back) = triangle.cut_preserving(&plane);
+
+        assert_eq!(front, Some(CutPiece::Triangle(triangle)));
+        assert!(back.is_none());
+    }
+
+    #[test]
+    fn triangle_cut_preserving_spanning_yields_polygons() {
+        let triangle = Triangle::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+        );
+        let plane = horizontal_plane(0.0);
+
+        let (front, back) = triangle.cut_preserving(&plane);
+
+        assert!(matches!(front, Some(CutPiece::Polygon(ref p)) if p.len() == 3));
+        assert!(matches!(back, Some(CutPiece::Polygon(ref p)) if p.len() == 4));
+    }
+
     // =========================================================================
     // Rectangle: Cuttable implementation tests
     // =========================================================================
@@ -698,6 +1048,21 @@ mod tests {
         assert_vertex_count(&back.unwrap(), 4);
     }
 
+    #[test]
+    fn rectangle_cut_preserving_keeps_type_when_unsplit() {
+        let rect = Rectangle::new(
+            Point3::new(0.0, -2.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.5, 0.0),
+        );
+        let plane = horizontal_plane(0.0);
+
+        let (front, back) = rect.cut_preserving(&plane);
+
+        assert!(front.is_none());
+        assert_eq!(back, Some(CutPiece::Rectangle(rect)));
+    }
+
     #[test]
     fn rectangle_coplanar() {
         let rect = Rectangle::new(
@@ -1091,4 +1456,295 @@ mod tests {
         assert_eq!(front1.as_ref().map(|p| p.len()), front2.as_ref().map(|p| p.len()));
         assert_eq!(back1.as_ref().map(|p| p.len()), back2.as_ref().map(|p| p.len()));
     }
+
+    #[test]
+    fn fragment_with_collinear_start_is_cleaned_and_inherits_plane() {
+        // Rectangle with an extra vertex on its left edge. Cutting at y = 1
+        // would yield a back fragment starting (0,1), (0,0), (0,-1), whose
+        // collinear middle vertex is dropped.
+        let polygon = Polygon::new(vec![
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(2.0, -1.0, 0.0),
+        ]);
+        let plane = horizontal_plane(1.0);
+
+        let (_, back) = polygon.cut(&plane);
+        let back = back.unwrap();
+
+        assert_eq!(back.len(), 4);
+        assert!(!back.vertices().contains(&Point3::new(0.0, 0.0, 0.0)));
+
+        let expected = polygon.plane();
+        let inherited = back.plane();
+        assert!((inherited.normal() - expected.normal()).norm() < 1e-6);
+        assert!((inherited.offset() - expected.offset()).abs() < 1e-6);
+        assert!(back.unit_normal().is_some());
+    }
+
+    #[test]
+    fn on_plane_vertex_next_to_crossing_edge_has_no_duplicates() {
+        // Vertex (0, 0) lies on the plane y = 0. The next vertex is barely in
+        // front, so the edge after it crosses the plane right next to (0, 0).
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(-1e-5, 2e-4, 0.0),
+            Point3::new(-0.01, -1.0, 0.0),
+            Point3::new(0.01, -1.0, 0.0),
+        ]);
+        let plane = horizontal_plane(0.0);
+
+        let (front, back) = polygon.cut(&plane);
+
+        for part in [&front, &back].into_iter().flatten() {
+            let v = part.vertices();
+            for i in 0..v.len() {
+                let next = v[(i + 1) % v.len()];
+                assert!((next - v[i]).norm() > PLANE_EPSILON, "duplicate {next}");
+            }
+        }
+        // The sliver in front collapses; the back part keeps its corners
+        let back = back.unwrap();
+        assert_eq!(back.len(), 3);
+    }
+
+    #[test]
+    fn wide_plane_epsilon_does_not_merge_vertices() {
+        // The vertex at (5, -0.05) bends the bottom edge by less than the
+        // plane epsilon, but is still a distinct vertex
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(5.0, -0.05, 0.0),
+            Point3::new(10.0, 0.0, 0.0),
+            Point3::new(10.0, 10.0, 0.0),
+            Point3::new(0.0, 10.0, 0.0),
+        ]);
+        let plane = vertical_plane_x(2.0);
+
+        let (front, back) = polygon.clip(&plane, 0.1);
+
+        assert_vertex_count(&front.unwrap(), 5);
+        assert_vertex_count(&back.unwrap(), 4);
+    }
+
+    #[test]
+    fn zero_area_polygon_spanning_is_dropped() {
+        // All vertices on a line crossing y = 0, so there is no plane
+        let sliver = Polygon::new(vec![
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(0.0, 0.5, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let plane = horizontal_plane(0.0);
+
+        assert_eq!(sliver.cut(&plane), (None, None));
+    }
+
+    // =========================================================================
+    // Seam segments
+    // =========================================================================
+
+    #[test]
+    fn cut_faces_of_two_spanning_polygons() {
+        let spanning = [
+            Polygon::new(vec![
+                Point3::new(-1.0, -1.0, 0.0),
+                Point3::new(1.0, -1.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(-1.0, 1.0, 0.0),
+            ]),
+            Polygon::new(vec![
+                Point3::new(3.0, -1.0, 0.0),
+                Point3::new(3.0, 2.0, 1.0),
+                Point3::new(3.0, 2.0, -1.0),
+            ]),
+        ];
+        let above = Polygon::new(vec![
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(1.0, 2.0, 0.0),
+            Point3::new(0.0, 3.0, 0.0),
+        ]);
+        let plane = horizontal_plane(0.5);
+
+        let mut polygons = spanning.to_vec();
+        polygons.push(above);
+        let seams = compute_cut_faces(&polygons, &plane);
+
+        assert_eq!(seams.len(), 2);
+        for &(start, end) in &seams {
+            assert_point_on_plane(start, &plane);
+            assert_point_on_plane(end, &plane);
+            assert!((end - start).norm() > PLANE_EPSILON);
+        }
+        assert!(approx_eq((seams[0].1 - seams[0].0).norm(), 2.0, 1e-5));
+    }
+
+    #[test]
+    fn cut_faces_ignores_polygons_not_spanning() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        let polygons = [polygon];
+
+        assert!(compute_cut_faces(&polygons, &horizontal_plane(2.0)).is_empty());
+        assert!(compute_cut_faces(&polygons, &vertical_plane_z(0.0)).is_empty());
+    }
+
+    #[test]
+    fn split_distinguishes_coplanar_facing() {
+        let up = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 0.0),
+        ]);
+        let plane = horizontal_plane(0.0);
+
+        assert_eq!(
+            up.split(&plane),
+            SplitResult::Coplanar { same_facing: true }
+        );
+        assert_eq!(
+            up.flipped().split(&plane),
+            SplitResult::Coplanar { same_facing: false }
+        );
+        // `cut` puts both in front
+        assert!(up.flipped().cut(&plane).0.is_some());
+    }
+
+    #[test]
+    fn split_matches_cut_off_the_plane() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        assert_eq!(polygon.split(&horizontal_plane(-2.0)), SplitResult::Front);
+        assert_eq!(polygon.split(&horizontal_plane(2.0)), SplitResult::Back);
+
+        let plane = horizontal_plane(0.0);
+        let (front, back) = polygon.cut(&plane);
+        assert_eq!(polygon.split(&plane), SplitResult::Spanning { front, back });
+    }
+
+    #[test]
+    fn clip_sends_coplanar_polygons_by_facing() {
+        let up = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 0.0),
+        ]);
+        let plane = horizontal_plane(0.0);
+
+        assert_eq!(up.clip(&plane, PLANE_EPSILON), (Some(up.clone()), None));
+        assert_eq!(
+            up.flipped().clip(&plane, PLANE_EPSILON),
+            (None, Some(up.flipped()))
+        );
+    }
+
+    #[test]
+    fn clip_matches_cut_off_the_plane() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        for height in [-2.0, 0.0, 2.0] {
+            let plane = horizontal_plane(height);
+            assert_eq!(polygon.clip(&plane, PLANE_EPSILON), polygon.cut(&plane));
+        }
+    }
+
+    #[test]
+    fn chained_clips_keep_the_inside() {
+        // A wide quad through a unit box around the origin: the planes face
+        // inward, so the box interior is in front of all of them
+        let quad = Polygon::new(vec![
+            Point3::new(-3.0, -3.0, 0.0),
+            Point3::new(3.0, -3.0, 0.0),
+            Point3::new(3.0, 3.0, 0.0),
+            Point3::new(-3.0, 3.0, 0.0),
+        ]);
+        let planes: Vec<Plane3D> = [Vector3::x(), -Vector3::x(), Vector3::y(), -Vector3::y()]
+            .iter()
+            .map(|&n| Plane3D::from_point_and_normal(Point3::origin() - n, n))
+            .collect();
+
+        let clipped = planes
+            .iter()
+            .try_fold(quad.clone(), |kept, plane| {
+                kept.clip(plane, PLANE_EPSILON).0
+            })
+            .unwrap();
+        assert!(approx_eq(clipped.area(), 4.0, 1e-5));
+        for plane in &planes {
+            assert_all_vertices_on_side(&clipped, plane, PlaneSide::Front);
+        }
+
+        let (outside, _) = quad.clip(&vertical_plane_x(4.0), PLANE_EPSILON);
+        assert!(outside.is_none());
+    }
+
+    #[test]
+    fn split_interpolates_uvs() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(2.0, -1.0, 0.0),
+            Point3::new(2.0, 3.0, 0.0),
+            Point3::new(0.0, 3.0, 0.0),
+        ])
+        .with_uvs(vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(1.0, 1.0),
+            Vector2::new(0.0, 1.0),
+        ]);
+
+        let (front, back) = polygon.cut(&horizontal_plane(0.0));
+        for fragment in [front.unwrap(), back.unwrap()] {
+            let uvs = fragment.uvs().unwrap();
+            assert_eq!(uvs.len(), fragment.len());
+            // The texture maps x to u and y to v linearly
+            for (vertex, uv) in fragment.vertices().iter().zip(uvs) {
+                assert!(approx_eq(uv.x, vertex.x / 2.0, 1e-6));
+                assert!(approx_eq(uv.y, (vertex.y + 1.0) / 4.0, 1e-6));
+            }
+        }
+    }
+
+    #[test]
+    fn split_without_uvs_keeps_none() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ]);
+
+        let (front, back) = polygon.cut(&horizontal_plane(0.0));
+        assert!(front.unwrap().uvs().is_none());
+        assert!(back.unwrap().uvs().is_none());
+    }
+
+    #[test]
+    fn split_fragments_keep_payload() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ])
+        .with_data(7_u32);
+
+        let (front, back) = polygon.cut(&horizontal_plane(0.0));
+        assert_eq!(*front.unwrap().data(), 7);
+        assert_eq!(*back.unwrap().data(), 7);
+    }
 }
@@ -0,0 +1,208 @@
+//! View frustums for culling.
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{Aabb, Plane3D};
+
+/// A view frustum, stored as six planes whose normals point into the
+/// frustum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frustum {
+    planes: [Plane3D; 6],
+}
+
+impl Frustum {
+    /// Creates a frustum from six planes whose normals point inside.
+    pub fn new(planes: [Plane3D; 6]) -> Self {
+        Self { planes }
+    }
+
+    /// Creates the frustum of a perspective camera at `position` looking at
+    /// `target`.
+    ///
+    /// `fov` is the vertical field of view in radians and `aspect` the
+    /// width/height ratio of the view. `near` and `far` are the distances of
+    /// the clipping planes along the view direction.
+    ///
+    /// # Panics
+    /// Panics if `target` equals `position` or `up` is parallel to the view
+    /// direction.
+    pub fn from_camera(
+        position: Point3<f32>,
+        target: Point3<f32>,
+        up: Vector3<f32>,
+        fov: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        let (forward, right, up) = view_basis(position, target, up);
+
+        let tan_vertical = (fov / 2.0).tan();
+        let tan_horizontal = tan_vertical * aspect;
+
+        // Each side plane contains the camera position and one edge
+        // direction of the view pyramid
+        let side = |normal: Vector3<f32>| Plane3D::from_point_and_normal(position, normal);
+        Self::new([
+            Plane3D::from_point_and_normal(position + forward * near, forward),
+            Plane3D::from_point_and_normal(position + forward * far, -forward),
+            side(right + forward * tan_horizontal),
+            side(forward * tan_horizontal - right),
+            side(up + forward * tan_vertical),
+            side(forward * tan_vertical - up),
+        ])
+    }
+
+    /// Creates the frustum of an orthographic camera at `position` looking
+    /// at `target`.
+    ///
+    /// The view is a box `height` world units tall and `height * aspect`
+    /// wide, centered on the view axis. `near` and `far` are the distances
+    /// of the clipping planes along the view direction.
+    ///
+    /// # Panics
+    /// Panics if `target` equals `position` or `up` is parallel to the view
+    /// direction.
+    pub fn from_orthographic(
+        position: Point3<f32>,
+        target: Point3<f32>,
+        up: Vector3<f32>,
+        height: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        let (forward, right, up) = view_basis(position, target, up);
+        let half_height = height / 2.0;
+        let half_width = half_height * aspect;
+
+        Self::new([
+            Plane3D::from_point_and_normal(position + forward * near, forward),
+            Plane3D::from_point_and_normal(position + forward * far, -forward),
+            Plane3D::from_point_and_normal(position - right * half_width, right),
+            Plane3D::from_point_and_normal(position + right * half_width, -right),
+            Plane3D::from_point_and_normal(position - up * half_height, up),
+            Plane3D::from_point_and_normal(position + up * half_height, -up),
+        ])
+    }
+
+    /// Returns the planes, in the order near, far, left, right, bottom, top
+    /// for frustums built with [`from_camera`](Self::from_camera).
+    #[inline]
+    pub fn planes(&self) -> &[Plane3D; 6] {
+        &self.planes
+    }
+
+    /// Returns `true` if `aabb` lies entirely behind one of the planes, so
+    /// nothing in it can be visible.
+    ///
+    /// Boxes straddling a plane are kept. The test is conservative: a box
+    /// near a corner of the frustum may be kept although it lies outside.
+    pub fn excludes(&self, aabb: &Aabb) -> bool {
+        self.planes
+            .iter()
+            .any(|plane| plane.distance_to_aabb(aabb) < 0.0)
+    }
+
+    /// Returns `true` if `point` is inside the frustum or on its boundary.
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+}
+
+/// Returns the unit forward, right and up vectors of a camera.
+fn view_basis(
+    position: Point3<f32>,
+    target: Point3<f32>,
+    up: Vector3<f32>,
+) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>) {
+    let forward = (target - position).normalize();
+    let right = forward
+        .cross(&up)
+        .try_normalize(f32::EPSILON)
+        .expect("up must not be parallel to the view direction");
+    (forward, right, right.cross(&forward))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A camera at the origin looking down -Z with a 90 degree field of view.
+    fn camera() -> Frustum {
+        Frustum::from_camera(
+            Point3::origin(),
+            Point3::new(0.0, 0.0, -1.0),
+            Vector3::y(),
+            90.0_f32.to_radians(),
+            2.0,
+            1.0,
+            10.0,
+        )
+    }
+
+    fn cube_at(center: [f32; 3], half_size: f32) -> Aabb {
+        let center = Point3::from(center);
+        let half = Vector3::repeat(half_size);
+        Aabb::new(center - half, center + half)
+    }
+
+    #[test]
+    fn from_camera_planes_bound_the_view() {
+        let frustum = camera();
+
+        assert!(frustum.contains_point(Point3::new(0.0, 0.0, -5.0)));
+        // The horizontal half-extent is twice the vertical one
+        assert!(frustum.contains_point(Point3::new(9.0, 0.0, -5.0)));
+        assert!(!frustum.contains_point(Point3::new(0.0, 6.0, -5.0)));
+        assert!(!frustum.contains_point(Point3::new(0.0, 0.0, -0.5)));
+        assert!(!frustum.contains_point(Point3::new(0.0, 0.0, -11.0)));
+        assert!(!frustum.contains_point(Point3::new(0.0, 0.0, 5.0)));
+    }
+
+    #[test]
+    fn from_orthographic_bounds_a_box() {
+        // Looking down -Z from z = 1, 4 units tall and 8 wide
+        let frustum = Frustum::from_orthographic(
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vector3::y(),
+            4.0,
+            2.0,
+            1.0,
+            10.0,
+        );
+
+        // Unlike a perspective view, the width does not grow with depth
+        assert!(frustum.contains_point(Point3::new(3.9, 1.9, -0.5)));
+        assert!(frustum.contains_point(Point3::new(3.9, 1.9, -8.5)));
+        assert!(!frustum.contains_point(Point3::new(4.1, 0.0, -8.5)));
+        assert!(!frustum.contains_point(Point3::new(0.0, -2.1, -5.0)));
+        assert!(!frustum.contains_point(Point3::new(0.0, 0.0, 0.5)));
+        assert!(!frustum.contains_point(Point3::new(0.0, 0.0, -9.5)));
+    }
+
+    #[test]
+    fn excludes_boxes_outside_one_plane() {
+        let frustum = camera();
+
+        assert!(!frustum.excludes(&cube_at([0.0, 0.0, -5.0], 1.0)));
+        assert!(frustum.excludes(&cube_at([0.0, 0.0, 5.0], 1.0)));
+        assert!(frustum.excludes(&cube_at([0.0, 0.0, -20.0], 1.0)));
+        assert!(frustum.excludes(&cube_at([-20.0, 0.0, -5.0], 1.0)));
+        assert!(frustum.excludes(&cube_at([0.0, 10.0, -5.0], 1.0)));
+    }
+
+    #[test]
+    fn keeps_boxes_straddling_a_plane() {
+        let frustum = camera();
+
+        // Crosses the far plane, the top plane and the camera position
+        assert!(!frustum.excludes(&cube_at([0.0, 0.0, -10.0], 1.0)));
+        assert!(!frustum.excludes(&cube_at([0.0, 5.0, -5.0], 1.0)));
+        assert!(!frustum.excludes(&cube_at([0.0, 0.0, 0.0], 2.0)));
+    }
+}
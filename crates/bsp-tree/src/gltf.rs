@@ -0,0 +1,196 @@
+//! glTF 2.0 export of a BSP tree's polygons.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::BspTree;
+
+/// `componentType` for 32-bit floats.
+const FLOAT: u32 = 5126;
+/// `componentType` for 32-bit unsigned integers.
+const UNSIGNED_INT: u32 = 5125;
+/// `target` for vertex attribute buffer views.
+const ARRAY_BUFFER: u32 = 34962;
+/// `target` for index buffer views.
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+impl<T: Clone> BspTree<T> {
+    /// Writes the tree's polygons as a minimal glTF 2.0 JSON document.
+    ///
+    /// The output contains a single mesh with one triangle primitive. Polygons
+    /// are fan-triangulated as by
+    /// [`Polygon::triangulate`](crate::Polygon::triangulate), vertices with
+    /// identical position and normal are shared, and each vertex carries its
+    /// polygon's unit normal. Geometry is embedded as a base64 data URI, so the
+    /// document is self-contained. Degenerate polygons (without a normal) are
+    /// skipped.
+    pub fn write_gltf<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut normals: Vec<[f32; 3]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        let mut lookup: HashMap<[u32; 6], u32> = HashMap::new();
+
+        for polygon in self.collect_polygons() {
+            let Some(normal) = polygon.unit_normal() else {
+                continue;
+            };
+            let normal = [normal.x, normal.y, normal.z];
+
+            let ids: Vec<u32> = polygon
+                .vertices()
+                .iter()
+                .map(|v| {
+                    let position = [v.x, v.y, v.z];
+                    let key = [
+                        position[0].to_bits(),
+                        position[1].to_bits(),
+                        position[2].to_bits(),
+                        normal[0].to_bits(),
+                        normal[1].to_bits(),
+                        normal[2].to_bits(),
+                    ];
+                    *lookup.entry(key).or_insert_with(|| {
+                        positions.push(position);
+                        normals.push(normal);
+                        (positions.len() - 1) as u32
+                    })
+                })
+                .collect();
+
+            for corners in polygon.fan() {
+                indices.extend(corners.map(|i| ids[i]));
+            }
+        }
+
+        if indices.is_empty() {
+            return writeln!(writer, r#"{{"asset":{{"version":"2.0"}}}}"#);
+        }
+
+        let mut buffer: Vec<u8> = Vec::new();
+        for p in positions.iter().chain(&normals) {
+            for c in p {
+                buffer.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        for i in &indices {
+            buffer.extend_from_slice(&i.to_le_bytes());
+        }
+
+        let attribute_len = positions.len() * 12;
+        let index_len = indices.len() * 4;
+
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for p in &positions {
+            for k in 0..3 {
+                min[k] = min[k].min(p[k]);
+                max[k] = max[k].max(p[k]);
+            }
+        }
+
+        let json = format!(
+            concat!(
+                r#"{{"asset":{{"version":"2.0","generator":"bsp-tree"}},"#,
+                r#""scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0}}],"#,
+                r#""meshes":[{{"primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1}},"indices":2,"mode":4}}]}}],"#,
+                r#""accessors":["#,
+                r#"{{"bufferView":0,"componentType":{float},"count":{vertices},"type":"VEC3","min":[{min}],"max":[{max}]}},"#,
+                r#"{{"bufferView":1,"componentType":{float},"count":{vertices},"type":"VEC3"}},"#,
+                r#"{{"bufferView":2,"componentType":{uint},"count":{indices},"type":"SCALAR"}}],"#,
+                r#""bufferViews":["#,
+                r#"{{"buffer":0,"byteOffset":0,"byteLength":{attr_len},"target":{array}}},"#,
+                r#"{{"buffer":0,"byteOffset":{attr_len},"byteLength":{attr_len},"target":{array}}},"#,
+                r#"{{"buffer":0,"byteOffset":{index_offset},"byteLength":{index_len},"target":{element}}}],"#,
+                r#""buffers":[{{"byteLength":{buffer_len},"uri":"data:application/octet-stream;base64,{data}"}}]}}"#,
+            ),
+            float = FLOAT,
+            uint = UNSIGNED_INT,
+            array = ARRAY_BUFFER,
+            element = ELEMENT_ARRAY_BUFFER,
+            vertices = positions.len(),
+            indices = indices.len(),
+            min = format_vec3(min),
+            max = format_vec3(max),
+            attr_len = attribute_len,
+            index_offset = attribute_len * 2,
+            index_len = index_len,
+            buffer_len = buffer.len(),
+            data = base64(&buffer),
+        );
+
+        writeln!(writer, "{json}")
+    }
+}
+
+fn format_vec3(v: [f32; 3]) -> String {
+    format!("{:?},{:?},{:?}", v[0], v[1], v[2])
+}
+
+/// Standard base64 encoding with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for k in 0..4 {
+            if k <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * k)) as usize & 0x3F] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::cuboid;
+
+    #[test]
+    fn base64_encoding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn cube_exports_valid_gltf() {
+        let tree = BspTree::from_polygons(cuboid([0.0; 3], [1.0; 3]));
+        let mut out = Vec::new();
+        tree.write_gltf(&mut out).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["asset"]["version"], "2.0");
+
+        let accessors = json["accessors"].as_array().unwrap();
+        assert_eq!(accessors.len(), 3);
+        // 6 faces x 4 corners, each with its own face normal
+        assert_eq!(accessors[0]["count"], 24);
+        assert_eq!(accessors[1]["count"], 24);
+        // 6 quads x 2 triangles x 3 indices
+        assert_eq!(accessors[2]["count"], 36);
+
+        let buffer_len = json["buffers"][0]["byteLength"].as_u64().unwrap();
+        assert_eq!(buffer_len, 24 * 12 * 2 + 36 * 4);
+    }
+
+    #[test]
+    fn empty_tree_exports_asset_only() {
+        let mut out = Vec::new();
+        BspTree::new().write_gltf(&mut out).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["asset"]["version"], "2.0");
+        assert!(json.get("meshes").is_none());
+    }
+}
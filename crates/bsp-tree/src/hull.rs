@@ -0,0 +1,182 @@
+//! 3D convex hull computation.
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{Plane3D, Polygon, PLANE_EPSILON};
+
+/// Computes the convex hull of a point set as outward-facing triangles.
+///
+/// Uses the incremental algorithm: starting from a tetrahedron of extreme
+/// points, each remaining point removes the faces it can see and is joined
+/// to the resulting horizon edges.
+///
+/// Returns an empty list if the points do not span a volume (fewer than four
+/// points, or all points coplanar within [`PLANE_EPSILON`]).
+pub(crate) fn convex_hull(points: &[Point3<f32>]) -> Vec<Polygon> {
+    let Some(initial) = initial_tetrahedron(points) else {
+        return Vec::new();
+    };
+
+    let interior = Point3::from(
+        initial
+            .iter()
+            .map(|&i| points[i].coords)
+            .sum::<Vector3<f32>>()
+            / 4.0,
+    );
+
+    let [a, b, c, d] = initial;
+    let mut faces: Vec<[usize; 3]> = [[a, b, c], [a, b, d], [a, c, d], [b, c, d]]
+        .into_iter()
+        .map(|face| orient_outward(points, face, interior))
+        .collect();
+
+    for (i, point) in points.iter().enumerate() {
+        if initial.contains(&i) {
+            continue;
+        }
+
+        let visible: Vec<bool> = faces
+            .iter()
+            .map(|face| face_plane(points, face).signed_distance(*point) > PLANE_EPSILON)
+            .collect();
+        if !visible.contains(&true) {
+            continue;
+        }
+
+        // Horizon edges belong to exactly one visible face
+        let mut horizon = Vec::new();
+        for (face, _) in faces.iter().zip(&visible).filter(|(_, v)| **v) {
+            for k in 0..3 {
+                let edge = (face[k], face[(k + 1) % 3]);
+                let shared = faces
+                    .iter()
+                    .zip(&visible)
+                    .filter(|(_, v)| **v)
+                    .any(|(other, _)| (0..3).any(|m| (other[(m + 1) % 3], other[m]) == edge));
+                if !shared {
+                    horizon.push(edge);
+                }
+            }
+        }
+
+        let mut kept: Vec<[usize; 3]> = faces
+            .iter()
+            .zip(&visible)
+            .filter(|(_, v)| !**v)
+            .map(|(face, _)| *face)
+            .collect();
+        kept.extend(horizon.into_iter().map(|(from, to)| [from, to, i]));
+        faces = kept;
+    }
+
+    faces
+        .iter()
+        .map(|&[a, b, c]| Polygon::new(vec![points[a], points[b], points[c]]))
+        .collect()
+}
+
+/// Finds four affinely independent points, or `None` if the set is flat.
+fn initial_tetrahedron(points: &[Point3<f32>]) -> Option<[usize; 4]> {
+    let farthest = |score: &dyn Fn(&Point3<f32>) -> f32| {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, score(p)))
+            .max_by(|x, y| x.1.total_cmp(&y.1))
+    };
+
+    let a = 0;
+    let origin = *points.first()?;
+    let (b, dist) = farthest(&|p| (p - origin).norm())?;
+    if dist <= PLANE_EPSILON {
+        return None;
+    }
+
+    let axis = (points[b] - origin) / dist;
+    let (c, dist) = farthest(&|p| {
+        let offset = p - origin;
+        (offset - axis * offset.dot(&axis)).norm()
+    })?;
+    if dist <= PLANE_EPSILON {
+        return None;
+    }
+
+    let plane = Plane3D::from_three_points(points[a], points[b], points[c]);
+    let (d, dist) = farthest(&|p| plane.signed_distance(*p).abs())?;
+    if dist <= PLANE_EPSILON {
+        return None;
+    }
+
+    Some([a, b, c, d])
+}
+
+/// Returns the face with its winding flipped if needed so that its normal
+/// points away from `interior`.
+fn orient_outward(points: &[Point3<f32>], face: [usize; 3], interior: Point3<f32>) -> [usize; 3] {
+    if face_plane(points, &face).signed_distance(interior) > 0.0 {
+        [face[0], face[2], face[1]]
+    } else {
+        face
+    }
+}
+
+fn face_plane(points: &[Point3<f32>], face: &[usize; 3]) -> Plane3D {
+    Plane3D::from_three_points(points[face[0]], points[face[1]], points[face[2]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Volume enclosed by outward-facing triangles (divergence theorem).
+    fn enclosed_volume(faces: &[Polygon]) -> f32 {
+        faces
+            .iter()
+            .map(|f| {
+                let v = f.vertices();
+                v[0].coords.dot(&v[1].coords.cross(&v[2].coords)) / 6.0
+            })
+            .sum()
+    }
+
+    fn cube_corners(size: f32) -> Vec<Point3<f32>> {
+        let mut corners = Vec::new();
+        for x in [0.0, size] {
+            for y in [0.0, size] {
+                for z in [0.0, size] {
+                    corners.push(Point3::new(x, y, z));
+                }
+            }
+        }
+        corners
+    }
+
+    #[test]
+    fn hull_of_flat_points_is_empty() {
+        let points = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ];
+        assert!(convex_hull(&points).is_empty());
+    }
+
+    #[test]
+    fn hull_ignores_interior_points() {
+        let mut points = cube_corners(2.0);
+        points.push(Point3::new(1.0, 1.0, 1.0));
+        points.push(Point3::new(0.5, 1.5, 0.2));
+
+        let hull = convex_hull(&points);
+
+        assert_eq!(hull.len(), 12);
+        assert!((enclosed_volume(&hull) - 8.0).abs() < 1e-4);
+        for face in &hull {
+            for v in face.vertices() {
+                assert!(cube_corners(2.0).contains(v));
+            }
+        }
+    }
+}
@@ -6,10 +6,21 @@
 //! # Core Types
 //!
 //! - [`Polygon`], [`Triangle`], [`Rectangle`]: Geometric primitives
-//! - [`Plane3D`]: 3D plane representation with classification operations
+//! - [`Plane3D`]: 3D plane representation with classification operations,
+//!   generic over the scalar as [`Plane3DGeneric`]
+//! - [`Aabb`]: Axis-aligned bounding boxes
+//! - [`Frustum`]: View frustums for culling
 //! - [`Cuttable`]: Trait for splitting geometry by planes
 //! - [`BspTree`]: The BSP tree container
 //! - [`BspNode`]: Tree nodes holding splitting planes and coplanar polygons
+//! - [`MeshData`]: Indexed triangle meshes for export
+//!
+//! Trees can also be exported to and imported from Wavefront OBJ with
+//! `BspTree::to_obj` and `BspTree::from_obj_reader`, see [`ObjError`].
+//!
+//! # Features
+//!
+//! - `gltf`: `BspTree::write_gltf` for exporting the tree as a glTF 2.0 mesh
 //!
 //! # Example
 //!
@@ -30,18 +41,39 @@
 //! assert_eq!(tree.polygon_count(), 1);
 //! ```
 
+mod aabb;
 pub mod bsp;
 mod cuttable;
+mod frustum;
+#[cfg(feature = "gltf")]
+mod gltf;
+mod hull;
+mod mesh;
+mod obj;
 mod plane;
 mod polygon;
 mod rectangle;
+mod sdf;
+#[cfg(test)]
+mod test_fixtures;
 mod triangle;
 
 // Re-export BSP tree types at crate root for convenience
-pub use bsp::{BspNode, BspTree, BspVisitor, FirstPolygon, PlaneSelector};
+pub use bsp::{
+    AxisAlignedSelector, BalancedSelector, BspNode, BspNodeVisitor, BspTree, BspVisitor,
+    BuildOptions, BuildProgress, BuildReport, CostSelector, Direction, FirstPolygon,
+    InsertLocation, MinSplitSelector, NodeId, PlaneSelector, RandomSelector, SampledSelector,
+    TraversalCache, TreeStats,
+};
 
-pub use cuttable::Cuttable;
-pub use plane::{Classification, Plane3D, PlaneSide, PLANE_EPSILON};
-pub use polygon::Polygon;
+pub use aabb::Aabb;
+pub use cuttable::{
+    compute_cut_faces, compute_cut_faces_with_epsilon, CutPiece, Cuttable, SplitResult,
+};
+pub use frustum::Frustum;
+pub use mesh::MeshData;
+pub use obj::ObjError;
+pub use plane::{Classification, Plane3D, Plane3DGeneric, PlaneScalar, PlaneSide, PLANE_EPSILON};
+pub use polygon::{merge_coplanar, Polygon, PolygonError};
 pub use rectangle::Rectangle;
 pub use triangle::Triangle;
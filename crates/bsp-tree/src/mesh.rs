@@ -0,0 +1,210 @@
+//! Indexed triangle meshes built from a BSP tree's polygons.
+
+use std::collections::HashMap;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{BspTree, Triangle};
+
+/// An indexed triangle mesh with one normal per vertex.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    /// Vertex positions.
+    pub positions: Vec<Point3<f32>>,
+    /// Unit vertex normals, parallel to `positions`.
+    pub normals: Vec<Vector3<f32>>,
+    /// Triangles as indices into `positions`, wound counter-clockwise when
+    /// seen from the front.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl<T: Clone> BspTree<T> {
+    /// Triangulates the tree's whole surface, as
+    /// [`Polygon::triangulate`](crate::Polygon::triangulate) on every polygon.
+    pub fn triangulate(&self) -> Vec<Triangle> {
+        self.collect_polygons()
+            .iter()
+            .flat_map(|polygon| polygon.triangulate())
+            .collect()
+    }
+
+    /// Builds an indexed triangle mesh with welded vertices and smooth
+    /// normals, for viewing without cracks or facet noise.
+    ///
+    /// Polygons are fan-triangulated as by
+    /// [`Polygon::triangulate`](crate::Polygon::triangulate), and vertices
+    /// closer than `tol` to an already emitted vertex are merged into it.
+    /// Each vertex normal is the average of the normals of the triangles
+    /// using it, weighted by the triangles' angles at the vertex. Triangles
+    /// that collapse when welding are dropped. Welding does not remove
+    /// T-junctions, so a mesh whose faces were split by the tree may still
+    /// have edges used by a single triangle.
+    pub fn to_welded_mesh(&self, tol: f32) -> MeshData {
+        let mut mesh = MeshData::default();
+        let mut welder = Welder::new(tol);
+
+        for polygon in self.collect_polygons() {
+            let ids: Vec<u32> = polygon
+                .vertices()
+                .iter()
+                .map(|&v| welder.weld(v, &mut mesh.positions))
+                .collect();
+            for corners in polygon.fan() {
+                let triangle = corners.map(|i| ids[i]);
+                if triangle[0] != triangle[1]
+                    && triangle[1] != triangle[2]
+                    && triangle[2] != triangle[0]
+                {
+                    mesh.triangles.push(triangle);
+                }
+            }
+        }
+
+        // Weighting by the corner angle makes the normal independent of how
+        // a face was triangulated
+        let mut normals = vec![Vector3::zeros(); mesh.positions.len()];
+        for triangle in &mesh.triangles {
+            let corners = triangle.map(|i| mesh.positions[i as usize]);
+            let Some(face_normal) = (corners[1] - corners[0])
+                .cross(&(corners[2] - corners[0]))
+                .try_normalize(f32::EPSILON)
+            else {
+                continue;
+            };
+            for k in 0..3 {
+                let to_next = corners[(k + 1) % 3] - corners[k];
+                let to_prev = corners[(k + 2) % 3] - corners[k];
+                normals[triangle[k] as usize] += face_normal * to_next.angle(&to_prev);
+            }
+        }
+        mesh.normals = normals
+            .into_iter()
+            .map(|n| n.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::zeros))
+            .collect();
+
+        mesh
+    }
+}
+
+/// Merges nearby points, using a uniform grid so that only points in
+/// neighboring cells are compared.
+pub(crate) struct Welder {
+    tol: f32,
+    cell_size: f32,
+    cells: HashMap<[i64; 3], Vec<u32>>,
+}
+
+impl Welder {
+    pub(crate) fn new(tol: f32) -> Self {
+        Self {
+            tol,
+            cell_size: tol.max(1e-6),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, point: Point3<f32>) -> [i64; 3] {
+        point
+            .coords
+            .map(|c| (c / self.cell_size).floor() as i64)
+            .into()
+    }
+
+    /// Returns the index of a position within `tol` of `point`, pushing
+    /// `point` as a new position if there is none.
+    pub(crate) fn weld(&mut self, point: Point3<f32>, positions: &mut Vec<Point3<f32>>) -> u32 {
+        let [x, y, z] = self.cell(point);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(candidates) = self.cells.get(&[x + dx, y + dy, z + dz]) else {
+                        continue;
+                    };
+                    if let Some(&id) = candidates
+                        .iter()
+                        .find(|&&id| (positions[id as usize] - point).norm() <= self.tol)
+                    {
+                        return id;
+                    }
+                }
+            }
+        }
+
+        let id = positions.len() as u32;
+        positions.push(point);
+        self.cells.entry([x, y, z]).or_default().push(id);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::cube;
+    use crate::Polygon;
+
+    #[test]
+    fn welded_cube_is_watertight() {
+        let tree = BspTree::from_polygons(cube());
+        let mesh = tree.to_welded_mesh(1e-4);
+
+        assert_eq!(mesh.positions.len(), 8);
+        assert_eq!(mesh.normals.len(), 8);
+        assert_eq!(mesh.triangles.len(), 12);
+
+        // Every directed edge is matched by the reverse edge of exactly one
+        // other triangle
+        let mut edges: HashMap<(u32, u32), usize> = HashMap::new();
+        for t in &mesh.triangles {
+            for i in 0..3 {
+                *edges.entry((t[i], t[(i + 1) % 3])).or_default() += 1;
+            }
+        }
+        assert_eq!(edges.len(), 36);
+        assert!(edges.values().all(|&count| count == 1));
+        assert!(edges.keys().all(|&(a, b)| edges.contains_key(&(b, a))));
+    }
+
+    #[test]
+    fn welded_cube_normals_point_outward() {
+        let tree = BspTree::from_polygons(cube());
+        let mesh = tree.to_welded_mesh(1e-4);
+        let center = Point3::new(1.0, 1.0, 1.0);
+
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            let outward = (position - center).normalize();
+            assert!((normal.norm() - 1.0).abs() < 1e-5);
+            assert!(normal.dot(&outward) > 0.99);
+        }
+    }
+
+    #[test]
+    fn tolerance_merges_nearby_vertices() {
+        let triangle = |offset: f32| {
+            Polygon::new(vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0 + offset, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ])
+        };
+        let tree = BspTree::from_polygons(vec![triangle(0.0), triangle(1e-3)]);
+
+        assert_eq!(tree.to_welded_mesh(1e-2).positions.len(), 3);
+        assert_eq!(tree.to_welded_mesh(1e-4).positions.len(), 4);
+    }
+
+    #[test]
+    fn triangulated_cube_covers_its_surface() {
+        let tree = BspTree::from_polygons(cube());
+        let triangles = tree.triangulate();
+
+        assert_eq!(triangles.len(), 12);
+        let area: f32 = triangles.iter().map(|t| t.normal().norm() / 2.0).sum();
+        assert!((area - 24.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn empty_tree_gives_empty_mesh() {
+        assert_eq!(BspTree::new().to_welded_mesh(1e-4), MeshData::default());
+    }
+}
@@ -0,0 +1,361 @@
+//! Wavefront OBJ import and export of a BSP tree's polygons.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use nalgebra::Point3;
+
+use crate::mesh::Welder;
+use crate::{BspTree, Polygon, PLANE_EPSILON};
+
+/// Error returned when reading an OBJ document fails.
+#[derive(Debug)]
+pub enum ObjError {
+    /// Reading from the source failed.
+    Io(io::Error),
+    /// A line could not be parsed or describes an invalid face.
+    Parse {
+        /// The 1-based line number.
+        line: usize,
+        /// What is wrong with the line.
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "failed to read OBJ: {err}"),
+            ObjError::Parse { line, message } => write!(f, "OBJ line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io(err) => Some(err),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+impl<T: Clone> BspTree<T> {
+    /// Writes the tree's polygons as a Wavefront OBJ document.
+    ///
+    /// Each polygon becomes one `f` line with its vertices in order, so the
+    /// winding, and with it the normal, is preserved. Vertices closer than
+    /// [`PLANE_EPSILON`] to an earlier vertex are shared, and polygons that
+    /// collapse to fewer than three distinct vertices when shared are
+    /// skipped.
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut positions = Vec::new();
+        let mut welder = Welder::new(PLANE_EPSILON);
+        let mut faces: Vec<Vec<u32>> = Vec::new();
+
+        for polygon in self.collect_polygons() {
+            let mut ids: Vec<u32> = Vec::with_capacity(polygon.len());
+            for &v in polygon.vertices() {
+                let id = welder.weld(v, &mut positions);
+                if ids.last() != Some(&id) {
+                    ids.push(id);
+                }
+            }
+            if ids.len() > 1 && ids.first() == ids.last() {
+                ids.pop();
+            }
+            if ids.len() >= 3 {
+                faces.push(ids);
+            }
+        }
+
+        writeln!(writer, "# bsp-tree")?;
+        for p in &positions {
+            writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        for face in &faces {
+            write!(writer, "f")?;
+            // OBJ indices are 1-based
+            for id in face {
+                write!(writer, " {}", id + 1)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the tree's polygons as a Wavefront OBJ document, as
+    /// [`write_obj`](Self::write_obj).
+    pub fn to_obj(&self) -> String {
+        let mut out = Vec::new();
+        self.write_obj(&mut out)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("OBJ output is ASCII")
+    }
+}
+
+impl BspTree {
+    /// Builds a tree from the faces of an OBJ document, using
+    /// [`from_polygons`](Self::from_polygons).
+    ///
+    /// Only `v` and `f` lines are used; other statements, such as texture
+    /// coordinates, normals, groups and materials, are ignored. Each face
+    /// becomes one polygon with its vertices in order, so n-gons are kept
+    /// whole. Face vertices may carry texture and normal indices
+    /// (`1/2/3`, `1//3`), which are skipped, and negative indices count back
+    /// from the last vertex read, as in the OBJ format.
+    ///
+    /// Returns [`ObjError::Parse`] for malformed lines, out of range indices,
+    /// and faces that are degenerate, not planar within [`PLANE_EPSILON`] or
+    /// not [convex](Polygon::is_convex).
+    pub fn from_obj_reader<R: Read>(reader: R) -> Result<BspTree, ObjError> {
+        let mut vertices: Vec<Point3<f32>> = Vec::new();
+        let mut polygons = Vec::new();
+
+        for (index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let error = |message: String| ObjError::Parse {
+                line: index + 1,
+                message,
+            };
+
+            let content = line.split('#').next().unwrap_or_default();
+            let mut parts = content.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let coords = parts
+                        .take(3)
+                        .map(|p| {
+                            p.parse::<f32>()
+                                .map_err(|_| error(format!("invalid coordinate `{p}`")))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let &[x, y, z] = coords.as_slice() else {
+                        return Err(error("vertex needs three coordinates".to_string()));
+                    };
+                    vertices.push(Point3::new(x, y, z));
+                }
+                Some("f") => {
+                    let corners = parts
+                        .map(|p| {
+                            resolve_index(p, vertices.len())
+                                .map(|i| vertices[i])
+                                .map_err(&error)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let polygon =
+                        Polygon::try_new(corners).map_err(|err| error(err.to_string()))?;
+                    polygons.push(polygon);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(BspTree::from_polygons(polygons))
+    }
+}
+
+/// Resolves the vertex part of a face element (`v`, `v/vt`, `v//vn` or
+/// `v/vt/vn`) to a 0-based index into the `count` vertices read so far.
+fn resolve_index(element: &str, count: usize) -> Result<usize, String> {
+    let vertex = element.split('/').next().unwrap_or_default();
+    let index: i64 = vertex
+        .parse()
+        .map_err(|_| format!("invalid face element `{element}`"))?;
+    let resolved = match index {
+        1.. => index - 1,
+        ..0 => count as i64 + index,
+        0 => return Err("vertex index 0 is not valid".to_string()),
+    };
+    if (0..count as i64).contains(&resolved) {
+        Ok(resolved as usize)
+    } else {
+        Err(format!(
+            "vertex index {index} is out of range for {count} vertices"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::cube;
+    use nalgebra::Vector3;
+
+    /// Parses the `v` and `f` lines of an OBJ document.
+    fn parse(obj: &str) -> (Vec<Point3<f32>>, Vec<Vec<usize>>) {
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for line in obj.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let c: Vec<f32> = parts.map(|p| p.parse().unwrap()).collect();
+                    vertices.push(Point3::new(c[0], c[1], c[2]));
+                }
+                Some("f") => faces.push(parts.map(|p| p.parse().unwrap()).collect()),
+                _ => {}
+            }
+        }
+        (vertices, faces)
+    }
+
+    #[test]
+    fn cube_shares_corner_vertices() {
+        let tree = BspTree::from_polygons(cube());
+        let (vertices, faces) = parse(&tree.to_obj());
+
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(faces.len(), 6);
+        assert!(faces.iter().all(|f| f.len() == 4));
+        assert!(faces.iter().flatten().all(|&i| (1..=8).contains(&i)));
+    }
+
+    #[test]
+    fn faces_keep_winding() {
+        let tree = BspTree::from_polygons(cube());
+        let (vertices, faces) = parse(&tree.to_obj());
+        let center = Point3::new(1.0, 1.0, 1.0);
+
+        for face in faces {
+            let [a, b, c] = [face[0], face[1], face[2]].map(|i| vertices[i - 1]);
+            let normal: Vector3<f32> = (b - a).cross(&(c - a));
+            assert!(normal.dot(&(a - center)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn split_fragments_share_seam_vertices() {
+        // The slanted quad cuts through two faces of the cube
+        let mut polygons = vec![Polygon::new(vec![
+            Point3::new(-1.0, -1.0, 1.5),
+            Point3::new(3.0, -1.0, 0.5),
+            Point3::new(3.0, 3.0, 0.5),
+            Point3::new(-1.0, 3.0, 1.5),
+        ])];
+        polygons.extend(cube());
+        let tree = BspTree::from_polygons(polygons);
+        let (vertices, faces) = parse(&tree.to_obj());
+
+        assert_eq!(faces.len(), tree.polygon_count());
+        let corner_count: usize = tree.collect_polygons().iter().map(|p| p.len()).sum();
+        assert!(vertices.len() < corner_count);
+    }
+
+    #[test]
+    fn empty_tree_has_no_geometry() {
+        let (vertices, faces) = parse(&BspTree::new().to_obj());
+        assert!(vertices.is_empty());
+        assert!(faces.is_empty());
+    }
+
+    /// Builds a tree from an OBJ document given as a string.
+    fn read(obj: &str) -> Result<BspTree, ObjError> {
+        BspTree::from_obj_reader(obj.as_bytes())
+    }
+
+    /// Returns the line and message of a parse error.
+    fn parse_error(result: Result<BspTree, ObjError>) -> (usize, String) {
+        match result {
+            Err(ObjError::Parse { line, message }) => (line, message),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let tree = BspTree::from_polygons(cube());
+        let restored = read(&tree.to_obj()).unwrap();
+
+        // Rebuilding may order the polygons differently
+        let keys = |tree: &BspTree| {
+            let mut keys: Vec<_> = tree
+                .collect_polygons()
+                .iter()
+                .map(|p| p.canonical_key(1e-4))
+                .collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(keys(&restored), keys(&tree));
+    }
+
+    #[test]
+    fn import_keeps_ngons_and_skips_extra_indices() {
+        let obj = "\
+# A unit square and a pentagon
+o shapes
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vn 0 0 1
+f 1/1/1 2/1/1 3/1/1 4/1/1
+v 0 0 1
+v 2 0 1
+v 3 1 1
+v 1 2 1
+v -1 1 1
+usemtl none
+f -5//1 -4//1 -3//1 -2//1 -1//1 # trailing comment
+";
+        let tree = read(obj).unwrap();
+        let polygons = tree.collect_polygons();
+
+        assert_eq!(polygons.len(), 2);
+        let mut sizes: Vec<usize> = polygons.iter().map(|p| p.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, [4, 5]);
+        for polygon in &polygons {
+            assert!(polygon.unit_normal().unwrap().z > 0.99);
+        }
+    }
+
+    #[test]
+    fn import_reports_malformed_lines() {
+        let (line, message) = parse_error(read("v 0 0 0\nv 1 0\n"));
+        assert_eq!(line, 2);
+        assert!(message.contains("three coordinates"));
+
+        let (line, message) = parse_error(read("v 0 0 x\n"));
+        assert_eq!(line, 1);
+        assert!(message.contains("`x`"));
+
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n";
+        let (line, message) = parse_error(read(obj));
+        assert_eq!(line, 4);
+        assert!(message.contains("out of range"));
+
+        let (_, message) = parse_error(read("v 0 0 0\nv 1 0 0\nf 1 2\n"));
+        assert!(message.contains("at least 3"));
+
+        let (_, message) = parse_error(read("v 0 0 0\nv 1 0 0\nv 2 0 0\nf 1 2 3\n"));
+        assert!(message.contains("degenerate"));
+
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 1\nv 0 1 0\nf 1 2 3 4\n";
+        let (_, message) = parse_error(read(obj));
+        assert!(message.contains("not planar"));
+
+        let obj = "v 0 0 0\nv 2 1 0\nv 0 2 0\nv 0.5 1 0\nf 1 2 3 4\n";
+        let (_, message) = parse_error(read(obj));
+        assert!(message.contains("not convex"));
+    }
+
+    #[test]
+    fn import_accepts_collinear_start() {
+        // The first three corners lie on one edge of the square
+        let obj = "v 0 0 0\nv 1 0 0\nv 2 0 0\nv 2 2 0\nv 0 2 0\nf 1 2 3 4 5\n";
+        let tree = read(obj).unwrap();
+        let polygon = &tree.collect_polygons()[0];
+
+        assert_eq!(polygon.len(), 5);
+        assert!(polygon.unit_normal().unwrap().z > 0.99);
+    }
+}
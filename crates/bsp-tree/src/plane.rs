@@ -1,11 +1,28 @@
 //! Plane representation and operations for BSP trees.
 
-use nalgebra::{Point3, Vector3};
+use nalgebra::{Isometry3, Point3, RealField, Vector3};
+
+use crate::Aabb;
 
 /// Default epsilon for plane classification.
 /// Points within this distance of the plane are considered "on" the plane.
 pub const PLANE_EPSILON: f32 = 1e-4;
 
+/// A scalar type planes can be built over, with its own classification
+/// tolerance.
+pub trait PlaneScalar: RealField + Copy {
+    /// Default epsilon for plane classification at this precision.
+    const PLANE_EPSILON: Self;
+}
+
+impl PlaneScalar for f32 {
+    const PLANE_EPSILON: f32 = PLANE_EPSILON;
+}
+
+impl PlaneScalar for f64 {
+    const PLANE_EPSILON: f64 = 1e-9;
+}
+
 /// Which side of a plane a point lies on.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlaneSide {
@@ -31,35 +48,46 @@ pub enum Classification {
 }
 
 /// A plane in 3D space, represented as `normal · point = offset`.
+///
+/// Generic over the scalar type; most code uses the `f32` alias [`Plane3D`].
 #[derive(Debug, Clone, PartialEq)]
-pub struct Plane3D {
-    normal: Vector3<f32>,
-    offset: f32,
+pub struct Plane3DGeneric<T: PlaneScalar> {
+    normal: Vector3<T>,
+    offset: T,
 }
 
-impl Plane3D {
+/// A single-precision plane.
+pub type Plane3D = Plane3DGeneric<f32>;
+
+impl<T: PlaneScalar> Plane3DGeneric<T> {
     /// Creates a new plane from a normal vector and offset.
     /// The normal will be normalized automatically.
     ///
     /// # Panics
     /// Panics if the normal vector has zero length.
-    pub fn new(normal: Vector3<f32>, offset: f32) -> Self {
+    pub fn new(normal: Vector3<T>, offset: T) -> Self {
         let norm = normal.norm();
-        assert!(norm > f32::EPSILON, "Plane normal cannot be zero");
+        assert!(norm > T::default_epsilon(), "Plane normal cannot be zero");
         Self {
             normal: normal / norm,
             offset: offset / norm,
         }
     }
 
+    /// Creates a plane from a normal that is already unit length, without
+    /// renormalizing it (so stored planes round-trip exactly).
+    pub(crate) fn from_unit_normal(normal: Vector3<T>, offset: T) -> Self {
+        Self { normal, offset }
+    }
+
     /// Creates a plane from a point on the plane and a normal vector.
     /// The normal will be normalized automatically.
     ///
     /// # Panics
     /// Panics if the normal vector has zero length.
-    pub fn from_point_and_normal(point: Point3<f32>, normal: Vector3<f32>) -> Self {
+    pub fn from_point_and_normal(point: Point3<T>, normal: Vector3<T>) -> Self {
         let norm = normal.norm();
-        assert!(norm > f32::EPSILON, "Plane normal cannot be zero");
+        assert!(norm > T::default_epsilon(), "Plane normal cannot be zero");
         let unit_normal = normal / norm;
         let offset = unit_normal.dot(&point.coords);
         Self {
@@ -73,22 +101,34 @@ impl Plane3D {
     ///
     /// # Panics
     /// Panics if the points are collinear (or nearly so).
-    pub fn from_three_points(a: Point3<f32>, b: Point3<f32>, c: Point3<f32>) -> Self {
+    pub fn from_three_points(a: Point3<T>, b: Point3<T>, c: Point3<T>) -> Self {
         let ab = b - a;
         let ac = c - a;
         let normal = ab.cross(&ac);
         Self::from_point_and_normal(a, normal)
     }
 
+    /// Creates a plane from three points like
+    /// [`from_three_points`](Self::from_three_points), or returns `None` if
+    /// they are collinear (or nearly so).
+    pub fn try_from_three_points(a: Point3<T>, b: Point3<T>, c: Point3<T>) -> Option<Self> {
+        let (ab, ac) = (b - a, c - a);
+        let normal = ab.cross(&ac);
+        // Rounding noise in the cross product grows with the squared lengths
+        let scale = ab.norm_squared().max(ac.norm_squared());
+        (normal.norm() > T::default_epsilon() * scale)
+            .then(|| Self::from_point_and_normal(a, normal))
+    }
+
     /// Returns the unit normal vector of the plane.
     #[inline]
-    pub fn normal(&self) -> Vector3<f32> {
+    pub fn normal(&self) -> Vector3<T> {
         self.normal
     }
 
     /// Returns the signed distance from the origin to the plane along the normal.
     #[inline]
-    pub fn offset(&self) -> f32 {
+    pub fn offset(&self) -> T {
         self.offset
     }
 
@@ -97,19 +137,19 @@ impl Plane3D {
     /// - Negative: point is behind (opposite side from normal)
     /// - Zero: point is on the plane
     #[inline]
-    pub fn signed_distance(&self, point: Point3<f32>) -> f32 {
+    pub fn signed_distance(&self, point: Point3<T>) -> T {
         self.normal.dot(&point.coords) - self.offset
     }
 
     /// Classifies which side of the plane a point lies on.
-    /// Uses the default `PLANE_EPSILON` tolerance.
+    /// Uses the scalar's default `PLANE_EPSILON` tolerance.
     #[inline]
-    pub fn classify_point(&self, point: Point3<f32>) -> PlaneSide {
-        self.classify_point_with_epsilon(point, PLANE_EPSILON)
+    pub fn classify_point(&self, point: Point3<T>) -> PlaneSide {
+        self.classify_point_with_epsilon(point, T::PLANE_EPSILON)
     }
 
     /// Classifies which side of the plane a point lies on, with a custom epsilon.
-    pub fn classify_point_with_epsilon(&self, point: Point3<f32>, epsilon: f32) -> PlaneSide {
+    pub fn classify_point_with_epsilon(&self, point: Point3<T>, epsilon: T) -> PlaneSide {
         let dist = self.signed_distance(point);
         if dist > epsilon {
             PlaneSide::Front
@@ -129,9 +169,49 @@ impl Plane3D {
         }
     }
 
+    /// Reflects a point across the plane.
+    #[inline]
+    pub fn reflect_point(&self, point: Point3<T>) -> Point3<T> {
+        let distance = self.signed_distance(point);
+        point - self.normal * (nalgebra::convert::<f64, T>(2.0) * distance)
+    }
+
+    /// Reflects this plane across `mirror`.
+    ///
+    /// Points in front of this plane reflect to points in front of the result.
+    pub fn mirrored_across(&self, mirror: &Self) -> Self {
+        let along = self.normal.dot(&mirror.normal);
+        let normal = self.normal - mirror.normal * (along + along);
+        let point = mirror.reflect_point(Point3::from(self.normal * self.offset));
+        Self::from_point_and_normal(point, normal)
+    }
+
+    /// Moves this plane by a rigid motion.
+    ///
+    /// Points in front of this plane are moved to points in front of the
+    /// result. The normal is rotated, and the offset moves with the
+    /// translation along the new normal.
+    ///
+    /// ```
+    /// use bsp_tree::Plane3D;
+    /// use nalgebra::{Isometry3, Vector3};
+    ///
+    /// let floor = Plane3D::new(Vector3::y(), 0.0);
+    /// let raised = floor.transformed(&Isometry3::translation(3.0, 2.0, 0.0));
+    /// assert_eq!(raised.normal(), Vector3::y());
+    /// assert_eq!(raised.offset(), 2.0);
+    /// ```
+    pub fn transformed(&self, iso: &Isometry3<T>) -> Self {
+        let normal = iso.rotation * self.normal;
+        Self {
+            normal,
+            offset: self.offset + normal.dot(&iso.translation.vector),
+        }
+    }
+
     /// Projects a point onto the plane (finds the closest point on the plane).
     #[inline]
-    pub fn project_point(&self, point: Point3<f32>) -> Point3<f32> {
+    pub fn project_point(&self, point: Point3<T>) -> Point3<T> {
         point - self.normal * self.signed_distance(point)
     }
 
@@ -142,27 +222,251 @@ impl Plane3D {
     /// - `point` is the intersection point
     ///
     /// Returns `None` if the segment is parallel to the plane or doesn't intersect.
-    pub fn intersect_segment(
-        &self,
-        start: Point3<f32>,
-        end: Point3<f32>,
-    ) -> Option<(f32, Point3<f32>)> {
+    pub fn intersect_segment(&self, start: Point3<T>, end: Point3<T>) -> Option<(T, Point3<T>)> {
         let direction = end - start;
         let denom = self.normal.dot(&direction);
 
         // Segment is parallel to plane
-        if denom.abs() < f32::EPSILON {
+        if denom.abs() < T::default_epsilon() {
             return None;
         }
 
         let t = (self.offset - self.normal.dot(&start.coords)) / denom;
 
         // Intersection is outside the segment
-        if t < 0.0 || t > 1.0 {
+        if t < T::zero() || t > T::one() {
             return None;
         }
 
         let point = start + direction * t;
         Some((t, point))
     }
+
+    /// Computes the line where this plane meets `other`.
+    ///
+    /// Returns `Some((point, direction))` where `point` is the point of the
+    /// line closest to the origin and `direction` is the cross product of the
+    /// two normals, so its length is the sine of the angle between the
+    /// planes. Returns `None` if that length is within the scalar's
+    /// `PLANE_EPSILON` of zero, i.e. the planes are parallel.
+    pub fn intersect_plane(&self, other: &Self) -> Option<(Point3<T>, Vector3<T>)> {
+        let direction = self.normal.cross(&other.normal);
+        let length_squared = direction.norm_squared();
+        if length_squared.sqrt() <= T::PLANE_EPSILON {
+            return None;
+        }
+
+        // Satisfies both plane equations, and lies in the span of the two
+        // normals, so it is the point of the line closest to the origin
+        let point = (other.normal.cross(&direction) * self.offset
+            + direction.cross(&self.normal) * other.offset)
+            / length_squared;
+        Some((Point3::from(point), direction))
+    }
+}
+
+impl Plane3D {
+    /// Classifies a box against the plane.
+    ///
+    /// Uses the default [`PLANE_EPSILON`] tolerance, see
+    /// [`classify_aabb_with_epsilon`](Self::classify_aabb_with_epsilon).
+    #[inline]
+    pub fn classify_aabb(&self, aabb: &Aabb) -> Classification {
+        self.classify_aabb_with_epsilon(aabb, PLANE_EPSILON)
+    }
+
+    /// Classifies a box against the plane, with a custom epsilon.
+    ///
+    /// Only the box's corners nearest to and furthest along the normal are
+    /// tested, so this takes constant time. As with
+    /// [`Polygon::classify`](crate::Polygon::classify), corners within
+    /// `epsilon` of the plane do not count for either side: a box touching
+    /// the plane is classified by the side the rest of it lies on, and a box
+    /// flat within the plane is `Coplanar`.
+    pub fn classify_aabb_with_epsilon(&self, aabb: &Aabb, epsilon: f32) -> Classification {
+        let (min, max) = self.distance_range(aabb);
+        match (max > epsilon, min < -epsilon) {
+            (true, true) => Classification::Spanning,
+            (true, false) => Classification::Front,
+            (false, true) => Classification::Back,
+            (false, false) => Classification::Coplanar,
+        }
+    }
+
+    /// Returns the signed distance from the plane to the nearest point of a
+    /// box.
+    ///
+    /// Positive if the box lies entirely in front of the plane, negative if
+    /// it lies entirely behind it and zero if the plane passes through it.
+    /// Like [`signed_distance`](Self::signed_distance), this is exact for a
+    /// unit normal.
+    pub fn distance_to_aabb(&self, aabb: &Aabb) -> f32 {
+        let (min, max) = self.distance_range(aabb);
+        if min > 0.0 {
+            min
+        } else if max < 0.0 {
+            max
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the range `(min, max)` of signed distances from the plane
+    /// over all points in a box, i.e. those of the box corners nearest to and
+    /// furthest along the normal.
+    pub(crate) fn distance_range(&self, aabb: &Aabb) -> (f32, f32) {
+        let (min, max) = aabb.projected_extent(&self.normal);
+        (min - self.offset, max - self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box_at(x: f32) -> Aabb {
+        Aabb::new(Point3::new(x, 0.0, 0.0), Point3::new(x + 1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn classify_aabb_uses_the_extreme_corners() {
+        use Classification::{Back, Coplanar, Front, Spanning};
+
+        let plane = Plane3D::from_point_and_normal(Point3::new(2.0, 0.0, 0.0), Vector3::x());
+        assert_eq!(plane.classify_aabb(&unit_box_at(3.0)), Front);
+        assert_eq!(plane.classify_aabb(&unit_box_at(0.0)), Back);
+        assert_eq!(plane.classify_aabb(&unit_box_at(1.5)), Spanning);
+        // Touching boxes count as on the side of the rest of the box
+        assert_eq!(plane.classify_aabb(&unit_box_at(1.0)), Back);
+        assert_eq!(plane.classify_aabb(&unit_box_at(2.0)), Front);
+
+        let flat = Aabb::new(Point3::new(2.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+        assert_eq!(plane.classify_aabb(&flat), Coplanar);
+
+        // The plane x + y = 1 crosses the unit box's diagonal and only
+        // touches the corners of its neighbours along x
+        let diagonal = Plane3D::new(Vector3::new(1.0, 1.0, 0.0).normalize(), 0.5_f32.sqrt());
+        assert_eq!(diagonal.classify_aabb(&unit_box_at(0.0)), Spanning);
+        assert_eq!(diagonal.classify_aabb(&unit_box_at(1.0)), Front);
+        assert_eq!(diagonal.classify_aabb(&unit_box_at(-1.0)), Back);
+    }
+
+    #[test]
+    fn distance_to_aabb_is_zero_through_the_box() {
+        let plane = Plane3D::from_point_and_normal(Point3::new(2.0, 0.0, 0.0), Vector3::x());
+        assert_eq!(plane.distance_to_aabb(&unit_box_at(3.5)), 1.5);
+        assert_eq!(plane.distance_to_aabb(&unit_box_at(-2.0)), -3.0);
+        assert_eq!(plane.distance_to_aabb(&unit_box_at(1.5)), 0.0);
+
+        let flipped = plane.flipped();
+        assert_eq!(flipped.distance_to_aabb(&unit_box_at(3.5)), -1.5);
+    }
+
+    #[test]
+    fn f64_plane_keeps_precision_far_from_origin() {
+        let plane =
+            Plane3DGeneric::<f64>::from_point_and_normal(Point3::new(0.0, 0.0, 1e7), Vector3::z());
+
+        let above = Point3::new(3.0, -2.0, 1e7 + 1e-6);
+        assert!((plane.signed_distance(above) - 1e-6).abs() < 1e-8);
+        assert_eq!(plane.classify_point(above), PlaneSide::Front);
+
+        // The same offset is lost entirely in single precision
+        let plane = Plane3D::from_point_and_normal(Point3::new(0.0, 0.0, 1e7), Vector3::z());
+        let above = Point3::new(3.0, -2.0, 1e7 + 1e-6);
+        assert_eq!(plane.signed_distance(above), 0.0);
+    }
+
+    #[test]
+    fn f64_intersect_segment_matches_f32() {
+        let plane64 = Plane3DGeneric::<f64>::new(Vector3::new(0.0, 2.0, 0.0), 1.0);
+        let plane32 = Plane3D::new(Vector3::new(0.0, 2.0, 0.0), 1.0);
+
+        let (t64, hit64) = plane64
+            .intersect_segment(Point3::new(1.0, -1.0, 0.0), Point3::new(1.0, 3.0, 0.0))
+            .unwrap();
+        let (t32, hit32) = plane32
+            .intersect_segment(Point3::new(1.0, -1.0, 0.0), Point3::new(1.0, 3.0, 0.0))
+            .unwrap();
+
+        assert_eq!(t64, 0.375);
+        assert_eq!(t32, 0.375);
+        assert_eq!(hit64, Point3::new(1.0, 0.5, 0.0));
+        assert_eq!(hit32, Point3::new(1.0, 0.5, 0.0));
+        assert!(
+            plane64
+                .intersect_segment(Point3::new(0.0, 1.0, 0.0), Point3::new(0.0, 2.0, 0.0))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn try_from_three_points_rejects_collinear_points() {
+        let plane = Plane3D::try_from_three_points(
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+        )
+        .unwrap();
+        assert_eq!(plane.normal(), Vector3::z());
+        assert_eq!(plane.offset(), 1.0);
+
+        let collinear = Plane3D::try_from_three_points(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(3.0, 3.0, 3.0),
+        );
+        assert!(collinear.is_none());
+    }
+
+    #[test]
+    fn transformed_moves_points_with_the_plane() {
+        let plane = Plane3D::new(Vector3::new(1.0, 2.0, -1.0), 3.0);
+        let iso = Isometry3::new(Vector3::new(4.0, -1.0, 2.0), Vector3::new(0.3, -0.2, 0.9));
+        let moved = plane.transformed(&iso);
+
+        assert!((moved.normal().norm() - 1.0).abs() < 1e-6);
+        for point in [Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, -1.0, 5.0)] {
+            let distance = plane.signed_distance(point);
+            assert!((moved.signed_distance(iso * point) - distance).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn intersect_plane_of_perpendicular_planes() {
+        let floor = Plane3D::new(Vector3::z(), 1.0);
+        let wall = Plane3D::new(Vector3::x(), 2.0);
+
+        let (point, direction) = floor.intersect_plane(&wall).unwrap();
+        assert!((point - Point3::new(2.0, 0.0, 1.0)).norm() < 1e-6);
+        assert!((direction - Vector3::y()).norm() < 1e-6);
+
+        // Swapping the planes reverses the direction
+        let (other_point, other_direction) = wall.intersect_plane(&floor).unwrap();
+        assert!((other_point - point).norm() < 1e-6);
+        assert!((other_direction + direction).norm() < 1e-6);
+    }
+
+    #[test]
+    fn intersect_plane_point_lies_on_both_slanted_planes() {
+        let a = Plane3DGeneric::<f64>::new(Vector3::new(1.0, 2.0, -1.0), 3.0);
+        let b = Plane3DGeneric::<f64>::new(Vector3::new(-2.0, 0.5, 1.0), -1.0);
+
+        let (point, direction) = a.intersect_plane(&b).unwrap();
+        for along in [0.0, 1.0, -10.0] {
+            let on_line = point + direction * along;
+            assert!(a.signed_distance(on_line).abs() < 1e-9);
+            assert!(b.signed_distance(on_line).abs() < 1e-9);
+        }
+        assert!(point.coords.dot(&direction).abs() < 1e-9);
+    }
+
+    #[test]
+    fn intersect_plane_rejects_parallel_planes() {
+        let floor = Plane3D::new(Vector3::z(), 0.0);
+        let ceiling = Plane3D::new(Vector3::z(), 5.0);
+        assert!(floor.intersect_plane(&ceiling).is_none());
+        assert!(floor.intersect_plane(&floor.flipped()).is_none());
+        assert!(floor.intersect_plane(&floor).is_none());
+    }
 }
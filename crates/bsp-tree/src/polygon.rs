@@ -1,16 +1,62 @@
 //! Generic polygon representation for BSP trees.
 
-use nalgebra::{Point3, Vector3};
+use std::fmt;
 
-use crate::{Classification, Plane3D, PlaneSide, Rectangle, Triangle};
+use nalgebra::{Isometry3, Point3, Vector2, Vector3};
+
+use crate::{Aabb, Classification, Cuttable, Plane3D, PlaneSide, Rectangle, Triangle, PLANE_EPSILON};
+
+/// Why [`Polygon::try_new`] rejected a list of vertices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolygonError {
+    /// Fewer than three vertices were given.
+    TooFewVertices {
+        /// The number of vertices given.
+        count: usize,
+    },
+    /// The vertices enclose no area, e.g. they are all collinear.
+    Degenerate,
+    /// Some vertex lies further than [`PLANE_EPSILON`] from the plane.
+    NotCoplanar,
+    /// The vertices do not form a [convex](Polygon::is_convex) polygon.
+    NotConvex,
+}
+
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolygonError::TooFewVertices { count } => {
+                write!(f, "polygon needs at least 3 vertices, got {count}")
+            }
+            PolygonError::Degenerate => write!(f, "polygon is degenerate"),
+            PolygonError::NotCoplanar => write!(f, "polygon is not planar"),
+            PolygonError::NotConvex => write!(f, "polygon is not convex"),
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
 
 /// A convex polygon in 3D space, defined by an ordered list of vertices.
 ///
 /// Vertices should be coplanar and in counter-clockwise winding order
 /// when viewed from the front (the direction the normal points).
-#[derive(Debug, Clone, PartialEq)]
-pub struct Polygon {
+///
+/// Polygons produced by splitting remember the plane of the polygon they were
+/// cut from, so [`plane`](Self::plane) stays exact even when a fragment's
+/// first vertices are (nearly) collinear. Equality compares vertices only.
+///
+/// A polygon can carry [texture coordinates](Self::with_uvs), one per
+/// vertex, which splitting interpolates at the vertices it inserts, and a
+/// [payload](Self::with_data) of type `T`, such as a material id, which
+/// every fragment of a split receives a clone of. The default payload `()`
+/// carries nothing.
+#[derive(Debug, Clone)]
+pub struct Polygon<T = ()> {
     vertices: Vec<Point3<f32>>,
+    plane: Option<Plane3D>,
+    uvs: Option<Vec<Vector2<f32>>>,
+    data: T,
 }
 
 impl Polygon {
@@ -19,28 +65,263 @@ impl Polygon {
     /// # Panics (debug builds only)
     /// - Panics if fewer than 3 vertices are provided.
     /// - Panics if vertices are not coplanar.
+    /// - Panics if the polygon is not [convex](Self::is_convex).
     pub fn new(vertices: Vec<Point3<f32>>) -> Self {
         debug_assert!(
             vertices.len() >= 3,
             "Polygon must have at least 3 vertices"
         );
         debug_assert!(
-            Self::are_coplanar(&vertices),
+            Self::are_coplanar(&vertices, PLANE_EPSILON),
             "Polygon vertices must be coplanar"
         );
-        Self { vertices }
+        debug_assert!(Self::are_convex(&vertices), "Polygon must be convex");
+        Self {
+            vertices,
+            plane: None,
+            uvs: None,
+            data: (),
+        }
     }
 
-    /// Checks if all vertices lie on the same plane.
-    fn are_coplanar(vertices: &[Point3<f32>]) -> bool {
+    /// Creates a new polygon from a list of vertices, checking them in all
+    /// builds.
+    ///
+    /// Unlike [`new`](Self::new), which only checks in debug builds, bad
+    /// input is reported as an error, so untrusted data such as imported
+    /// meshes can be skipped rather than crash or produce invalid geometry.
+    /// The plane passes through the centroid with the polygon's
+    /// [`normal`](Self::normal), and every vertex must lie within
+    /// [`PLANE_EPSILON`] of it.
+    ///
+    /// ```
+    /// use bsp_tree::{Polygon, PolygonError};
+    /// use nalgebra::Point3;
+    ///
+    /// let sliver = vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 1.0, 0.0),
+    ///     Point3::new(2.0, 2.0, 0.0),
+    /// ];
+    /// assert_eq!(Polygon::try_new(sliver), Err(PolygonError::Degenerate));
+    /// ```
+    pub fn try_new(vertices: Vec<Point3<f32>>) -> Result<Self, PolygonError> {
+        Self::try_new_with_epsilon(vertices, PLANE_EPSILON)
+    }
+
+    /// Like [`try_new`](Self::try_new), but vertices may lie up to `epsilon`
+    /// from the plane, e.g. `1e-3` for meshes with large coordinates whose
+    /// f32 rounding exceeds [`PLANE_EPSILON`].
+    ///
+    /// Vertices further than [`PLANE_EPSILON`] from the plane are projected
+    /// onto it, so the polygon is planar to the precision the rest of the
+    /// crate expects. Vertices within it are kept exactly.
+    pub fn try_new_with_epsilon(
+        mut vertices: Vec<Point3<f32>>,
+        epsilon: f32,
+    ) -> Result<Self, PolygonError> {
+        if vertices.len() < 3 {
+            return Err(PolygonError::TooFewVertices {
+                count: vertices.len(),
+            });
+        }
+
+        // Relative to the polygon's size, as in unit_normal, so that small
+        // but valid polygons are accepted
+        let normal = newell_normal(&vertices);
+        if normal.norm() <= f32::EPSILON * longest_edge_squared(&vertices) {
+            return Err(PolygonError::Degenerate);
+        }
+        let sum: Vector3<f32> = vertices.iter().map(|v| v.coords).sum();
+        let centroid = Point3::from(sum / vertices.len() as f32);
+        let plane = Plane3D::from_point_and_normal(centroid, normal.normalize());
+        if vertices
+            .iter()
+            .any(|&v| plane.classify_point_with_epsilon(v, epsilon) != PlaneSide::OnPlane)
+        {
+            return Err(PolygonError::NotCoplanar);
+        }
+        for vertex in &mut vertices {
+            if plane.classify_point(*vertex) != PlaneSide::OnPlane {
+                *vertex = plane.project_point(*vertex);
+            }
+        }
+
+        let polygon = Self::with_plane(vertices, plane);
+        if !polygon.is_convex() {
+            return Err(PolygonError::NotConvex);
+        }
+        Ok(polygon)
+    }
+
+    /// Creates a polygon whose plane is already known, e.g. a fragment of a
+    /// split polygon inheriting its parent's plane.
+    pub(crate) fn with_plane(vertices: Vec<Point3<f32>>, plane: Plane3D) -> Self {
+        debug_assert!(
+            vertices.len() >= 3,
+            "Polygon must have at least 3 vertices"
+        );
+        debug_assert!(
+            vertices
+                .iter()
+                .all(|v| plane.classify_point(*v) == PlaneSide::OnPlane),
+            "Polygon vertices must lie on the given plane"
+        );
+        Self {
+            vertices,
+            plane: Some(plane),
+            uvs: None,
+            data: (),
+        }
+    }
+
+    /// Checks if all vertices lie within `epsilon` of the same plane.
+    ///
+    /// Degenerate vertex lists, without a normal, are accepted.
+    fn are_coplanar(vertices: &[Point3<f32>], epsilon: f32) -> bool {
         if vertices.len() <= 3 {
             return true;
         }
 
-        let plane = Plane3D::from_three_points(vertices[0], vertices[1], vertices[2]);
-        vertices[3..]
+        let normal = newell_normal(vertices);
+        if normal.norm() <= f32::EPSILON {
+            return true;
+        }
+        let plane = Plane3D::from_point_and_normal(vertices[0], normal);
+        vertices[1..]
             .iter()
-            .all(|v| plane.classify_point(*v) == PlaneSide::OnPlane)
+            .all(|v| plane.classify_point_with_epsilon(*v, epsilon) == PlaneSide::OnPlane)
+    }
+
+    /// Checks if the vertices, in order, form a convex polygon.
+    ///
+    /// Every vertex must turn the same way relative to the Newell normal,
+    /// allowing collinear vertices and a slight opposite turn within
+    /// [`PLANE_EPSILON`], and the boundary must wind around exactly once.
+    /// Degenerate vertex lists, without a normal, are accepted.
+    fn are_convex(vertices: &[Point3<f32>]) -> bool {
+        let Some(normal) = newell_normal(vertices).try_normalize(f32::EPSILON) else {
+            return true;
+        };
+        let count = vertices.len();
+        let mut winding = 0.0;
+        for k in 0..count {
+            let incoming = vertices[k] - vertices[(k + count - 1) % count];
+            let outgoing = vertices[(k + 1) % count] - vertices[k];
+            let turn = incoming.cross(&outgoing).dot(&normal);
+            if turn < -PLANE_EPSILON * incoming.norm() * outgoing.norm() {
+                return false;
+            }
+            winding += turn.atan2(incoming.dot(&outgoing));
+        }
+        // A star turns the same way at every vertex but winds around twice
+        (winding - std::f32::consts::TAU).abs() < 1e-2
+    }
+}
+
+impl<T> Polygon<T> {
+    /// Attaches a payload, replacing the current one.
+    ///
+    /// Cutting gives every fragment a clone of the payload, so it survives
+    /// tree builds, insertion and CSG, and a [`BspTree`](crate::BspTree) of
+    /// such polygons can tell which payload a [raycast](crate::BspTree::raycast)
+    /// hit. Like the plane, it does not take part in equality.
+    ///
+    /// ```
+    /// use bsp_tree::{BspTree, FirstPolygon, Polygon};
+    /// use nalgebra::{Point3, Vector3};
+    ///
+    /// let quad = |z: f32| {
+    ///     Polygon::new(vec![
+    ///         Point3::new(-1.0, -1.0, z),
+    ///         Point3::new(1.0, -1.0, z),
+    ///         Point3::new(1.0, 1.0, z),
+    ///         Point3::new(-1.0, 1.0, z),
+    ///     ])
+    /// };
+    ///
+    /// let polygons = vec![quad(0.0).with_data("stone"), quad(2.0).with_data("glass")];
+    /// let tree = BspTree::build(polygons, &FirstPolygon);
+    /// let (_, hit) = tree.raycast(Point3::new(0.0, 0.0, 5.0), -Vector3::z()).unwrap();
+    /// assert_eq!(*hit.data(), "glass");
+    /// ```
+    pub fn with_data<U>(self, data: U) -> Polygon<U> {
+        Polygon {
+            vertices: self.vertices,
+            plane: self.plane,
+            uvs: self.uvs,
+            data,
+        }
+    }
+
+    /// Replaces the payload with `f` applied to it.
+    pub fn map_data<U, F: FnOnce(T) -> U>(self, f: F) -> Polygon<U> {
+        Polygon {
+            vertices: self.vertices,
+            plane: self.plane,
+            uvs: self.uvs,
+            data: f(self.data),
+        }
+    }
+
+    /// Returns the payload.
+    #[inline]
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    /// Returns the payload for modification.
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+
+    /// Attaches texture coordinates, one per vertex in the same order.
+    ///
+    /// Cutting interpolates them at the vertices it inserts, and operations
+    /// that drop or reorder vertices keep them in step. Like the plane, they
+    /// do not take part in equality.
+    ///
+    /// # Panics
+    /// Panics if `uvs` does not have one entry per vertex.
+    pub fn with_uvs(mut self, uvs: Vec<Vector2<f32>>) -> Self {
+        assert_eq!(
+            uvs.len(),
+            self.vertices.len(),
+            "Polygon must have one texture coordinate per vertex"
+        );
+        self.uvs = Some(uvs);
+        self
+    }
+
+    /// Returns the texture coordinates, one per vertex, if the polygon has
+    /// any.
+    #[inline]
+    pub fn uvs(&self) -> Option<&[Vector2<f32>]> {
+        self.uvs.as_deref()
+    }
+
+    /// Returns `true` if the polygon is convex.
+    ///
+    /// Every consecutive pair of edges must turn the same way around the
+    /// polygon's normal; collinear vertices are allowed. Splitting,
+    /// triangulation and point containment all assume convex polygons, and
+    /// [`Polygon::new`] checks this in debug builds.
+    ///
+    /// ```
+    /// use bsp_tree::Polygon;
+    /// use nalgebra::Point3;
+    ///
+    /// let square = Polygon::new(vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 1.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    /// ]);
+    /// assert!(square.is_convex());
+    /// ```
+    pub fn is_convex(&self) -> bool {
+        Polygon::are_convex(&self.vertices)
     }
 
     /// Returns the vertices of the polygon.
@@ -49,6 +330,13 @@ impl Polygon {
         &self.vertices
     }
 
+    /// Returns the polygon's edges as pairs of consecutive vertices, the
+    /// last edge leading back to the first vertex.
+    pub fn edges(&self) -> impl Iterator<Item = (Point3<f32>, Point3<f32>)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
     /// Returns the number of vertices.
     #[inline]
     pub fn len(&self) -> usize {
@@ -63,24 +351,27 @@ impl Polygon {
 
     /// Computes the (unnormalized) normal vector of the polygon.
     ///
-    /// Uses the first three vertices to compute the normal via cross product.
-    /// The direction follows the right-hand rule based on vertex winding.
+    /// Uses Newell's method, summing the cross products over all edges, so
+    /// collinear vertices do not matter and noise in single vertices is
+    /// averaged out. The direction follows the right-hand rule based on
+    /// vertex winding, and the length is twice the polygon's area.
     pub fn normal(&self) -> Vector3<f32> {
-        let a = &self.vertices[0];
-        let b = &self.vertices[1];
-        let c = &self.vertices[2];
-        let ab = b - a;
-        let ac = c - a;
-        ab.cross(&ac)
+        newell_normal(&self.vertices)
     }
 
     /// Computes the unit normal vector of the polygon.
     ///
-    /// Returns `None` if the first three vertices are collinear.
+    /// Uses the inherited plane if this polygon was produced by a split.
+    /// Otherwise returns `None` if the polygon has zero area relative to its
+    /// size: a normal shorter than rounding noise on the squared length of
+    /// its longest edge does not give a reliable direction.
     pub fn unit_normal(&self) -> Option<Vector3<f32>> {
+        if let Some(ref plane) = self.plane {
+            return Some(plane.normal());
+        }
         let n = self.normal();
         let len = n.norm();
-        if len > f32::EPSILON {
+        if len > f32::EPSILON * longest_edge_squared(&self.vertices) {
             Some(n / len)
         } else {
             None
@@ -89,10 +380,28 @@ impl Polygon {
 
     /// Returns the plane that this polygon lies on.
     ///
+    /// Uses the inherited plane if this polygon was produced by a split.
+    /// Otherwise the plane has the polygon's [`normal`](Self::normal) and
+    /// passes through its centroid.
+    ///
     /// # Panics
-    /// Panics if the first three vertices are collinear.
+    /// Panics if no plane is inherited and the polygon has zero area; see
+    /// [`try_plane`](Self::try_plane).
     pub fn plane(&self) -> Plane3D {
-        Plane3D::from_three_points(self.vertices[0], self.vertices[1], self.vertices[2])
+        self.try_plane()
+            .expect("Polygon must have a non-zero area to define a plane")
+    }
+
+    /// Returns the plane that this polygon lies on, as [`plane`](Self::plane)
+    /// does, or `None` if no plane is inherited and the polygon has no
+    /// [`unit_normal`](Self::unit_normal), e.g. a sliver whose vertices are
+    /// all collinear.
+    pub fn try_plane(&self) -> Option<Plane3D> {
+        if let Some(ref plane) = self.plane {
+            return Some(plane.clone());
+        }
+        let normal = self.unit_normal()?;
+        Some(Plane3D::from_point_and_normal(self.centroid(), normal))
     }
 
     /// Computes the centroid (center of mass) of the polygon.
@@ -101,6 +410,46 @@ impl Polygon {
         Point3::from(sum / self.vertices.len() as f32)
     }
 
+    /// Computes the area of the polygon, from the length of its Newell
+    /// [`normal`](Self::normal). The result does not depend on the winding.
+    pub fn area(&self) -> f32 {
+        self.normal().norm() / 2.0
+    }
+
+    /// Computes the bounding box of the polygon's vertices.
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(&self.vertices).expect("Polygon must have at least 3 vertices")
+    }
+
+    /// Splits the polygon into triangles fanning out from the first vertex.
+    ///
+    /// This is valid because polygons are convex. The triangles keep the
+    /// polygon's winding and texture coordinates. Zero-area triangles, such
+    /// as slivers left by cutting, are dropped.
+    pub fn triangulate(&self) -> Vec<Triangle> {
+        self.fan()
+            .map(|corners| {
+                let [a, b, c] = corners.map(|i| self.vertices[i]);
+                let triangle = Triangle::new(a, b, c);
+                match &self.uvs {
+                    Some(uvs) => triangle.with_uvs(corners.map(|i| uvs[i])),
+                    None => triangle,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the vertex indices of the triangles
+    /// [`triangulate`](Self::triangulate) produces.
+    pub(crate) fn fan(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        (1..self.vertices.len() - 1)
+            .map(|i| [0, i, i + 1])
+            .filter(|corners| {
+                let [a, b, c] = corners.map(|i| self.vertices[i]);
+                Triangle::new(a, b, c).unit_normal().is_some()
+            })
+    }
+
     /// Classifies this polygon relative to a plane.
     ///
     /// Returns:
@@ -108,13 +457,21 @@ impl Polygon {
     /// - `Back` if all vertices are behind the plane
     /// - `Coplanar` if all vertices lie on the plane
     /// - `Spanning` if vertices are on both sides
+    ///
+    /// Uses the default [`PLANE_EPSILON`] tolerance.
     pub fn classify(&self, plane: &Plane3D) -> Classification {
+        self.classify_with_epsilon(plane, PLANE_EPSILON)
+    }
+
+    /// Classifies this polygon relative to a plane, treating vertices within
+    /// `epsilon` of the plane as on it.
+    pub fn classify_with_epsilon(&self, plane: &Plane3D, epsilon: f32) -> Classification {
         let mut front = 0;
         let mut back = 0;
         let mut on_plane = 0;
 
         for vertex in &self.vertices {
-            match plane.classify_point(*vertex) {
+            match plane.classify_point_with_epsilon(*vertex, epsilon) {
                 PlaneSide::Front => front += 1,
                 PlaneSide::Back => back += 1,
                 PlaneSide::OnPlane => on_plane += 1,
@@ -131,12 +488,579 @@ impl Polygon {
             Classification::Spanning
         }
     }
+
+    /// Classifies this polygon relative to a plane, tolerating stray vertices
+    /// that sit just outside `epsilon` when the polygon as a whole lies on it.
+    ///
+    /// The polygon is considered `Coplanar` when its area-weighted signed
+    /// distance to the plane is within `epsilon` and its normal is parallel
+    /// to the plane normal (within `epsilon`). This avoids spurious splits
+    /// where surfaces touch and a single vertex drifts from a shared plane.
+    /// Otherwise, vertices are classified individually with `epsilon` as in
+    /// [`classify`](Self::classify).
+    pub fn classify_stable(&self, plane: &Plane3D, epsilon: f32) -> Classification {
+        let distances: Vec<f32> = self
+            .vertices
+            .iter()
+            .map(|v| plane.signed_distance(*v))
+            .collect();
+
+        // Fan-triangulate to weight each triangle's mean distance by its area
+        let mut area_vector = Vector3::zeros();
+        let mut weighted_distance = 0.0;
+        let mut total_area = 0.0;
+        let a = self.vertices[0];
+        for i in 1..self.vertices.len() - 1 {
+            let cross = (self.vertices[i] - a).cross(&(self.vertices[i + 1] - a));
+            let area = cross.norm() * 0.5;
+            area_vector += cross;
+            weighted_distance += area * (distances[0] + distances[i] + distances[i + 1]) / 3.0;
+            total_area += area;
+        }
+
+        let area_norm = area_vector.norm();
+        if total_area > f32::EPSILON && area_norm > f32::EPSILON {
+            let mean_distance = weighted_distance / total_area;
+            let alignment = (area_vector / area_norm).dot(&plane.normal()).abs();
+            if mean_distance.abs() <= epsilon && 1.0 - alignment <= epsilon {
+                return Classification::Coplanar;
+            }
+        }
+
+        let front = distances.iter().filter(|&&d| d > epsilon).count();
+        let back = distances.iter().filter(|&&d| d < -epsilon).count();
+
+        if front == 0 && back == 0 {
+            Classification::Coplanar
+        } else if back == 0 {
+            Classification::Front
+        } else if front == 0 {
+            Classification::Back
+        } else {
+            Classification::Spanning
+        }
+    }
+
+    /// Returns the texture coordinates in reverse order, matching reversed
+    /// vertices.
+    fn reversed_uvs(&self) -> Option<Vec<Vector2<f32>>> {
+        self.uvs
+            .as_ref()
+            .map(|uvs| uvs.iter().rev().copied().collect())
+    }
+
+    /// Returns the point on this polygon closest to `point`.
+    ///
+    /// The polygon is treated as a filled convex region, so points above its
+    /// interior project straight onto it and other points snap to the nearest
+    /// edge.
+    pub fn closest_point(&self, point: Point3<f32>) -> Point3<f32> {
+        let plane = self.plane();
+        let projected = plane.project_point(point);
+        let normal = plane.normal();
+        let n = self.vertices.len();
+
+        let inside = (0..n).all(|i| {
+            let a = self.vertices[i];
+            let b = self.vertices[(i + 1) % n];
+            (b - a).cross(&(projected - a)).dot(&normal) >= 0.0
+        });
+        if inside {
+            return projected;
+        }
+
+        (0..n)
+            .map(|i| {
+                let a = self.vertices[i];
+                let ab = self.vertices[(i + 1) % n] - a;
+                let len_sq = ab.norm_squared();
+                let t = if len_sq > f32::EPSILON {
+                    ((point - a).dot(&ab) / len_sq).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                a + ab * t
+            })
+            .min_by(|p, q| {
+                (p - point)
+                    .norm_squared()
+                    .total_cmp(&(q - point).norm_squared())
+            })
+            .expect("Polygon must have at least 3 vertices")
+    }
+
+    /// Returns where the ray from `origin` along `dir` hits this polygon, as
+    /// the parameter `t` of the hit point `origin + dir * t`.
+    ///
+    /// `t` is the hit distance when `dir` is unit length. Both sides of the
+    /// polygon are hit. Returns `None` if the ray misses, points away from
+    /// the polygon or runs parallel to its plane.
+    pub fn ray_intersect(&self, origin: Point3<f32>, dir: Vector3<f32>) -> Option<f32> {
+        let plane = self.plane();
+        let normal = plane.normal();
+        let denom = normal.dot(&dir);
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let t = -plane.signed_distance(origin) / denom;
+        if t < 0.0 {
+            return None;
+        }
+
+        // Points near an edge count as inside, so a ray through an edge
+        // shared by two polygons hits at least one of them
+        let hit = origin + dir * t;
+        self.contains_point_with_epsilon(hit, PLANE_EPSILON)
+            .then_some(t)
+    }
+
+    /// Returns `true` if `point` lies on this polygon: within
+    /// [`PLANE_EPSILON`] of its plane and inside or on every edge.
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.contains_point_with_epsilon(point, 0.0)
+    }
+
+    /// Like [`contains_point`](Self::contains_point), but also accepts points
+    /// up to `epsilon` outside an edge.
+    pub fn contains_point_with_epsilon(&self, point: Point3<f32>, epsilon: f32) -> bool {
+        let plane = self.plane();
+        if plane.signed_distance(point).abs() > PLANE_EPSILON {
+            return false;
+        }
+
+        // Vertices wind counter-clockwise seen from the front, so the
+        // interior lies to the left of every edge
+        let normal = plane.normal();
+        let n = self.vertices.len();
+        (0..n).all(|i| {
+            let a = self.vertices[i];
+            let ab = self.vertices[(i + 1) % n] - a;
+            ab.cross(&(point - a)).dot(&normal) >= -epsilon * ab.norm()
+        })
+    }
+
+    /// Returns a key identifying this polygon up to vertex rotation, for
+    /// canonical sorting, deduplication and `BTreeMap`/`HashMap` grouping.
+    ///
+    /// Each vertex is snapped to a grid of spacing `quantize`, and the
+    /// vertex list is rotated so that the lexicographically smallest snapped
+    /// vertex comes first. Cyclic rotations of a polygon get the same key;
+    /// reversing the winding gives a different one.
+    ///
+    /// # Panics
+    /// Panics if `quantize` is not positive.
+    pub fn canonical_key(&self, quantize: f32) -> Vec<[i64; 3]> {
+        assert!(quantize > 0.0, "quantize must be positive");
+
+        let snapped: Vec<[i64; 3]> = self
+            .vertices
+            .iter()
+            .map(|v| v.coords.map(|c| (c / quantize).round() as i64).into())
+            .collect();
+        let start = (0..snapped.len())
+            .min_by_key(|&i| snapped[i])
+            .expect("Polygon must have at least 3 vertices");
+
+        let mut key = snapped;
+        key.rotate_left(start);
+        key
+    }
+}
+
+impl<T: Clone> Polygon<T> {
+    /// Cuts this polygon by a slab of thickness `2 * half_thickness` centered
+    /// on `plane`.
+    ///
+    /// Returns `(front, middle, back)` where `middle` is the part lying
+    /// within `half_thickness` of the plane, and `front`/`back` are the parts
+    /// beyond the slab on either side. Any part may be `None` if empty.
+    pub fn cut_slab(
+        &self,
+        plane: &Plane3D,
+        half_thickness: f32,
+    ) -> (Option<Self>, Option<Self>, Option<Self>) {
+        let upper = Plane3D::new(plane.normal(), plane.offset() + half_thickness);
+        let lower = Plane3D::new(plane.normal(), plane.offset() - half_thickness);
+
+        let (front, rest) = self.cut(&upper);
+        let (middle, back) = match rest {
+            Some(rest) => rest.cut(&lower),
+            None => (None, None),
+        };
+
+        (front, middle, back)
+    }
+
+    /// Splits this polygon through its centroid into two convex halves.
+    ///
+    /// The cut is perpendicular to the polygon's longest axis, taken as the
+    /// edge direction along which the polygon is widest. Repeated bisection
+    /// gives evenly sized pieces, e.g. for building deep, balanced test trees.
+    ///
+    /// # Panics
+    /// Panics if the polygon is degenerate and has no extent to cut.
+    pub fn bisect(&self) -> (Polygon<T>, Polygon<T>) {
+        let n = self.vertices.len();
+        let mut axis = None;
+        let mut widest = 0.0;
+        for i in 0..n {
+            let edge = self.vertices[(i + 1) % n] - self.vertices[i];
+            let Some(direction) = edge.try_normalize(f32::EPSILON) else {
+                continue;
+            };
+            let projections = self.vertices.iter().map(|v| v.coords.dot(&direction));
+            let min = projections.clone().fold(f32::MAX, f32::min);
+            let max = projections.fold(f32::MIN, f32::max);
+            if max - min > widest {
+                widest = max - min;
+                axis = Some(direction);
+            }
+        }
+        let axis = axis.expect("cannot bisect a degenerate polygon");
+
+        let plane = Plane3D::from_point_and_normal(self.centroid(), axis);
+        match self.cut(&plane) {
+            (Some(front), Some(back)) => (front, back),
+            _ => panic!("cannot bisect a degenerate polygon"),
+        }
+    }
+
+    /// Reflects this polygon across `mirror`.
+    ///
+    /// The vertex order is reversed so the winding stays counter-clockwise
+    /// seen from the front, i.e. the normal is the reflected normal.
+    pub fn mirrored_across(&self, mirror: &Plane3D) -> Polygon<T> {
+        let vertices = self
+            .vertices
+            .iter()
+            .rev()
+            .map(|v| mirror.reflect_point(*v))
+            .collect();
+        let mut mirrored = match self.plane {
+            Some(ref plane) => Polygon::with_plane(vertices, plane.mirrored_across(mirror)),
+            None => Polygon::new(vertices),
+        }
+        .with_data(self.data.clone());
+        mirrored.uvs = self.reversed_uvs();
+        mirrored
+    }
+
+    /// Moves this polygon by a rigid motion.
+    ///
+    /// The winding is kept, so the normal is the rotated normal, and texture
+    /// coordinates are carried over unchanged.
+    pub fn transformed(&self, iso: &Isometry3<f32>) -> Polygon<T> {
+        let vertices = self.vertices.iter().map(|v| iso * v).collect();
+        let mut transformed = match self.plane {
+            Some(ref plane) => Polygon::with_plane(vertices, plane.transformed(iso)),
+            None => Polygon::new(vertices),
+        }
+        .with_data(self.data.clone());
+        transformed.uvs = self.uvs.clone();
+        transformed
+    }
+
+    /// Returns this polygon facing the other way.
+    ///
+    /// The vertex order is reversed, so [`unit_normal`](Self::unit_normal)
+    /// points the opposite way, and an inherited plane is flipped with it.
+    pub fn flipped(&self) -> Polygon<T> {
+        let vertices = self.vertices.iter().rev().copied().collect();
+        let mut flipped =
+            Polygon::with_plane(vertices, self.plane().flipped()).with_data(self.data.clone());
+        flipped.uvs = self.reversed_uvs();
+        flipped
+    }
+
+    /// Returns a copy of this polygon without redundant vertices.
+    ///
+    /// A vertex is removed when the edges into and out of it deviate by less
+    /// than `angle_epsilon` radians (i.e. it lies on a straight edge), or when
+    /// it coincides with its predecessor. Removal repeats until no such vertex
+    /// remains. If fewer than three vertices would be left, the polygon is
+    /// returned unchanged.
+    pub fn simplified(&self, angle_epsilon: f32) -> Polygon<T> {
+        let mut vertices = self.vertices.clone();
+        let mut uvs = self.uvs.clone();
+
+        let mut k = 0;
+        while k < vertices.len() && vertices.len() >= 3 {
+            let n = vertices.len();
+            let prev = vertices[(k + n - 1) % n];
+            let next = vertices[(k + 1) % n];
+            let incoming = vertices[k] - prev;
+            let outgoing = next - vertices[k];
+
+            let redundant = incoming.norm() <= f32::EPSILON
+                || outgoing.norm() <= f32::EPSILON
+                || incoming.angle(&outgoing) < angle_epsilon;
+
+            if redundant {
+                vertices.remove(k);
+                if let Some(ref mut uvs) = uvs {
+                    uvs.remove(k);
+                }
+                // Both neighbors have changed; re-check them, wrapping around
+                // to vertex 0 after removing the last vertex
+                k = if k == vertices.len() {
+                    0
+                } else {
+                    k.saturating_sub(1)
+                };
+            } else {
+                k += 1;
+            }
+        }
+        if vertices.len() < 3 {
+            return self.clone();
+        }
+
+        Polygon {
+            vertices,
+            plane: self.plane.clone(),
+            uvs,
+            data: self.data.clone(),
+        }
+    }
+
+    /// Returns a copy of this polygon without duplicate or collinear
+    /// vertices, or `None` if fewer than three vertices remain.
+    ///
+    /// Uses the default [`PLANE_EPSILON`] tolerance, see
+    /// [`cleaned_with_epsilon`](Self::cleaned_with_epsilon).
+    pub fn cleaned(&self) -> Option<Polygon<T>> {
+        self.cleaned_with_epsilon(PLANE_EPSILON)
+    }
+
+    /// Returns a copy of this polygon without duplicate or collinear
+    /// vertices, or `None` if fewer than three vertices remain.
+    ///
+    /// A vertex is removed when it lies within `epsilon` of the line through
+    /// its neighbors, which covers vertices within `epsilon` of either
+    /// neighbor. Unlike [`simplified`](Self::simplified), the tolerance is a
+    /// distance, so it matches the one used to classify and cut polygons.
+    pub fn cleaned_with_epsilon(&self, epsilon: f32) -> Option<Polygon<T>> {
+        let mut vertices = self.vertices.clone();
+        let mut uvs = self.uvs.clone();
+
+        // Walk the loop cyclically, so the last vertex and vertex 0 are
+        // checked as neighbors too, until every vertex has been kept since
+        // the last removal
+        let mut k = 0;
+        let mut kept = 0;
+        while kept < vertices.len() && vertices.len() >= 3 {
+            let n = vertices.len();
+            k %= n;
+            let prev = vertices[(k + n - 1) % n];
+            let next = vertices[(k + 1) % n];
+            let chord = next - prev;
+            let offset = vertices[k] - prev;
+
+            let redundant = if chord.norm() <= epsilon {
+                // Both neighbors coincide, so the vertex is a spike
+                true
+            } else {
+                offset.cross(&chord).norm() / chord.norm() <= epsilon
+            };
+
+            if redundant {
+                vertices.remove(k);
+                if let Some(ref mut uvs) = uvs {
+                    uvs.remove(k);
+                }
+                // The previous vertex has a new neighbor; re-check it
+                k = (k + n - 2) % (n - 1);
+                kept = 0;
+            } else {
+                k += 1;
+                kept += 1;
+            }
+        }
+
+        (vertices.len() >= 3).then(|| Polygon {
+            vertices,
+            plane: self.plane.clone(),
+            uvs,
+            data: self.data.clone(),
+        })
+    }
+
+    /// Merges this polygon with another that shares a full edge.
+    ///
+    /// Returns the combined polygon if both lie on the same plane (within
+    /// `epsilon`) and face the same way, `other` contains one of this
+    /// polygon's edges in reverse order, and the union is convex. Vertices
+    /// that become collinear at the ends of the shared edge are dropped.
+    /// Returns `None` otherwise, including when the two payloads differ. The
+    /// result keeps texture coordinates only if both polygons have them.
+    pub fn merge_with(&self, other: &Polygon<T>, epsilon: f32) -> Option<Polygon<T>>
+    where
+        T: PartialEq,
+    {
+        if self.data != other.data {
+            return None;
+        }
+        let plane = self.plane();
+        let facing = other.unit_normal()?.dot(&plane.normal());
+        if facing <= 0.0
+            || other
+                .vertices
+                .iter()
+                .any(|v| plane.signed_distance(*v).abs() > epsilon)
+        {
+            return None;
+        }
+
+        let close = |p: &Point3<f32>, q: &Point3<f32>| (p - q).norm() <= epsilon;
+        let n = self.vertices.len();
+        let m = other.vertices.len();
+
+        // Find edge a -> b in self matching edge b -> a in other
+        let (i, j) = (0..n).find_map(|i| {
+            let a = &self.vertices[i];
+            let b = &self.vertices[(i + 1) % n];
+            (0..m)
+                .find(|&j| close(&other.vertices[j], b) && close(&other.vertices[(j + 1) % m], a))
+                .map(|j| (i, j))
+        })?;
+
+        // Walk self from b around to a, then other past the shared edge
+        let self_walk = (1..=n).map(|k| (i + k) % n);
+        let other_walk = (2..m).map(|k| (j + k) % m);
+        let mut merged: Vec<Point3<f32>> = self_walk.clone().map(|k| self.vertices[k]).collect();
+        merged.extend(other_walk.clone().map(|k| other.vertices[k]));
+        // Texture coordinates survive only if both polygons have them
+        let merged_uvs = self.uvs().zip(other.uvs()).map(|(uvs, other_uvs)| {
+            let mut merged_uvs: Vec<Vector2<f32>> = self_walk.map(|k| uvs[k]).collect();
+            merged_uvs.extend(other_walk.map(|k| other_uvs[k]));
+            merged_uvs
+        });
+
+        let normal = plane.normal();
+        let mut result = Vec::with_capacity(merged.len());
+        let mut kept = Vec::with_capacity(merged.len());
+        let count = merged.len();
+        for k in 0..count {
+            let prev = merged[(k + count - 1) % count];
+            let current = merged[k];
+            let next = merged[(k + 1) % count];
+            let turn = (current - prev).cross(&(next - current)).dot(&normal);
+            if turn < -epsilon {
+                return None;
+            }
+            if turn > epsilon {
+                result.push(current);
+                kept.push(k);
+            }
+        }
+
+        if result.len() < 3 {
+            return None;
+        }
+        let mut polygon = Polygon::with_plane(result, plane).with_data(self.data.clone());
+        polygon.uvs = merged_uvs.map(|uvs| kept.iter().map(|&k| uvs[k]).collect());
+        Some(polygon)
+    }
+}
+
+/// Joins polygons that share a full edge into larger convex polygons.
+///
+/// Pairs are merged with [`Polygon::merge_with`] until no two polygons
+/// merge any more, so only polygons on the same plane (within `epsilon`),
+/// facing the same way, with equal payloads and with a convex union are
+/// joined. Polygons that
+/// merge with nothing are returned unchanged. Useful after CSG and
+/// splitting, which leave many coplanar fragments along each face.
+///
+/// ```
+/// use bsp_tree::{merge_coplanar, Polygon, PLANE_EPSILON};
+/// use nalgebra::Point3;
+///
+/// // A unit square cut into two triangles
+/// let a = Polygon::new(vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(1.0, 1.0, 0.0),
+/// ]);
+/// let b = Polygon::new(vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 1.0, 0.0),
+///     Point3::new(0.0, 1.0, 0.0),
+/// ]);
+///
+/// let merged = merge_coplanar(vec![a, b], PLANE_EPSILON);
+/// assert_eq!(merged.len(), 1);
+/// assert_eq!(merged[0].len(), 4);
+/// ```
+pub fn merge_coplanar<T: Clone + PartialEq>(
+    mut polygons: Vec<Polygon<T>>,
+    epsilon: f32,
+) -> Vec<Polygon<T>> {
+    loop {
+        let count = polygons.len();
+        let mut i = 0;
+        while i < polygons.len() {
+            // Grow polygon `i` with every later polygon it merges with,
+            // carrying on from each merge rather than starting over
+            let mut j = i + 1;
+            while j < polygons.len() {
+                match polygons[i].merge_with(&polygons[j], epsilon) {
+                    Some(merged) => {
+                        polygons[i] = merged;
+                        polygons.remove(j);
+                    }
+                    None => j += 1,
+                }
+            }
+            i += 1;
+        }
+        // Polygons passed over may merge with a grown polygon, so repeat
+        // until a pass merges nothing
+        if polygons.len() == count {
+            return polygons;
+        }
+    }
+}
+
+/// Computes the Newell normal of a closed vertex loop: the sum of the cross
+/// products over all edges, taken relative to the first vertex for precision
+/// away from the origin.
+pub(crate) fn newell_normal(vertices: &[Point3<f32>]) -> Vector3<f32> {
+    let Some(&origin) = vertices.first() else {
+        return Vector3::zeros();
+    };
+    let next = vertices.iter().cycle().skip(1);
+    vertices
+        .iter()
+        .zip(next)
+        .map(|(a, b)| (a - origin).cross(&(b - origin)))
+        .sum()
+}
+
+/// Returns the squared length of the longest edge of a closed vertex loop.
+fn longest_edge_squared(vertices: &[Point3<f32>]) -> f32 {
+    let next = vertices.iter().cycle().skip(1);
+    vertices
+        .iter()
+        .zip(next)
+        .map(|(a, b)| (b - a).norm_squared())
+        .fold(0.0, f32::max)
+}
+
+impl<T> PartialEq for Polygon<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vertices == other.vertices
+    }
 }
 
 impl From<Triangle> for Polygon {
     fn from(triangle: Triangle) -> Self {
         Self {
             vertices: triangle.vertices().to_vec(),
+            plane: None,
+            uvs: triangle.uvs().map(|uvs| uvs.to_vec()),
+            data: (),
         }
     }
 }
@@ -145,6 +1069,9 @@ impl From<&Triangle> for Polygon {
     fn from(triangle: &Triangle) -> Self {
         Self {
             vertices: triangle.vertices().to_vec(),
+            plane: None,
+            uvs: triangle.uvs().map(|uvs| uvs.to_vec()),
+            data: (),
         }
     }
 }
@@ -153,6 +1080,9 @@ impl From<Rectangle> for Polygon {
     fn from(rectangle: Rectangle) -> Self {
         Self {
             vertices: rectangle.vertices().to_vec(),
+            plane: None,
+            uvs: None,
+            data: (),
         }
     }
 }
@@ -161,18 +1091,914 @@ impl From<&Rectangle> for Polygon {
     fn from(rectangle: &Rectangle) -> Self {
         Self {
             vertices: rectangle.vertices().to_vec(),
+            plane: None,
+            uvs: None,
+            data: (),
         }
     }
 }
 
-impl From<Polygon> for Plane3D {
-    fn from(polygon: Polygon) -> Self {
+/// Panics for polygons without a plane; use [`Polygon::try_plane`] when the
+/// polygon may be degenerate.
+impl<T> From<Polygon<T>> for Plane3D {
+    fn from(polygon: Polygon<T>) -> Self {
         polygon.plane()
     }
 }
 
-impl From<&Polygon> for Plane3D {
-    fn from(polygon: &Polygon) -> Self {
+/// Panics for polygons without a plane; use [`Polygon::try_plane`] when the
+/// polygon may be degenerate.
+impl<T> From<&Polygon<T>> for Plane3D {
+    fn from(polygon: &Polygon<T>) -> Self {
         polygon.plane()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PLANE_EPSILON;
+
+    #[test]
+    fn ray_intersect_hits_inside_and_misses_outside() {
+        let square = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ]);
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        assert_eq!(square.ray_intersect(Point3::new(1.0, 1.0, 3.0), down), Some(3.0));
+        assert_eq!(square.ray_intersect(Point3::new(1.0, 1.0, -3.0), -down), Some(3.0));
+        assert_eq!(square.ray_intersect(Point3::new(2.0, 1.0, 3.0), down), Some(3.0));
+        assert_eq!(square.ray_intersect(Point3::new(3.0, 1.0, 3.0), down), None);
+        assert_eq!(square.ray_intersect(Point3::new(1.0, 1.0, -3.0), down), None);
+    }
+
+    /// Builds a polygon without the convexity check of [`Polygon::new`].
+    fn unchecked(vertices: &[[f32; 3]]) -> Polygon {
+        let vertices: Vec<Point3<f32>> = vertices.iter().map(|&v| v.into()).collect();
+        let plane = Plane3D::from_point_and_normal(vertices[0], newell_normal(&vertices));
+        Polygon::with_plane(vertices, plane)
+    }
+
+    #[test]
+    fn try_new_reports_each_problem() {
+        let points = |vertices: &[[f32; 3]]| -> Vec<Point3<f32>> {
+            vertices.iter().map(|&v| v.into()).collect()
+        };
+
+        let too_few = Polygon::try_new(points(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]]));
+        assert_eq!(too_few, Err(PolygonError::TooFewVertices { count: 2 }));
+
+        let collinear = points(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [3.0, 0.0, 0.0]]);
+        assert_eq!(Polygon::try_new(collinear), Err(PolygonError::Degenerate));
+
+        let warped = points(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 0.0],
+        ]);
+        assert_eq!(Polygon::try_new(warped), Err(PolygonError::NotCoplanar));
+
+        let dart = points(&[
+            [0.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [0.0, 2.0, 0.0],
+            [0.5, 1.0, 0.0],
+        ]);
+        assert_eq!(Polygon::try_new(dart), Err(PolygonError::NotConvex));
+
+        let square = points(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ]);
+        let polygon = Polygon::try_new(square.clone()).unwrap();
+        assert_eq!(polygon, Polygon::new(square));
+        assert_eq!(polygon.plane().normal(), Vector3::z());
+    }
+
+    #[test]
+    fn try_new_with_epsilon_accepts_and_flattens_rounded_quads() {
+        // A quad far from the origin with one corner lifted by rounding
+        let quad = vec![
+            Point3::new(1000.0, 0.0, 0.0),
+            Point3::new(1010.0, 0.0, 0.0),
+            Point3::new(1010.0, 10.0, 0.0005),
+            Point3::new(1000.0, 10.0, 0.0),
+        ];
+        assert_eq!(
+            Polygon::try_new(quad.clone()),
+            Err(PolygonError::NotCoplanar)
+        );
+
+        let polygon = Polygon::try_new_with_epsilon(quad.clone(), 1e-3).unwrap();
+        let plane = polygon.plane();
+        for vertex in polygon.vertices() {
+            assert!(plane.signed_distance(*vertex).abs() <= PLANE_EPSILON);
+        }
+        for (kept, original) in polygon.vertices().iter().zip(&quad) {
+            assert!((kept - original).norm() <= 1e-3);
+        }
+
+        // The flattened quad splits without tripping the on-plane checks
+        let cut = Plane3D::new(Vector3::x(), 1005.0);
+        let (front, back) = polygon.cut(&cut);
+        assert!(front.is_some() && back.is_some());
+    }
+
+    #[test]
+    fn is_convex_accepts_collinear_vertices() {
+        let square = unchecked(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 2.0, 0.0],
+            [0.0, 2.0, 0.0],
+        ]);
+        assert!(square.is_convex());
+        assert!(square.flipped().is_convex());
+    }
+
+    #[test]
+    fn is_convex_rejects_dents_and_stars() {
+        let dart = unchecked(&[
+            [0.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [0.0, 2.0, 0.0],
+            [0.5, 1.0, 0.0],
+        ]);
+        assert!(!dart.is_convex());
+
+        let star: Vec<[f32; 3]> = (0..5)
+            .map(|k| {
+                let angle = (k * 2) as f32 * std::f32::consts::TAU / 5.0;
+                [angle.cos(), angle.sin(), 1.0]
+            })
+            .collect();
+        assert!(!unchecked(&star).is_convex());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Polygon must be convex")]
+    fn new_panics_on_non_convex_vertices() {
+        Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.5, 1.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn normal_ignores_collinear_leading_vertices() {
+        // The first three vertices lie on the bottom edge
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 3.0),
+            Point3::new(1.0, 0.0, 3.0),
+            Point3::new(2.0, 0.0, 3.0),
+            Point3::new(2.0, 2.0, 3.0),
+            Point3::new(0.0, 2.0, 3.0),
+        ]);
+
+        assert_eq!(polygon.normal(), Vector3::new(0.0, 0.0, 8.0));
+        assert_eq!(polygon.unit_normal(), Some(Vector3::z()));
+        let plane = polygon.plane();
+        assert_eq!(plane.normal(), Vector3::z());
+        assert!((plane.offset() - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn normal_averages_vertex_noise() {
+        // A unit square far from the origin with one corner lifted slightly
+        let polygon = Polygon::new(vec![
+            Point3::new(100.0, 100.0, 0.0),
+            Point3::new(101.0, 100.0, 0.0),
+            Point3::new(101.0, 101.0, 5e-5),
+            Point3::new(100.0, 101.0, 0.0),
+        ]);
+        let plane = polygon.plane();
+
+        assert!(plane.normal().z > 0.999_999);
+        for &v in polygon.vertices() {
+            assert!(plane.signed_distance(v).abs() < 5e-5);
+        }
+    }
+
+    #[test]
+    fn area_of_planar_polygons() {
+        // A 2x3 rectangle tilted out of the XY plane and moved off the origin
+        let rectangle = Polygon::new(vec![
+            Point3::new(5.0, 1.0, 1.0),
+            Point3::new(7.0, 1.0, 1.0),
+            Point3::new(7.0, 1.0 + 3.0 * 0.6, 1.0 + 3.0 * 0.8),
+            Point3::new(5.0, 1.0 + 3.0 * 0.6, 1.0 + 3.0 * 0.8),
+        ]);
+        assert!((rectangle.area() - 6.0).abs() < 1e-4);
+
+        let reversed = Polygon::new(rectangle.vertices().iter().rev().copied().collect());
+        assert_eq!(reversed.area(), rectangle.area());
+
+        let triangle = Triangle::new(
+            Point3::new(1.0, 1.0, 2.0),
+            Point3::new(1.0, 4.0, 2.0),
+            Point3::new(1.0, 1.0, 6.0),
+        );
+        assert!((triangle.area() - 6.0).abs() < 1e-5);
+        assert!((Polygon::from(&triangle).area() - 6.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn triangulate_fans_from_first_vertex() {
+        let pentagon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(3.0, 1.0, 0.0),
+            Point3::new(1.0, 2.0, 0.0),
+            Point3::new(-1.0, 1.0, 0.0),
+        ]);
+        let triangles = pentagon.triangulate();
+
+        assert_eq!(triangles.len(), 3);
+        for (i, triangle) in triangles.iter().enumerate() {
+            let vertices = pentagon.vertices();
+            let expected = [vertices[0], vertices[i + 1], vertices[i + 2]];
+            assert_eq!(triangle.vertices(), &expected);
+            assert!(triangle.unit_normal().unwrap().z > 0.99);
+        }
+    }
+
+    #[test]
+    fn triangulate_drops_zero_area_slivers() {
+        // The last vertex sits on the edge from the fourth back to the first
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let triangles = polygon.triangulate();
+
+        assert_eq!(triangles.len(), 2);
+        assert!(triangles.iter().all(|t| t.unit_normal().is_some()));
+    }
+
+    #[test]
+    fn triangulate_keeps_uvs() {
+        let square = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ])
+        .with_uvs(vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(1.0, 1.0),
+            Vector2::new(0.0, 1.0),
+        ]);
+
+        let triangles = square.triangulate();
+
+        assert_eq!(triangles.len(), 2);
+        let uvs = square.uvs().unwrap();
+        assert_eq!(triangles[0].uvs(), Some(&[uvs[0], uvs[1], uvs[2]]));
+        assert_eq!(triangles[1].uvs(), Some(&[uvs[0], uvs[2], uvs[3]]));
+        let polygon = Polygon::from(&triangles[1]);
+        assert_eq!(polygon.uvs(), Some(&[uvs[0], uvs[2], uvs[3]][..]));
+    }
+
+    #[test]
+    fn canonical_key_ignores_rotation() {
+        let a = Point3::new(0.0, 0.0, 0.0);
+        let b = Point3::new(1.0, 0.0, 0.0);
+        let c = Point3::new(1.0, 1.0, 0.0);
+        let d = Point3::new(0.0, 1.0, 0.0);
+        let polygon = Polygon::new(vec![a, b, c, d]);
+        let rotated = Polygon::new(vec![c, d, a, b]);
+        let jittered = Polygon::new(vec![d, a + Vector3::new(1e-4, 0.0, 0.0), b, c]);
+        let other = Polygon::new(vec![a, b, c]);
+
+        let key = polygon.canonical_key(1e-3);
+        assert_eq!(rotated.canonical_key(1e-3), key);
+        assert_eq!(jittered.canonical_key(1e-3), key);
+        assert_ne!(other.canonical_key(1e-3), key);
+        assert_ne!(polygon.flipped().canonical_key(1e-3), key);
+    }
+
+    #[test]
+    fn contains_point_inside_edges_and_plane() {
+        let square = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(2.0, 0.0, 1.0),
+            Point3::new(2.0, 2.0, 1.0),
+            Point3::new(0.0, 2.0, 1.0),
+        ]);
+
+        assert!(square.contains_point(Point3::new(1.0, 1.0, 1.0)));
+        assert!(square.contains_point(Point3::new(2.0, 1.0, 1.0)));
+        assert!(square.contains_point(Point3::new(0.0, 0.0, 1.0)));
+        assert!(square.contains_point(Point3::new(1.0, 1.0, 1.0 + PLANE_EPSILON / 2.0)));
+        assert!(!square.contains_point(Point3::new(1.0, 1.0, 1.1)));
+        assert!(!square.contains_point(Point3::new(2.5, 1.0, 1.0)));
+        // The winding does not matter once the polygon faces the other way
+        assert!(square.flipped().contains_point(Point3::new(1.5, 0.5, 1.0)));
+    }
+
+    #[test]
+    fn contains_point_with_epsilon_accepts_near_edge() {
+        let triangle = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let just_outside = Point3::new(0.5, -1e-3, 0.0);
+
+        assert!(!triangle.contains_point(just_outside));
+        assert!(triangle.contains_point_with_epsilon(just_outside, 1e-2));
+        assert!(!triangle.contains_point_with_epsilon(just_outside, 1e-4));
+    }
+
+    #[test]
+    fn ray_intersect_parallel_ray_misses() {
+        let triangle = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let along = Vector3::new(1.0, 0.0, 0.0);
+
+        assert_eq!(triangle.ray_intersect(Point3::new(-1.0, 0.2, 0.0), along), None);
+        assert_eq!(triangle.ray_intersect(Point3::new(-1.0, 0.2, 1.0), along), None);
+    }
+
+    #[test]
+    fn classify_stable_ignores_jittered_vertex() {
+        // Triangle on the XY plane with one vertex nudged just past epsilon
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 2e-4),
+        ]);
+        let plane = Plane3D::new(Vector3::new(0.0, 0.0, 1.0), 0.0);
+
+        assert_eq!(polygon.classify(&plane), Classification::Front);
+        assert_eq!(
+            polygon.classify_stable(&plane, PLANE_EPSILON),
+            Classification::Coplanar
+        );
+    }
+
+    #[test]
+    fn classify_with_epsilon_widens_on_plane_band() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, -0.01),
+            Point3::new(1.0, 0.0, 0.01),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let plane = Plane3D::new(Vector3::new(0.0, 0.0, 1.0), 0.0);
+
+        assert_eq!(polygon.classify(&plane), Classification::Spanning);
+        assert_eq!(
+            polygon.classify_with_epsilon(&plane, 0.1),
+            Classification::Coplanar
+        );
+        assert_eq!(
+            polygon.classify_with_epsilon(&plane, 0.0),
+            polygon.classify(&Plane3D::new(Vector3::new(0.0, 0.0, 1.0), 0.0))
+        );
+    }
+
+    #[test]
+    fn classify_stable_still_detects_spanning() {
+        // Perpendicular polygon whose mean distance is zero must not be coplanar
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ]);
+        let plane = Plane3D::new(Vector3::new(0.0, 0.0, 1.0), 0.0);
+
+        assert_eq!(
+            polygon.classify_stable(&plane, PLANE_EPSILON),
+            Classification::Spanning
+        );
+    }
+
+    #[test]
+    fn cut_slab_yields_middle_band() {
+        // Triangle spanning y in [-2, 2], slab of half-thickness 0.5 around y = 0
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(-1.0, -2.0, 0.0),
+            Point3::new(1.0, -2.0, 0.0),
+        ]);
+        let plane = Plane3D::new(Vector3::new(0.0, 1.0, 0.0), 0.0);
+
+        let (front, middle, back) = polygon.cut_slab(&plane, 0.5);
+
+        assert!(front.is_some());
+        assert!(back.is_some());
+        let middle = middle.expect("slab should contain a middle band");
+        assert_eq!(middle.len(), 4);
+
+        let ys: Vec<f32> = middle.vertices().iter().map(|v| v.y).collect();
+        let min_y = ys.iter().copied().fold(f32::MAX, f32::min);
+        let max_y = ys.iter().copied().fold(f32::MIN, f32::max);
+        assert!((min_y + 0.5).abs() < 1e-5);
+        assert!((max_y - 0.5).abs() < 1e-5);
+        assert!((max_y - min_y - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn cut_slab_polygon_inside_slab() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.2, 0.0),
+            Point3::new(-1.0, -0.2, 0.0),
+            Point3::new(1.0, -0.2, 0.0),
+        ]);
+        let plane = Plane3D::new(Vector3::new(0.0, 1.0, 0.0), 0.0);
+
+        let (front, middle, back) = polygon.cut_slab(&plane, 0.5);
+
+        assert!(front.is_none());
+        assert_eq!(middle, Some(polygon));
+        assert!(back.is_none());
+    }
+
+    #[test]
+    fn bisect_rectangle_into_squares() {
+        let area = |polygon: &Polygon| {
+            let v = polygon.vertices();
+            (1..v.len() - 1)
+                .map(|i| (v[i] - v[0]).cross(&(v[i + 1] - v[0])).norm() * 0.5)
+                .sum::<f32>()
+        };
+        let rectangle = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        let (a, b) = rectangle.bisect();
+
+        for half in [&a, &b] {
+            assert!((area(half) - 1.0).abs() < 1e-5);
+            let xs: Vec<f32> = half.vertices().iter().map(|v| v.x).collect();
+            let width = xs.iter().copied().fold(f32::MIN, f32::max)
+                - xs.iter().copied().fold(f32::MAX, f32::min);
+            assert!((width - 1.0).abs() < 1e-5);
+        }
+        assert!((area(&a) + area(&b) - area(&rectangle)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn mirrored_across_reflects_normal() {
+        let polygon = Polygon::new(vec![
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ]);
+        let mirror = Plane3D::new(Vector3::new(1.0, 0.0, 0.0), 0.0);
+
+        let mirrored = polygon.mirrored_across(&mirror);
+
+        assert!(mirrored.vertices().contains(&Point3::new(-2.0, 0.0, 0.0)));
+        assert!(mirrored.vertices().contains(&Point3::new(-1.0, 1.0, 0.0)));
+        // A normal parallel to the mirror is unchanged
+        let normal = mirrored.unit_normal().unwrap();
+        assert!((normal - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn try_plane_is_none_for_slivers() {
+        let sliver = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+        ]);
+        assert!(sliver.try_plane().is_none());
+
+        // Collinear leading vertices are fine as long as there is area
+        let quad = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+        ]);
+        let plane = quad.try_plane().unwrap();
+        assert_eq!(plane.normal(), Vector3::z());
+        assert_eq!(Some(plane), Some(quad.plane()));
+    }
+
+    #[test]
+    fn try_plane_threshold_scales_with_edge_lengths() {
+        // Far from collinear, just small
+        let tiny = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1e-4, 0.0, 0.0),
+            Point3::new(0.0, 1e-4, 0.0),
+        ]);
+        assert_eq!(tiny.try_plane().unwrap().normal(), Vector3::z());
+
+        // Collinear up to rounding at a large scale
+        let sliver = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1000.0, 0.0, 0.0),
+            Point3::new(2000.0, 1e-6, 0.0),
+        ]);
+        assert!(sliver.try_plane().is_none());
+    }
+
+    #[test]
+    fn transformed_rotates_normal_and_keeps_uvs() {
+        let polygon = with_planar_uvs(Polygon::new(vec![
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+        ]));
+        let iso = Isometry3::new(
+            Vector3::new(0.0, 0.0, 2.0),
+            Vector3::new(std::f32::consts::FRAC_PI_2, 0.0, 0.0),
+        );
+
+        let moved = polygon.transformed(&iso);
+
+        // A quarter turn about x takes +z to -y
+        let normal = moved.unit_normal().unwrap();
+        assert!((normal - Vector3::new(0.0, -1.0, 0.0)).norm() < 1e-6);
+        assert!((moved.vertices()[0] - Point3::new(0.0, -1.0, 2.0)).norm() < 1e-6);
+        assert_eq!(moved.uvs(), polygon.uvs());
+        assert!((moved.area() - polygon.area()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn edges_form_a_closed_loop() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        let edges: Vec<_> = polygon.edges().collect();
+        assert_eq!(
+            edges,
+            vec![
+                (Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0)),
+                (Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 0.0)),
+                (Point3::new(0.0, 1.0, 0.0), Point3::new(0.0, 0.0, 0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn flipped_reverses_normal() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+        ]);
+
+        let flipped = polygon.flipped();
+
+        let normal = polygon.unit_normal().unwrap();
+        assert_eq!(flipped.unit_normal().unwrap(), -normal);
+        assert_eq!(flipped.plane().offset(), -1.0);
+        assert_eq!(flipped.flipped(), polygon);
+    }
+
+    #[test]
+    fn merge_with_adjacent_squares_forms_rectangle() {
+        let left = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let right = Polygon::new(vec![
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ]);
+
+        let merged = left.merge_with(&right, PLANE_EPSILON).unwrap();
+
+        assert_eq!(merged.len(), 4);
+        for corner in [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ] {
+            assert!(merged.vertices().contains(&corner));
+        }
+        assert!(merged.unit_normal().unwrap().z > 0.0);
+    }
+
+    /// Gives a polygon in the z = 0 plane the texture coordinates (x, y).
+    fn with_planar_uvs(polygon: Polygon) -> Polygon {
+        let uvs = polygon.vertices().iter().map(|v| v.xy().coords).collect();
+        polygon.with_uvs(uvs)
+    }
+
+    fn assert_planar_uvs(polygon: &Polygon) {
+        let uvs = polygon.uvs().expect("texture coordinates were dropped");
+        assert_eq!(uvs.len(), polygon.len());
+        for (vertex, uv) in polygon.vertices().iter().zip(uvs) {
+            assert_eq!(*uv, vertex.xy().coords);
+        }
+    }
+
+    #[test]
+    fn uvs_stay_in_step_with_vertices() {
+        let left = with_planar_uvs(Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.5, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]));
+        let right = with_planar_uvs(Polygon::new(vec![
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ]));
+
+        assert_planar_uvs(&left.flipped());
+        assert_planar_uvs(&left.simplified(1e-3));
+        let cleaned = left.cleaned().unwrap();
+        assert_eq!(cleaned.len(), 4);
+        assert_planar_uvs(&cleaned);
+        assert_planar_uvs(&left.merge_with(&right, PLANE_EPSILON).unwrap());
+
+        // Merging with a polygon without texture coordinates drops them
+        let bare = Polygon::new(right.vertices().to_vec());
+        let merged = left.merge_with(&bare, PLANE_EPSILON).unwrap();
+        assert!(merged.uvs().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "one texture coordinate per vertex")]
+    fn with_uvs_requires_one_per_vertex() {
+        let _ = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ])
+        .with_uvs(vec![Vector2::zeros()]);
+    }
+
+    /// Unit square in the z = 0 plane with its minimum corner at (x, y).
+    fn unit_square(x: f32, y: f32) -> Polygon {
+        Polygon::new(vec![
+            Point3::new(x, y, 0.0),
+            Point3::new(x + 1.0, y, 0.0),
+            Point3::new(x + 1.0, y + 1.0, 0.0),
+            Point3::new(x, y + 1.0, 0.0),
+        ])
+    }
+
+    #[test]
+    fn merge_coplanar_joins_grid_into_one_square() {
+        // Listed so that the first two squares only touch at a corner
+        let squares = vec![
+            unit_square(0.0, 0.0),
+            unit_square(1.0, 1.0),
+            unit_square(1.0, 0.0),
+            unit_square(0.0, 1.0),
+        ];
+
+        let merged = merge_coplanar(squares, PLANE_EPSILON);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].len(), 4);
+        assert!((merged[0].area() - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn merge_coplanar_keeps_unions_convex() {
+        // An L of three squares merges into a rectangle and a square
+        let squares = vec![
+            unit_square(0.0, 0.0),
+            unit_square(1.0, 0.0),
+            unit_square(0.0, 1.0),
+        ];
+        let merged = merge_coplanar(squares, PLANE_EPSILON);
+        assert_eq!(merged.len(), 2);
+        let area: f32 = merged.iter().map(Polygon::area).sum();
+        assert!((area - 3.0).abs() < 1e-6);
+
+        // Opposite-facing neighbors are left alone
+        let flipped = vec![unit_square(0.0, 0.0), unit_square(1.0, 0.0).flipped()];
+        assert_eq!(merge_coplanar(flipped, PLANE_EPSILON).len(), 2);
+    }
+
+    #[test]
+    fn merge_with_concave_union_returns_none() {
+        // Quads sharing a full edge whose union has a reflex corner at (1, 1)
+        let square = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        // Quad attached to the right edge, extending upward past the square
+        let skewed = Polygon::new(vec![
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ]);
+
+        assert!(square.merge_with(&skewed, PLANE_EPSILON).is_none());
+    }
+
+    #[test]
+    fn merge_with_requires_shared_edge() {
+        let a = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let b = Polygon::new(vec![
+            Point3::new(3.0, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            Point3::new(3.0, 1.0, 0.0),
+        ]);
+
+        assert!(a.merge_with(&b, PLANE_EPSILON).is_none());
+    }
+
+    #[test]
+    fn merge_with_requires_equal_payloads() {
+        let left = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let right = Polygon::new(vec![
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ]);
+
+        let stone = left.with_data("stone");
+        let glass = right.clone().with_data("glass");
+        assert!(stone.merge_with(&glass, PLANE_EPSILON).is_none());
+        let merged = stone.merge_with(&right.with_data("stone"), PLANE_EPSILON);
+        assert_eq!(merged.map(|p| *p.data()), Some("stone"));
+    }
+
+    #[test]
+    fn simplified_removes_midpoint_vertex() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.5, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        let simplified = polygon.simplified(1e-3);
+
+        assert_eq!(
+            simplified.vertices(),
+            &[
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn simplified_rechecks_vertex_zero_after_removing_the_last() {
+        // The last vertex is a slight bend; without it, vertex 0 lies on the
+        // nearly straight bottom edge
+        let polygon = unchecked(&[
+            [10.1, 0.211, 0.0],
+            [15.0, 0.211, 0.0],
+            [15.0, 5.0, 0.0],
+            [0.0, 5.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [10.0, 0.2, 0.0],
+        ]);
+
+        let simplified = polygon.simplified(0.1);
+
+        assert_eq!(simplified.vertices(), &polygon.vertices()[1..5]);
+    }
+
+    #[test]
+    fn simplified_leaves_slivers_unchanged() {
+        let sliver = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(3.0, 0.0, 0.0),
+        ]);
+
+        assert_eq!(sliver.simplified(1e-3), sliver);
+    }
+
+    #[test]
+    fn simplified_keeps_corners() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        assert_eq!(polygon.simplified(1e-3), polygon);
+    }
+
+    #[test]
+    fn cleaned_removes_collinear_midpoint() {
+        // A triangle with a redundant vertex halfway along its base
+        let quad = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ]);
+        let cleaned = quad.cleaned().unwrap();
+
+        assert_eq!(
+            cleaned.vertices(),
+            &[
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(2.0, 0.0, 0.0),
+                Point3::new(0.0, 2.0, 0.0),
+            ]
+        );
+        assert_eq!(cleaned.unit_normal(), quad.unit_normal());
+    }
+
+    #[test]
+    fn cleaned_removes_near_duplicates_and_degenerates() {
+        let square = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0 + PLANE_EPSILON / 4.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        assert_eq!(square.cleaned().unwrap().len(), 4);
+
+        // The last vertex duplicates vertex 0 across the wrap-around
+        let closed = unchecked(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [PLANE_EPSILON / 4.0, 0.0, 0.0],
+        ]);
+        assert_eq!(closed.cleaned().unwrap().len(), 4);
+
+        // A sliver whose apex is within epsilon of its base
+        let sliver = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.5, PLANE_EPSILON / 2.0, 0.0),
+        ]);
+        assert!(sliver.cleaned().is_none());
+        assert!(sliver.cleaned_with_epsilon(0.0).is_some());
+    }
+
+    #[test]
+    fn closest_point_inside_and_outside() {
+        let square = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        assert_eq!(
+            square.closest_point(Point3::new(0.25, 0.5, 3.0)),
+            Point3::new(0.25, 0.5, 0.0)
+        );
+        assert_eq!(
+            square.closest_point(Point3::new(2.0, 0.5, 1.0)),
+            Point3::new(1.0, 0.5, 0.0)
+        );
+        assert_eq!(
+            square.closest_point(Point3::new(-1.0, -1.0, 0.0)),
+            Point3::new(0.0, 0.0, 0.0)
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 use nalgebra::{Point3, Vector3};
 
-use crate::{Classification, Plane3D, PlaneSide};
+use crate::{Aabb, Classification, Plane3D, PlaneSide, PLANE_EPSILON};
 
 /// A rectangle (quad) in 3D space, defined by a corner and two edge vectors.
 ///
@@ -30,28 +30,91 @@ impl Rectangle {
     ///
     /// The winding order should be: a -> b -> c -> d (counter-clockwise).
     ///
-    /// Internally computes u = b - a and v = d - a.
+    /// Internally computes u = b - a and v = d - a, so `c` must be
+    /// `a + u + v`: the corners must form a parallelogram. Use
+    /// [`Polygon::new`](crate::Polygon::new) for other planar quads.
     ///
     /// # Panics (debug builds only)
-    /// Panics if the points are not coplanar.
+    /// Panics if `c` is further than [`PLANE_EPSILON`] (scaled by the edge
+    /// lengths) from `a + u + v`.
     pub fn from_corners(
         a: Point3<f32>,
         b: Point3<f32>,
         c: Point3<f32>,
         d: Point3<f32>,
     ) -> Self {
+        let u = b - a;
+        let v = d - a;
         debug_assert!(
             {
-                let plane = Plane3D::from_three_points(a, b, d);
-                plane.classify_point(c) == PlaneSide::OnPlane
+                let scale = 1.0f32.max(u.norm()).max(v.norm());
+                (c - (a + u + v)).norm() <= PLANE_EPSILON * scale
             },
-            "Rectangle corners must be coplanar"
+            "Rectangle corners must form a parallelogram"
         );
-        let u = b - a;
-        let v = d - a;
         Self { origin: a, u, v }
     }
 
+    /// Creates a rectangle from its center and two half-edge vectors.
+    ///
+    /// The rectangle spans `center ± u_half ± v_half`, with the normal
+    /// following u_half × v_half.
+    ///
+    /// ```
+    /// use bsp_tree::Rectangle;
+    /// use nalgebra::{Point3, Vector3};
+    ///
+    /// let rect = Rectangle::from_center(Point3::new(1.0, 2.0, 0.0), Vector3::x(), Vector3::y());
+    /// assert_eq!(rect.origin(), Point3::new(0.0, 1.0, 0.0));
+    /// assert_eq!(rect.centroid(), Point3::new(1.0, 2.0, 0.0));
+    /// assert_eq!(rect.area(), 4.0);
+    /// ```
+    pub fn from_center(center: Point3<f32>, u_half: Vector3<f32>, v_half: Vector3<f32>) -> Self {
+        Self {
+            origin: center - u_half - v_half,
+            u: u_half * 2.0,
+            v: v_half * 2.0,
+        }
+    }
+
+    /// Creates a square with side `2 * half_size` centered on `center`,
+    /// facing `normal`.
+    ///
+    /// The edges are picked perpendicular to `normal`, so a normal along a
+    /// coordinate axis gives an axis-aligned square. `normal` need not be
+    /// normalized.
+    ///
+    /// ```
+    /// use bsp_tree::Rectangle;
+    /// use nalgebra::{Point3, Vector3};
+    ///
+    /// let floor = Rectangle::square(Point3::origin(), Vector3::y(), 1.5);
+    /// assert_eq!(floor.unit_normal(), Some(Vector3::y()));
+    /// assert_eq!(floor.area(), 9.0);
+    /// ```
+    ///
+    /// # Panics (debug builds only)
+    /// Panics if `normal` is zero.
+    pub fn square(center: Point3<f32>, normal: Vector3<f32>, half_size: f32) -> Self {
+        debug_assert!(
+            normal.norm() > f32::EPSILON,
+            "Square normal must be non-zero"
+        );
+        let n = normal.normalize();
+        // Cross with the axis least aligned with the normal, which is never
+        // close to parallel to it
+        let axis = if n.x.abs() <= n.y.abs() && n.x.abs() <= n.z.abs() {
+            Vector3::x()
+        } else if n.y.abs() <= n.z.abs() {
+            Vector3::y()
+        } else {
+            Vector3::z()
+        };
+        let u = axis.cross(&n).normalize();
+        let v = n.cross(&u);
+        Self::from_center(center, u * half_size, v * half_size)
+    }
+
     /// Returns the origin corner of the rectangle.
     #[inline]
     pub fn origin(&self) -> Point3<f32> {
@@ -120,6 +183,12 @@ impl Rectangle {
         self.normal().norm()
     }
 
+    /// Computes the bounding box of the rectangle.
+    pub fn aabb(&self) -> Aabb {
+        let [a, b, c, d] = self.vertices();
+        Aabb::new(a, a).including(b).including(c).including(d)
+    }
+
     /// Classifies this rectangle relative to a plane.
     ///
     /// Returns:
@@ -163,3 +232,40 @@ impl From<&Rectangle> for Plane3D {
         rectangle.plane()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polygon;
+
+    #[test]
+    fn rotated_rectangle_corners_round_trip() {
+        let rotation = nalgebra::Rotation3::from_euler_angles(0.3, 0.4, 0.25);
+        let corners = [
+            Point3::new(-0.4, -0.4, 0.4),
+            Point3::new(0.4, -0.4, 0.4),
+            Point3::new(0.4, 0.4, 0.4),
+            Point3::new(-0.4, 0.4, 0.4),
+        ]
+        .map(|p| Point3::new(-1.0, 0.0, 0.0) + rotation * p.coords);
+
+        let polygon: Polygon =
+            Rectangle::from_corners(corners[0], corners[1], corners[2], corners[3]).into();
+        for (vertex, corner) in polygon.vertices().iter().zip(corners) {
+            assert!((vertex - corner).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "parallelogram")]
+    fn rectangle_from_skewed_corners_panics() {
+        // Planar, but the third corner is off a + u + v
+        Rectangle::from_corners(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.5, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Polygonization of signed-distance functions.
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{Aabb, Polygon};
+
+/// The six tetrahedra of a cell, as corner indices, all sharing the diagonal
+/// from corner 0 to corner 7. Corner `i` is offset by one cell along x if bit
+/// 0 is set, along y if bit 1 is set and along z if bit 2 is set.
+///
+/// Every cell is split the same way, so the triangulations of a face shared
+/// by two cells match and the resulting surface has no cracks.
+const TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 1, 5, 7],
+    [0, 2, 3, 7],
+    [0, 2, 6, 7],
+    [0, 4, 5, 7],
+    [0, 4, 6, 7],
+];
+
+/// Extracts the zero isosurface of `sdf` within `bounds` as outward-facing
+/// triangles.
+///
+/// The box is divided into `resolution` cells along each axis and each cell
+/// is split into six tetrahedra (marching tetrahedra, the table-free variant
+/// of marching cubes). Points where `sdf` is negative count as inside. The
+/// surface is closed as long as the solid does not touch `bounds`.
+///
+/// # Panics
+/// Panics if `resolution` is zero.
+pub(crate) fn polygonize<F: Fn(Point3<f32>) -> f32>(
+    sdf: F,
+    bounds: &Aabb,
+    resolution: usize,
+) -> Vec<Polygon> {
+    assert!(resolution > 0, "resolution must be at least 1");
+
+    let n = resolution + 1;
+    let step = bounds.extents() / resolution as f32;
+    let position = |i: usize, j: usize, k: usize| {
+        bounds.min() + step.component_mul(&Vector3::new(i as f32, j as f32, k as f32))
+    };
+
+    let mut values = Vec::with_capacity(n * n * n);
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                values.push(sdf(position(i, j, k)));
+            }
+        }
+    }
+
+    // Triangles thinner than this come from the surface passing exactly
+    // through a grid point and are dropped
+    let min_area = f32::EPSILON * step.norm_squared();
+
+    let mut triangles = Vec::new();
+    for k in 0..resolution {
+        for j in 0..resolution {
+            for i in 0..resolution {
+                let corners: [(Point3<f32>, f32); 8] = std::array::from_fn(|c| {
+                    let (ci, cj, ck) = (i + (c & 1), j + (c >> 1 & 1), k + (c >> 2));
+                    (position(ci, cj, ck), values[ci + n * (cj + n * ck)])
+                });
+                for tetrahedron in &TETRAHEDRA {
+                    let tetrahedron = tetrahedron.map(|c| corners[c]);
+                    for triangle in polygonize_tetrahedron(&tetrahedron) {
+                        let normal =
+                            (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+                        if normal.norm() > min_area {
+                            triangles.push(Polygon::new(triangle.to_vec()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    triangles
+}
+
+/// Returns the surface triangles inside one tetrahedron, wound
+/// counter-clockwise when seen from outside the solid.
+fn polygonize_tetrahedron(corners: &[(Point3<f32>, f32); 4]) -> Vec<[Point3<f32>; 3]> {
+    let (inside, outside): (Vec<_>, Vec<_>) =
+        corners.iter().copied().partition(|(_, value)| *value < 0.0);
+
+    // Always interpolating from the inside corner makes neighboring
+    // tetrahedra compute bit-identical points on a shared edge
+    let crossing = |inner: &(Point3<f32>, f32), outer: &(Point3<f32>, f32)| {
+        let t = inner.1 / (inner.1 - outer.1);
+        inner.0 + (outer.0 - inner.0) * t
+    };
+
+    let triangles = match (inside.as_slice(), outside.as_slice()) {
+        ([a], [b, c, d]) => vec![[crossing(a, b), crossing(a, c), crossing(a, d)]],
+        ([b, c, d], [a]) => vec![[crossing(b, a), crossing(c, a), crossing(d, a)]],
+        ([a, b], [c, d]) => {
+            let (ac, ad, bd, bc) = (
+                crossing(a, c),
+                crossing(a, d),
+                crossing(b, d),
+                crossing(b, c),
+            );
+            vec![[ac, ad, bd], [ac, bd, bc]]
+        }
+        _ => return Vec::new(),
+    };
+
+    // Wind each triangle so its normal points from the inside corners
+    // towards the outside ones
+    let centroid = |points: &[(Point3<f32>, f32)]| {
+        points.iter().map(|(p, _)| p.coords).sum::<Vector3<f32>>() / points.len() as f32
+    };
+    let outward = centroid(&outside) - centroid(&inside);
+
+    triangles
+        .into_iter()
+        .map(|[p, q, r]| {
+            if (q - p).cross(&(r - p)).dot(&outward) < 0.0 {
+                [p, r, q]
+            } else {
+                [p, q, r]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn sphere(radius: f32) -> impl Fn(Point3<f32>) -> f32 {
+        move |p| p.coords.norm() - radius
+    }
+
+    fn unit_box(half_size: f32) -> Aabb {
+        Aabb::new(
+            Point3::new(-half_size, -half_size, -half_size),
+            Point3::new(half_size, half_size, half_size),
+        )
+    }
+
+    #[test]
+    fn sphere_surface_is_closed() {
+        let triangles = polygonize(sphere(1.0), &unit_box(1.5), 8);
+        assert!(!triangles.is_empty());
+
+        // Every directed edge must be matched by the reverse edge of a
+        // neighboring triangle
+        let key = |p: &Point3<f32>| p.coords.map(f32::to_bits);
+        let mut edges: HashMap<_, i32> = HashMap::new();
+        for triangle in &triangles {
+            let v = triangle.vertices();
+            for i in 0..3 {
+                let (a, b) = (key(&v[i]), key(&v[(i + 1) % 3]));
+                *edges.entry((a, b)).or_default() += 1;
+                *edges.entry((b, a)).or_default() -= 1;
+            }
+        }
+        assert!(edges.values().all(|&count| count == 0));
+    }
+
+    #[test]
+    fn sphere_triangles_face_outward() {
+        for triangle in polygonize(sphere(1.0), &unit_box(1.5), 6) {
+            let centroid = triangle.centroid();
+            assert!(triangle.plane().normal().dot(&centroid.coords) > 0.0);
+        }
+    }
+
+    #[test]
+    fn sdf_without_surface_yields_nothing() {
+        assert!(polygonize(|_| 1.0, &unit_box(1.0), 4).is_empty());
+        assert!(polygonize(|_| -1.0, &unit_box(1.0), 4).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "resolution must be at least 1")]
+    fn zero_resolution_panics() {
+        polygonize(sphere(1.0), &unit_box(1.5), 0);
+    }
+}
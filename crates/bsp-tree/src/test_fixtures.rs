@@ -0,0 +1,45 @@
+//! Shared polygon fixtures for the unit tests.
+
+use nalgebra::Point3;
+
+use crate::Polygon;
+
+/// The six faces of the axis-aligned box `[min, max]`, wound
+/// counter-clockwise from outside.
+pub(crate) fn cuboid(min: [f32; 3], max: [f32; 3]) -> Vec<Polygon> {
+    let corner = |i: usize| {
+        Point3::new(
+            if i & 1 != 0 { max[0] } else { min[0] },
+            if i & 2 != 0 { max[1] } else { min[1] },
+            if i & 4 != 0 { max[2] } else { min[2] },
+        )
+    };
+    [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ]
+    .iter()
+    .map(|f| Polygon::new(f.iter().map(|&i| corner(i)).collect()))
+    .collect()
+}
+
+/// The six faces of the cube [0, 2]^3, wound counter-clockwise from outside.
+pub(crate) fn cube() -> Vec<Polygon> {
+    cuboid([0.0; 3], [2.0; 3])
+}
+
+/// An `n` x `n` grid of unit cubes spaced 3 apart in the plane z = 0.
+pub(crate) fn cube_grid(n: usize) -> Vec<Polygon> {
+    let mut polygons = Vec::new();
+    for i in 0..n {
+        for j in 0..n {
+            let (x, y) = (3.0 * i as f32, 3.0 * j as f32);
+            polygons.extend(cuboid([x, y, 0.0], [x + 1.0, y + 1.0, 1.0]));
+        }
+    }
+    polygons
+}
@@ -1,13 +1,14 @@
 //! Triangle representation for BSP trees.
 
-use nalgebra::{Point3, Vector3};
+use nalgebra::{Point3, Vector2, Vector3};
 
-use crate::{Classification, Plane3D, PlaneSide};
+use crate::{Aabb, Classification, Plane3D, PlaneSide, PLANE_EPSILON};
 
 /// A triangle in 3D space, defined by three vertices.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Triangle {
     vertices: [Point3<f32>; 3],
+    uvs: Option<[Vector2<f32>; 3]>,
 }
 
 impl Triangle {
@@ -18,6 +19,7 @@ impl Triangle {
     pub fn new(a: Point3<f32>, b: Point3<f32>, c: Point3<f32>) -> Self {
         Self {
             vertices: [a, b, c],
+            uvs: None,
         }
     }
 
@@ -27,6 +29,22 @@ impl Triangle {
         &self.vertices
     }
 
+    /// Attaches texture coordinates, one per vertex.
+    ///
+    /// As for [`Polygon::with_uvs`](crate::Polygon::with_uvs), they do not
+    /// take part in equality.
+    pub fn with_uvs(mut self, uvs: [Vector2<f32>; 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+
+    /// Returns the texture coordinates, one per vertex, if the triangle has
+    /// any.
+    #[inline]
+    pub fn uvs(&self) -> Option<&[Vector2<f32>; 3]> {
+        self.uvs.as_ref()
+    }
+
     /// Computes the (unnormalized) normal vector of the triangle.
     ///
     /// The direction follows the right-hand rule based on vertex winding.
@@ -65,6 +83,81 @@ impl Triangle {
         Point3::from((a.coords + b.coords + c.coords) / 3.0)
     }
 
+    /// Computes the area of the triangle, regardless of winding.
+    pub fn area(&self) -> f32 {
+        self.normal().norm() / 2.0
+    }
+
+    /// Computes the bounding box of the triangle.
+    pub fn aabb(&self) -> Aabb {
+        let [a, b, c] = self.vertices;
+        Aabb::new(a, a).including(b).including(c)
+    }
+
+    /// Computes the barycentric coordinates of `point`, the weights of the
+    /// vertices `a`, `b` and `c` that sum to 1.
+    ///
+    /// `point` is assumed to lie on the triangle's plane; a point off the
+    /// plane gets the weights of its projection onto it. The weights lie in
+    /// [0, 1] for points inside the triangle. Returns `None` if the triangle
+    /// is degenerate (zero area).
+    ///
+    /// ```
+    /// use bsp_tree::Triangle;
+    /// use nalgebra::Point3;
+    ///
+    /// let triangle = Triangle::new(
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(2.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 2.0, 0.0),
+    /// );
+    /// assert_eq!(triangle.barycentric(Point3::new(1.0, 0.0, 0.0)), Some((0.5, 0.5, 0.0)));
+    /// assert_eq!(triangle.barycentric(Point3::new(0.0, 2.0, 0.0)), Some((0.0, 0.0, 1.0)));
+    /// ```
+    pub fn barycentric(&self, point: Point3<f32>) -> Option<(f32, f32, f32)> {
+        let [a, b, c] = &self.vertices;
+        let n = self.normal();
+        let norm_squared = n.norm_squared();
+        if norm_squared <= f32::EPSILON * f32::EPSILON {
+            return None;
+        }
+
+        // Each weight is the area of the sub-triangle opposite its vertex,
+        // signed by winding, over the whole area
+        let wa = (c - b).cross(&(point - b)).dot(&n) / norm_squared;
+        let wb = (a - c).cross(&(point - c)).dot(&n) / norm_squared;
+        Some((wa, wb, 1.0 - wa - wb))
+    }
+
+    /// Returns `true` if `point` lies on this triangle: within
+    /// [`PLANE_EPSILON`] of its plane and with all
+    /// [barycentric](Self::barycentric) weights in [0, 1].
+    ///
+    /// Always `false` for a degenerate triangle.
+    ///
+    /// ```
+    /// use bsp_tree::Triangle;
+    /// use nalgebra::Point3;
+    ///
+    /// let triangle = Triangle::new(
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(2.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 2.0, 0.0),
+    /// );
+    /// assert!(triangle.contains_point(Point3::new(0.5, 0.5, 0.0)));
+    /// assert!(triangle.contains_point(Point3::new(1.0, 1.0, 0.0)));
+    /// assert!(!triangle.contains_point(Point3::new(1.5, 1.5, 0.0)));
+    /// assert!(!triangle.contains_point(Point3::new(0.5, 0.5, 1.0)));
+    /// ```
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        let (Some((wa, wb, wc)), Some(normal)) = (self.barycentric(point), self.unit_normal())
+        else {
+            return false;
+        };
+        let distance = (point - self.vertices[0]).dot(&normal);
+        distance.abs() <= PLANE_EPSILON && [wa, wb, wc].iter().all(|w| (0.0..=1.0).contains(w))
+    }
+
     /// Classifies this triangle relative to a plane.
     ///
     /// Returns:
@@ -97,6 +190,12 @@ impl Triangle {
     }
 }
 
+impl PartialEq for Triangle {
+    fn eq(&self, other: &Self) -> bool {
+        self.vertices == other.vertices
+    }
+}
+
 impl From<Triangle> for Plane3D {
     fn from(triangle: Triangle) -> Self {
         triangle.plane()
@@ -1,4 +1,4 @@
-use bsp_tree::{BspTree, Polygon, Rectangle};
+use bsp_tree::{BspTree, BuildOptions, FirstPolygon, Polygon, Rectangle};
 use bsp_viz::{generate_cube_polygons, generate_rotated_cube, OrbitCamera, TreeNavigator};
 use macroquad::prelude::*;
 use nalgebra::{Point3, Rotation3, Unit, Vector3};
@@ -18,11 +18,10 @@ fn generate_complex_scene() -> Vec<Polygon> {
     polygons.extend(generate_cube_polygons(Point3::new(1.0, 0.0, 0.0), 0.8));
 
     // Floor polygon at y = -1
-    let floor = Rectangle::from_corners(
-        Point3::new(-1.5, -1.0, -1.5),
-        Point3::new(1.5, -1.0, -1.5),
-        Point3::new(1.5, -1.0, 1.5),
-        Point3::new(-1.5, -1.0, 1.5),
+    let floor = Rectangle::from_center(
+        Point3::new(0.0, -1.0, 0.0),
+        Vector3::new(1.5, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.5),
     );
     polygons.push(floor.into());
 
@@ -37,7 +36,9 @@ async fn main() {
     println!("Created {} polygons (2 cubes + 1 floor)", polygon_count);
 
     println!("Building BSP tree...");
-    let tree = BspTree::from_polygons(polygons);
+    // Record cut edges so the navigator can show where each node slices geometry
+    let options = BuildOptions::new().with_cut_edges(true);
+    let tree = BspTree::build_with_options(polygons, &FirstPolygon, &options);
     println!(
         "BSP tree built: {} polygons, depth {}",
         tree.polygon_count(),
@@ -79,7 +80,13 @@ async fn main() {
 
         navigator.draw_ui(&tree, 70.0);
 
-        draw_text("Drag mouse to rotate, scroll to zoom", 10.0, 155.0, 16.0, DARKGRAY);
+        draw_text(
+            "Drag mouse to rotate, scroll to zoom, WASD to pan, O for orthographic",
+            10.0,
+            155.0,
+            16.0,
+            DARKGRAY,
+        );
         draw_text(&format!("FPS: {}", get_fps()), 10.0, 175.0, 16.0, DARKGRAY);
 
         next_frame().await
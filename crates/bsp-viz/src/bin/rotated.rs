@@ -1,4 +1,4 @@
-use bsp_tree::{BspTree, Polygon};
+use bsp_tree::{BspTree, BuildOptions, FirstPolygon, Polygon};
 use bsp_viz::{generate_rotated_cube, OrbitCamera, TreeNavigator};
 use macroquad::prelude::*;
 use nalgebra::{Point3, Rotation3, Unit, Vector3};
@@ -69,7 +69,9 @@ async fn main() {
     println!("Created {} polygons", polygon_count);
 
     println!("Building BSP tree...");
-    let tree = BspTree::from_polygons(polygons);
+    // Record cut edges so the navigator can show where each node slices geometry
+    let options = BuildOptions::new().with_cut_edges(true);
+    let tree = BspTree::build_with_options(polygons, &FirstPolygon, &options);
     println!(
         "BSP tree built: {} polygons, depth {}",
         tree.polygon_count(),
@@ -111,7 +113,13 @@ async fn main() {
 
         navigator.draw_ui(&tree, 70.0);
 
-        draw_text("Drag mouse to rotate, scroll to zoom", 10.0, 155.0, 16.0, DARKGRAY);
+        draw_text(
+            "Drag mouse to rotate, scroll to zoom, WASD to pan, O for orthographic",
+            10.0,
+            155.0,
+            16.0,
+            DARKGRAY,
+        );
         draw_text(&format!("FPS: {}", get_fps()), 10.0, 175.0, 16.0, DARKGRAY);
 
         next_frame().await
@@ -2,7 +2,7 @@
 
 use std::hash::{Hash, Hasher};
 
-use bsp_tree::{BspVisitor, Plane3D, Polygon, Rectangle};
+use bsp_tree::{Aabb, BspVisitor, Frustum, Plane3D, Polygon, Rectangle};
 use macroquad::models::{draw_mesh, Mesh, Vertex};
 use macroquad::prelude::*;
 use nalgebra::{Point3, Rotation3, Vector3};
@@ -43,10 +43,17 @@ pub fn draw_polygon(polygon: &Polygon) {
 
     let color = polygon_color(polygon);
 
-    // Convert nalgebra points to macroquad Vertices
+    // Convert nalgebra points to macroquad Vertices, with the polygon's
+    // texture coordinates if it has any
+    let uv = |i: usize| {
+        polygon
+            .uvs()
+            .map_or(vec2(0.0, 0.0), |uvs| vec2(uvs[i].x, uvs[i].y))
+    };
     let mesh_vertices: Vec<Vertex> = verts
         .iter()
-        .map(|p| Vertex::new2(vec3(p.x, p.y, p.z), vec2(0.0, 0.0), color))
+        .enumerate()
+        .map(|(i, p)| Vertex::new2(vec3(p.x, p.y, p.z), uv(i), color))
         .collect();
 
     // Fan triangulation: vertex 0 connects to all edges
@@ -66,6 +73,26 @@ pub fn draw_polygon(polygon: &Polygon) {
     draw_mesh(&mesh);
 }
 
+/// Returns the edges of a polygon as a closed loop, the last edge leading
+/// back to the first vertex.
+pub fn outline_edges(polygon: &Polygon) -> Vec<(Vec3, Vec3)> {
+    polygon
+        .edges()
+        .map(|(a, b)| (vec3(a.x, a.y, a.z), vec3(b.x, b.y, b.z)))
+        .collect()
+}
+
+/// Draws a polygon's outline in its own color.
+///
+/// Drawn after the polygon, the outline covers the pixel gaps that show up
+/// along T-junctions between split fragments.
+pub fn draw_seam_outline(polygon: &Polygon) {
+    let color = polygon_color(polygon);
+    for (a, b) in outline_edges(polygon) {
+        draw_line_3d(a, b, color);
+    }
+}
+
 /// Visitor that renders polygons using macroquad's 3D drawing.
 pub struct RenderVisitor;
 
@@ -77,6 +104,19 @@ impl BspVisitor for RenderVisitor {
     }
 }
 
+/// Visitor that renders polygons like [`RenderVisitor`], outlining each in
+/// its own color to mask seams.
+pub struct SeamFillingRenderVisitor;
+
+impl BspVisitor for SeamFillingRenderVisitor {
+    fn visit(&mut self, polygons: &[Polygon]) {
+        for polygon in polygons {
+            draw_polygon(polygon);
+            draw_seam_outline(polygon);
+        }
+    }
+}
+
 /// Generates the 6 face polygons of an axis-aligned cube.
 pub fn generate_cube_polygons(center: Point3<f32>, size: f32) -> Vec<Polygon> {
     let half = size / 2.0;
@@ -176,6 +216,22 @@ pub fn generate_rotated_cube(
         .collect()
 }
 
+/// How an [`OrbitCamera`] projects the scene onto the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Perspective projection with a vertical field of view in radians.
+    Perspective { fovy: f32 },
+    /// Parallel projection showing `scale` world units vertically.
+    ///
+    /// Without perspective distortion, coplanar faces and parallel split
+    /// planes line up on screen.
+    Orthographic { scale: f32 },
+}
+
+/// Distance behind an orthographic camera's target of the eye point used for
+/// BSP traversal, standing in for a viewer at infinity.
+const ORTHOGRAPHIC_EYE_DISTANCE: f32 = 1.0e4;
+
 /// Simple orbit camera for 3D scene navigation.
 pub struct OrbitCamera {
     pub distance: f32,
@@ -188,6 +244,10 @@ pub struct OrbitCamera {
     pub min_distance: f32,
     /// Maximum distance from target
     pub max_distance: f32,
+    /// Distance the target moves per frame while a pan key is held
+    pub pan_speed: f32,
+    /// Perspective or orthographic projection
+    pub projection: Projection,
 }
 
 impl OrbitCamera {
@@ -201,6 +261,10 @@ impl OrbitCamera {
             zoom_speed: 5.0,
             min_distance: 10.0,
             max_distance: 200.0,
+            pan_speed: 0.5,
+            projection: Projection::Perspective {
+                fovy: 45.0_f32.to_radians(),
+            },
         }
     }
 
@@ -212,13 +276,78 @@ impl OrbitCamera {
         self
     }
 
+    /// Sets how far the target moves per frame while a pan key is held.
+    pub fn with_pan_speed(mut self, speed: f32) -> Self {
+        self.pan_speed = speed;
+        self
+    }
+
     /// Sets the camera target point.
     pub fn with_target(mut self, target: Vec3) -> Self {
         self.target = target;
         self
     }
 
-    /// Updates camera state from user input (mouse drag, scroll, arrow keys).
+    /// Sets the projection.
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Switches between perspective and orthographic projection, keeping
+    /// the visible height at the target distance.
+    pub fn toggle_projection(&mut self) {
+        self.projection = match self.projection {
+            Projection::Perspective { fovy } => Projection::Orthographic {
+                scale: 2.0 * self.distance * (fovy / 2.0).tan(),
+            },
+            Projection::Orthographic { scale } => Projection::Perspective {
+                fovy: 2.0 * (scale / (2.0 * self.distance)).atan(),
+            },
+        };
+    }
+
+    /// Frames the whole bounding box, keeping the current yaw and pitch.
+    ///
+    /// Targets the box center and backs off until the box's bounding sphere
+    /// fits inside the vertical field of view, or, for an orthographic
+    /// projection, scales the view to the sphere's diameter. The zoom limits
+    /// and speed are rescaled to the scene size. Assumes the window is at
+    /// least as wide as it is tall, so the vertical extent is the limiting
+    /// one.
+    pub fn fit(&mut self, aabb: &Aabb) {
+        let center = aabb.center();
+        let radius = (aabb.extents().norm() / 2.0).max(1e-3);
+
+        self.target = vec3(center.x, center.y, center.z);
+        match &mut self.projection {
+            Projection::Perspective { fovy } => self.distance = radius / (*fovy / 2.0).sin(),
+            Projection::Orthographic { scale } => {
+                // Far enough back for the whole sphere to be past the near plane
+                self.distance = 2.0 * radius;
+                *scale = 2.0 * radius;
+            }
+        }
+        // Well inside the bounding sphere, so the scene can be zoomed into
+        self.min_distance = radius / 10.0;
+        self.max_distance = self.distance * 4.0;
+        self.zoom_speed = radius / 4.0;
+        self.pan_speed = radius / 50.0;
+    }
+
+    /// Moves the target, and with it the camera, by `right` and `up` world
+    /// units along the screen's horizontal and vertical axes.
+    pub fn pan(&mut self, right: f32, up: f32) {
+        let forward = (self.target - self.position()).normalize();
+        let right_axis = forward.cross(Vec3::Y).normalize();
+        let up_axis = right_axis.cross(forward);
+        self.target += right_axis * right + up_axis * up;
+    }
+
+    /// Updates camera state from user input (mouse drag, scroll, arrow keys,
+    /// WASD to pan, `O` to toggle the projection).
+    ///
+    /// Zooming an orthographic view scales it along with the distance.
     pub fn update(&mut self) {
         // Mouse drag for rotation
         if is_mouse_button_down(MouseButton::Left) {
@@ -232,8 +361,34 @@ impl OrbitCamera {
 
         // Mouse wheel for zoom
         let scroll = mouse_wheel().1;
+        let previous = self.distance;
         self.distance -= scroll * self.zoom_speed;
         self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+        if let Projection::Orthographic { scale } = &mut self.projection {
+            *scale *= self.distance / previous;
+        }
+
+        if is_key_pressed(KeyCode::O) {
+            self.toggle_projection();
+        }
+
+        // WASD to pan the target across the screen
+        let mut pan = Vec2::ZERO;
+        if is_key_down(KeyCode::A) {
+            pan.x -= 1.0;
+        }
+        if is_key_down(KeyCode::D) {
+            pan.x += 1.0;
+        }
+        if is_key_down(KeyCode::W) {
+            pan.y += 1.0;
+        }
+        if is_key_down(KeyCode::S) {
+            pan.y -= 1.0;
+        }
+        if pan != Vec2::ZERO {
+            self.pan(pan.x * self.pan_speed, pan.y * self.pan_speed);
+        }
 
         // Arrow keys for rotation
         if is_key_down(KeyCode::Left) {
@@ -260,17 +415,170 @@ impl OrbitCamera {
 
     /// Converts to macroquad's Camera3D for rendering.
     pub fn to_camera3d(&self) -> Camera3D {
+        // macroquad uses `fovy` as the view height for orthographic cameras
+        let (fovy, projection) = match self.projection {
+            Projection::Perspective { fovy } => (fovy, macroquad::camera::Projection::Perspective),
+            Projection::Orthographic { scale } => {
+                (scale, macroquad::camera::Projection::Orthographics)
+            }
+        };
         Camera3D {
             position: self.position(),
             up: vec3(0.0, 1.0, 0.0),
             target: self.target,
+            fovy,
+            projection,
             ..Default::default()
         }
     }
 
+    /// Returns the view frustum of [`to_camera3d`](Self::to_camera3d) for a
+    /// viewport with the given width/height ratio, for culled traversal.
+    pub fn frustum(&self, aspect: f32) -> Frustum {
+        let camera = self.to_camera3d();
+        let point = |v: Vec3| Point3::new(v.x, v.y, v.z);
+        let (position, target) = (point(camera.position), point(camera.target));
+        let up = Vector3::new(camera.up.x, camera.up.y, camera.up.z);
+        let (near, far) = (camera.z_near, camera.z_far);
+        match self.projection {
+            Projection::Perspective { fovy } => {
+                Frustum::from_camera(position, target, up, fovy, aspect, near, far)
+            }
+            Projection::Orthographic { scale } => {
+                Frustum::from_orthographic(position, target, up, scale, aspect, near, far)
+            }
+        }
+    }
+
     /// Returns the eye point as a nalgebra Point3 for BSP traversal.
+    ///
+    /// An orthographic view looks at the scene from infinitely far along the
+    /// view axis, so the eye point is placed far behind the target there
+    /// instead of at the camera position.
     pub fn eye_point(&self) -> Point3<f32> {
-        let pos = self.position();
+        let pos = match self.projection {
+            Projection::Perspective { .. } => self.position(),
+            Projection::Orthographic { .. } => {
+                let back = (self.position() - self.target).normalize();
+                self.target + back * ORTHOGRAPHIC_EYE_DISTANCE
+            }
+        };
         Point3::new(pos.x, pos.y, pos.z)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outline_edges_close_the_loop() {
+        let polygon = Polygon::new(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        let edges = outline_edges(&polygon);
+
+        assert_eq!(edges.len(), 4);
+        for (i, (_, end)) in edges.iter().enumerate() {
+            assert_eq!(*end, edges[(i + 1) % edges.len()].0);
+        }
+        assert_eq!(edges[0].0, vec3(0.0, 0.0, 0.0));
+        assert_eq!(edges[3], (vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn frustum_contains_target() {
+        let camera = OrbitCamera::new(20.0, 0.7, -0.4);
+        let frustum = camera.frustum(16.0 / 9.0);
+
+        assert!(frustum.contains_point(Point3::origin()));
+        assert!(!frustum.contains_point(camera.eye_point() * 2.0));
+    }
+
+    #[test]
+    fn fit_frames_all_corners() {
+        let aabb = Aabb::new(Point3::new(-3.0, 1.0, 10.0), Point3::new(5.0, 2.0, 14.0));
+        let mut camera = OrbitCamera::new(1.0, 0.7, -0.4);
+        camera.fit(&aabb);
+
+        let eye = camera.position();
+        let forward = (camera.target - eye).normalize();
+        let (min, max) = (aabb.min(), aabb.max());
+        for i in 0..8 {
+            let corner = vec3(
+                if i & 1 != 0 { max.x } else { min.x },
+                if i & 2 != 0 { max.y } else { min.y },
+                if i & 4 != 0 { max.z } else { min.z },
+            );
+            let angle = forward.angle_between(corner - eye);
+            let Projection::Perspective { fovy } = camera.projection else {
+                unreachable!()
+            };
+            assert!(angle <= fovy / 2.0, "corner {corner} outside view");
+        }
+        assert!(camera.min_distance <= camera.distance);
+        assert!(camera.distance <= camera.max_distance);
+    }
+
+    #[test]
+    fn orthographic_fit_and_frustum_cover_the_box() {
+        let aabb = Aabb::new(Point3::new(-3.0, 1.0, 10.0), Point3::new(5.0, 2.0, 14.0));
+        let mut camera = OrbitCamera::new(1.0, 0.7, -0.4)
+            .with_projection(Projection::Orthographic { scale: 1.0 });
+        camera.fit(&aabb);
+
+        let frustum = camera.frustum(1.0);
+        let (min, max) = (aabb.min(), aabb.max());
+        for i in 0..8 {
+            let corner = Point3::new(
+                if i & 1 != 0 { max.x } else { min.x },
+                if i & 2 != 0 { max.y } else { min.y },
+                if i & 4 != 0 { max.z } else { min.z },
+            );
+            let visible = frustum.contains_point(corner);
+            assert!(visible, "corner {corner} outside view");
+        }
+        // The traversal eye is far behind the camera, on the view axis
+        assert!((camera.eye_point() - aabb.center()).norm() > 1000.0);
+    }
+
+    #[test]
+    fn pan_moves_along_the_screen_axes() {
+        // Looking down -Z, so screen right is +X and screen up is +Y
+        let mut camera = OrbitCamera::new(20.0, 0.0, 0.0);
+        camera.pan(2.0, -1.0);
+        assert!(camera.target.abs_diff_eq(vec3(2.0, -1.0, 0.0), 1e-5));
+        assert!(camera.position().abs_diff_eq(vec3(2.0, -1.0, 20.0), 1e-5));
+
+        // Panning keeps the view direction and distance
+        let mut camera = OrbitCamera::new(20.0, 0.7, -0.4);
+        let before = camera.position() - camera.target;
+        camera.pan(3.0, 4.0);
+        assert!((camera.target.length() - 5.0).abs() < 1e-4);
+        assert!((camera.position() - camera.target).abs_diff_eq(before, 1e-4));
+        assert!(camera.target.dot(before).abs() < 1e-3);
+    }
+
+    #[test]
+    fn toggle_projection_keeps_the_view_height() {
+        let mut camera = OrbitCamera::new(20.0, 0.7, -0.4);
+        let original = camera.projection;
+
+        camera.toggle_projection();
+        let Projection::Orthographic { scale } = camera.projection else {
+            panic!("expected an orthographic projection");
+        };
+        assert!((scale - 40.0 * 22.5_f32.to_radians().tan()).abs() < 1e-4);
+
+        camera.toggle_projection();
+        let (Projection::Perspective { fovy: a }, Projection::Perspective { fovy: b }) =
+            (original, camera.projection)
+        else {
+            panic!("expected a perspective projection");
+        };
+        assert!((a - b).abs() < 1e-5);
+    }
+}
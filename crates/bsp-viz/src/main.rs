@@ -1,4 +1,4 @@
-use bsp_tree::{BspTree, Polygon};
+use bsp_tree::{Aabb, BspTree, BuildOptions, FirstPolygon, Polygon};
 use bsp_viz::{generate_cube_polygons, OrbitCamera, TreeNavigator};
 use macroquad::prelude::*;
 use nalgebra::Point3;
@@ -37,9 +37,12 @@ async fn main() {
     let polygons = generate_random_cubes(42);
     let polygon_count = polygons.len();
     println!("Created {} polygons", polygon_count);
+    let bounds = Aabb::from_points(polygons.iter().flat_map(|p| p.vertices()));
 
     println!("Building BSP tree...");
-    let tree = BspTree::from_polygons(polygons);
+    // Record cut edges so the navigator can show where each node slices geometry
+    let options = BuildOptions::new().with_cut_edges(true);
+    let tree = BspTree::build_with_options(polygons, &FirstPolygon, &options);
     println!(
         "BSP tree built: {} polygons, depth {}",
         tree.polygon_count(),
@@ -47,6 +50,9 @@ async fn main() {
     );
 
     let mut camera = OrbitCamera::new(80.0, 0.0, 0.3);
+    if let Some(bounds) = bounds {
+        camera.fit(&bounds);
+    }
     let mut navigator = TreeNavigator::new();
 
     loop {
@@ -85,7 +91,13 @@ async fn main() {
         // Navigator UI
         navigator.draw_ui(&tree, 70.0);
 
-        draw_text("Drag mouse to rotate, scroll to zoom", 10.0, 155.0, 16.0, DARKGRAY);
+        draw_text(
+            "Drag mouse to rotate, scroll to zoom, WASD to pan, O for orthographic",
+            10.0,
+            155.0,
+            16.0,
+            DARKGRAY,
+        );
         draw_text(&format!("FPS: {}", get_fps()), 10.0, 175.0, 16.0, DARKGRAY);
 
         next_frame().await
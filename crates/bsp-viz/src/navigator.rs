@@ -1,21 +1,21 @@
 //! BSP tree navigation utilities for interactive visualization.
 
-use bsp_tree::{BspNode, BspTree, PlaneSide};
+use bsp_tree::{BspNode, BspTree, NodeId, PlaneSide, Polygon};
 use macroquad::prelude::*;
 use nalgebra::Point3;
 
-use crate::draw_polygon;
+use crate::{draw_polygon, draw_seam_outline, outline_edges};
 
-/// Direction taken at each node in the navigation path.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Direction {
-    Front,
-    Back,
-}
+pub use bsp_tree::Direction;
 
 /// Interactive BSP tree navigator for exploring tree structure.
 pub struct TreeNavigator {
     path: Vec<Direction>,
+    fill_seams: bool,
+    wireframe: bool,
+    /// The unique edges of the subtree at `edges_path`, for the wireframe.
+    edges: Vec<(Point3<f32>, Point3<f32>)>,
+    edges_path: Option<Vec<Direction>>,
 }
 
 impl Default for TreeNavigator {
@@ -27,7 +27,13 @@ impl Default for TreeNavigator {
 impl TreeNavigator {
     /// Creates a new navigator starting at the root.
     pub fn new() -> Self {
-        Self { path: Vec::new() }
+        Self {
+            path: Vec::new(),
+            fill_seams: false,
+            wireframe: false,
+            edges: Vec::new(),
+            edges_path: None,
+        }
     }
 
     /// Returns the current navigation path.
@@ -35,6 +41,26 @@ impl TreeNavigator {
         &self.path
     }
 
+    /// Returns whether polygons are outlined in their own color to mask seams.
+    pub fn fill_seams(&self) -> bool {
+        self.fill_seams
+    }
+
+    /// Sets whether polygons are outlined in their own color to mask seams.
+    pub fn set_fill_seams(&mut self, fill_seams: bool) {
+        self.fill_seams = fill_seams;
+    }
+
+    /// Returns whether the subtree is drawn as edges instead of filled faces.
+    pub fn wireframe(&self) -> bool {
+        self.wireframe
+    }
+
+    /// Sets whether the subtree is drawn as edges instead of filled faces.
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        self.wireframe = wireframe;
+    }
+
     /// Returns the current depth in the tree.
     pub fn depth(&self) -> usize {
         self.path.len()
@@ -72,7 +98,19 @@ impl TreeNavigator {
         self.path.clear();
     }
 
-    /// Handles keyboard input for navigation.
+    /// Jumps to the node with the given id. Returns true if the node exists.
+    pub fn go_to(&mut self, tree: &BspTree, id: NodeId) -> bool {
+        match tree.path_to(id) {
+            Some(path) => {
+                self.path = path;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Handles keyboard input for navigation, `G` to toggle seam filling and
+    /// `E` to toggle the wireframe.
     /// Returns true if navigation state changed.
     pub fn update(&mut self, tree: &BspTree) -> bool {
         let mut changed = false;
@@ -92,6 +130,12 @@ impl TreeNavigator {
                 changed = true;
             }
         }
+        if is_key_pressed(KeyCode::G) {
+            self.fill_seams = !self.fill_seams;
+        }
+        if is_key_pressed(KeyCode::E) {
+            self.wireframe = !self.wireframe;
+        }
 
         changed
     }
@@ -101,13 +145,46 @@ impl TreeNavigator {
         tree.root().and_then(|root| get_node_at_path(root, &self.path))
     }
 
+    /// Returns the current subtree as a standalone tree, to isolate a branch.
+    pub fn isolated(&self, tree: &BspTree) -> BspTree {
+        tree.with_root_at(&self.path)
+    }
+
     /// Renders only the polygons in the current subtree with proper depth ordering.
-    pub fn render(&self, tree: &BspTree, eye: Point3<f32>) {
+    ///
+    /// In wireframe mode each edge is drawn once instead, so edges shared by
+    /// split fragments are not doubled. The edges are collected once per
+    /// selected node and reused until the selection changes. The current
+    /// node's splitter polygon and the edges its plane cut, if recorded, are
+    /// drawn on top.
+    pub fn render(&mut self, tree: &BspTree, eye: Point3<f32>) {
         if let Some(node) = self.current_node(tree) {
-            render_node_back_to_front(node, eye);
+            if self.wireframe {
+                for &(a, b) in self.unique_edges(tree) {
+                    draw_line_3d(vec3(a.x, a.y, a.z), vec3(b.x, b.y, b.z), LIGHTGRAY);
+                }
+            } else {
+                render_node_back_to_front(node, eye, self.fill_seams);
+            }
+            if let Some(splitter) = node.splitter() {
+                draw_polygon_outline(splitter, WHITE);
+            }
+            for &(a, b) in node.cut_edges() {
+                draw_line_3d(vec3(a.x, a.y, a.z), vec3(b.x, b.y, b.z), MAGENTA);
+            }
         }
     }
 
+    /// Returns the unique edges of the current subtree, collecting them only
+    /// if the selection changed since the last call.
+    fn unique_edges(&mut self, tree: &BspTree) -> &[(Point3<f32>, Point3<f32>)] {
+        if self.edges_path.as_ref() != Some(&self.path) {
+            self.edges = self.isolated(tree).collect_unique_edges();
+            self.edges_path = Some(self.path.clone());
+        }
+        &self.edges
+    }
+
     /// Draws the navigation UI overlay.
     pub fn draw_ui(&self, tree: &BspTree, y_offset: f32) {
         let (node_polygons, has_front, has_back, is_leaf) = if let Some(node) = self.current_node(tree) {
@@ -162,7 +239,11 @@ impl TreeNavigator {
             if is_leaf { ORANGE } else { GREEN },
         );
         draw_text(
-            "[P]arent | [R]oot",
+            &format!(
+                "[P]arent | [R]oot | [G]ap fill: {} | [E]dges: {}",
+                if self.fill_seams { "on" } else { "off" },
+                if self.wireframe { "on" } else { "off" }
+            ),
             10.0,
             y_offset + 60.0,
             16.0,
@@ -183,33 +264,54 @@ fn get_node_at_path<'a>(root: &'a BspNode, path: &[Direction]) -> Option<&'a Bsp
     Some(current)
 }
 
+/// Draws the edges of a polygon as 3D lines.
+fn draw_polygon_outline(polygon: &Polygon, color: Color) {
+    for (a, b) in outline_edges(polygon) {
+        draw_line_3d(a, b, color);
+    }
+}
+
+/// Draws the polygons stored at a node.
+///
+/// A leaf built with a depth limit or leaf polygon budget can hold polygons
+/// that are not coplanar, so a leaf's polygons are drawn farthest first.
+fn draw_node_polygons(node: &BspNode, eye: Point3<f32>, fill_seams: bool) {
+    let mut polygons: Vec<&Polygon> = node.polygons().collect();
+    if node.is_leaf() {
+        let distance = |p: &Polygon| nalgebra::distance_squared(&p.centroid(), &eye);
+        polygons.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+    }
+    for polygon in polygons {
+        draw_polygon(polygon);
+        if fill_seams {
+            draw_seam_outline(polygon);
+        }
+    }
+}
+
 /// Recursively renders a node's subtree with back-to-front ordering.
-fn render_node_back_to_front(node: &BspNode, eye: Point3<f32>) {
+fn render_node_back_to_front(node: &BspNode, eye: Point3<f32>, fill_seams: bool) {
     let side = node.plane().classify_point(eye);
 
     match side {
         PlaneSide::Front | PlaneSide::OnPlane => {
             // Eye is in front: render back, then coplanar, then front
             if let Some(back) = node.back() {
-                render_node_back_to_front(back, eye);
-            }
-            for polygon in node.all_coplanar() {
-                draw_polygon(polygon);
+                render_node_back_to_front(back, eye, fill_seams);
             }
+            draw_node_polygons(node, eye, fill_seams);
             if let Some(front) = node.front() {
-                render_node_back_to_front(front, eye);
+                render_node_back_to_front(front, eye, fill_seams);
             }
         }
         PlaneSide::Back => {
             // Eye is in back: render front, then coplanar, then back
             if let Some(front) = node.front() {
-                render_node_back_to_front(front, eye);
-            }
-            for polygon in node.all_coplanar() {
-                draw_polygon(polygon);
+                render_node_back_to_front(front, eye, fill_seams);
             }
+            draw_node_polygons(node, eye, fill_seams);
             if let Some(back) = node.back() {
-                render_node_back_to_front(back, eye);
+                render_node_back_to_front(back, eye, fill_seams);
             }
         }
     }